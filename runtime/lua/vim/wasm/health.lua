@@ -0,0 +1,68 @@
+local M = {}
+local health = require('vim.health')
+
+local function format_size(bytes)
+  if bytes >= 1024 * 1024 then
+    return ('%.1f MiB'):format(bytes / 1024 / 1024)
+  end
+  return ('%d KiB'):format(math.ceil(bytes / 1024))
+end
+
+local function check_runtime(info)
+  health.start('WASM runtime')
+  health.info(('Wasmtime version: %s'):format(info.wasmtime_version))
+  health.info(('Enabled proposals: %s'):format(table.concat(info.proposals, ', ')))
+  health.info(('nvim:api WIT package: %s (API level %d)'):format(info.api_version, info.api_level))
+  if info.cache.enabled then
+    health.ok('Compiled plugins are cached')
+  else
+    health.info('Compiled plugins are not cached, precompile them with nvim-wasm-precompile')
+  end
+end
+
+local function check_tools()
+  health.start('WASM tools')
+  if vim.fn.executable('wasm-tools') == 1 then
+    local version = vim.fn.system({ 'wasm-tools', '--version' })
+    health.ok(('wasm-tools: %s'):format(vim.trim(version)))
+  else
+    health.warn('wasm-tools not found', {
+      'Core modules are wrapped in components when they are loaded.',
+      'Install wasm-tools to build components: https://github.com/bytecodealliance/wasm-tools',
+    })
+  end
+end
+
+local function check_plugins(info)
+  health.start('WASM plugins')
+  if #info.instances == 0 then
+    health.info('No plugins loaded')
+    return
+  end
+  for _, instance in ipairs(info.instances) do
+    local msg = ('Instance %d: %s, %s of memory'):format(
+      instance.id,
+      instance.source,
+      format_size(instance.memory)
+    )
+    if instance.api_version == nil or instance.api_version == info.api_version then
+      health.ok(msg)
+    else
+      health.warn(('%s, built against nvim:api@%s'):format(msg, instance.api_version), {
+        ('Rebuild the plugin against nvim:api@%s to use the API functions added since.'):format(
+          info.api_version
+        ),
+      })
+    end
+  end
+end
+
+--- Performs a healthcheck for WASM plugins
+function M.check()
+  local info = vim.api.nvim_wasm_get_runtime_info()
+  check_runtime(info)
+  check_tools()
+  check_plugins(info)
+end
+
+return M
@@ -0,0 +1,38 @@
+local M = {}
+
+--- Adds the calls `calls`, from function names to calls, to the report `lines`, slowest first.
+local function add_calls(lines, title, calls)
+  local names = vim.tbl_keys(calls)
+  if #names == 0 then
+    return
+  end
+  table.sort(names, function(a, b)
+    return calls[a].time > calls[b].time
+  end)
+  table.insert(lines, '  ' .. title)
+  table.insert(lines, '    count   total (ms)  function')
+  for _, name in ipairs(names) do
+    local call = calls[name]
+    table.insert(lines, ('    %5d  %11.3f  %s'):format(call.count, call.time / 1e6, name))
+  end
+end
+
+--- Returns the lines of the report of the profiles of WASM plugins.
+---
+---@param profiles table[] Profiles returned by |nvim_wasm_profile()|
+---@return string[]
+function M.report(profiles)
+  local lines = {}
+  for _, profile in ipairs(profiles) do
+    local title = ('Instance %d: %s'):format(profile.id, profile.source)
+    if profile.fuel then
+      title = ('%s, %d fuel consumed'):format(title, profile.fuel)
+    end
+    table.insert(lines, title)
+    add_calls(lines, 'Exported functions:', profile.exports)
+    add_calls(lines, 'API calls:', profile.api_calls)
+  end
+  return lines
+end
+
+return M
@@ -19,6 +19,68 @@ vim.api.nvim_create_user_command('InspectTree', function(cmd)
   end
 end, { desc = 'Inspect treesitter language tree for buffer', count = true })
 
+vim.api.nvim_create_user_command('WasmTrust', function(cmd)
+  local opts, file = { action = 'allow' }, cmd.args
+  local opt, rest = cmd.args:match('^%+%+(%S+)%s*(.*)$')
+  local capabilities = opt and opt:match('^allow=(.+)$')
+  if opt == 'deny' or opt == 'remove' then
+    opts.action, file = opt, rest
+  elseif capabilities then
+    opts.capabilities, file = vim.split(capabilities, ','), rest
+  elseif opt then
+    vim.notify(('Invalid argument: ++%s'):format(opt), vim.log.levels.ERROR)
+    return
+  end
+  local ok, err = pcall(vim.api.nvim_wasm_trust, file, opts)
+  if not ok then
+    vim.notify(err, vim.log.levels.ERROR)
+    return
+  end
+  local path = vim.fn.fnamemodify(file, ':p')
+  if opts.action == 'allow' then
+    vim.notify(('Allowed "%s" in WASM trust database.'):format(path))
+  elseif opts.action == 'deny' then
+    vim.notify(('Denied "%s" in WASM trust database.'):format(path))
+  else
+    vim.notify(('Removed "%s" from WASM trust database.'):format(path))
+  end
+end, { desc = 'Manage the trust database of WASM plugins', nargs = '+', complete = 'file' })
+
+vim.api.nvim_create_user_command('WasmProfile', function(cmd)
+  local action, file = cmd.fargs[1], cmd.fargs[2]
+  if file and action ~= 'dump' then
+    vim.notify(('Trailing characters: %s'):format(file), vim.log.levels.ERROR)
+    return
+  end
+  local ok, profiles = pcall(vim.api.nvim_wasm_profile, action)
+  if not ok then
+    vim.notify(profiles, vim.log.levels.ERROR)
+    return
+  elseif action ~= 'dump' then
+    return
+  elseif #profiles == 0 then
+    vim.notify('No WASM plugins are loaded')
+    return
+  end
+  local lines = require('vim.wasm.profile').report(profiles)
+  if file then
+    vim.fn.writefile(lines, file)
+  else
+    vim.api.nvim_echo({ { table.concat(lines, '\n') } }, true, {})
+  end
+end, {
+  desc = 'Profile the calls to and from WASM plugins',
+  nargs = '+',
+  complete = function(arglead, cmdline)
+    if cmdline:match('^%S+%s+%S*$') then
+      return vim.tbl_filter(function(action)
+        return vim.startswith(action, arglead)
+      end, { 'dump', 'start', 'stop' })
+    end
+    return vim.fn.getcompletion(arglead, 'file')
+  end,
+})
+
 -- TODO: use vim.region() when it lands... #13896 #16843
 local function get_visual_selection()
   local save_a = vim.fn.getreginfo('a')
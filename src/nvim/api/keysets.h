@@ -17,6 +17,37 @@ typedef struct {
   Object _on_spell_nav;
 } Dict(set_decoration_provider);
 
+typedef struct {
+  Object on_start;
+  Object on_buf;
+  Object on_win;
+  Object on_line;
+  Object on_end;
+} Dict(wasm_decoration_provider);
+
+typedef struct {
+  Object on_lines;
+  Object on_bytes;
+  Object on_changedtick;
+  Object on_detach;
+  Object utf_sizes;
+  Object preview;
+} Dict(wasm_buf_attach);
+
+typedef struct {
+  Object name;
+} Dict(wasm_load_bytes);
+
+typedef struct {
+  Object level;
+  Object notify;
+} Dict(wasm_set_log);
+
+typedef struct {
+  Object action;
+  Object capabilities;
+} Dict(wasm_trust);
+
 typedef struct {
   Object id;
   Object end_line;
@@ -48,6 +48,10 @@ typedef enum {
 /// Internal call from Lua code
 #define LUA_INTERNAL_CALL (VIML_INTERNAL_CALL + 1)
 
+/// Internal call from the WASM instance with ID 0. Each instance has its own pseudo-channel, see
+/// wasm_channel_id().
+#define WASM_CHANNEL_ID_BASE (INTERNAL_CALL_MASK + ((uint64_t)1 << 32))
+
 static inline bool is_internal_call(uint64_t channel_id)
   REAL_FATTR_ALWAYS_INLINE REAL_FATTR_CONST;
 
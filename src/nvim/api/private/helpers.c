@@ -1019,6 +1019,7 @@ sctx_T api_set_sctx(uint64_t channel_id)
     current_sctx.sc_sid =
       channel_id == LUA_INTERNAL_CALL ? SID_LUA : SID_API_CLIENT;
     current_sctx.sc_lnum = 0;
+    current_sctx.sc_chan = channel_id;
   }
   return old_current_sctx;
 }
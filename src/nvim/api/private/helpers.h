@@ -193,6 +193,7 @@ typedef struct {
     current_sctx.sc_sid = \
       (channel_id) == LUA_INTERNAL_CALL ? SID_LUA : SID_API_CLIENT; \
     current_sctx.sc_lnum = 0; \
+    current_sctx.sc_chan = (channel_id); \
     current_channel_id = channel_id; \
     code; \
     current_channel_id = save_channel_id; \
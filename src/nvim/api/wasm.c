@@ -1,28 +1,496 @@
+#include <assert.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include "nvim/api/extmark.h"
+#include "nvim/api/keysets.h"
 #include "nvim/api/private/defs.h"
 #include "nvim/api/private/helpers.h"
+#include "nvim/api/private/validate.h"
+#include "nvim/api/wasm.h"
+#include "nvim/buffer_defs.h"
+#include "nvim/buffer_updates.h"
+#include "nvim/decoration.h"
+#include "nvim/decoration_provider.h"
+#include "nvim/drawscreen.h"
+#include "nvim/garray.h"
+#include "nvim/highlight_group.h"
+#include "nvim/memory.h"
+#include "nvim/pos.h"
 #include "nvim/rust/nvim-wasm/include/wasm-rs.h"
+#include "nvim/wasm/executor.h"
+#include "nvim/window.h"
 
 #ifdef INCLUDE_GENERATED_DECLARATIONS
-# include "api/autocmd.c.generated.h"
+# include "api/wasm.c.generated.h"
 #endif
 
 Integer nvim_wasm_load(String file, Error *error) FUNC_API_SINCE(99)
 {
-  const char *errmsg = NULL;
-  int32_t instance_id = wasm_load_file(file.data, &errmsg);
-  if (instance_id < 0) {
-    api_set_error(error, kErrorTypeException, "%s", errmsg);
+  return wasm_load(file.data, true, error);
+}
+
+/// Loads a WASM plugin from memory, like |nvim_wasm_load()| loads a file.
+///
+/// This lets Lua plugins embed or generate WASM plugins. The plugin can be a component or a core
+/// module, in the binary or in the text format (WAT). Precompiled plugins are native code, which
+/// is only loaded from files allowed with |:WasmTrust|, so they are refused.
+///
+/// @param blob   The plugin, e.g. read with |readblob()|
+/// @param opts   Optional parameters.
+///             - name: Name of the plugin, shown in error messages and by |:verbose|. It also
+///               names the log file and the store of the plugin, unless the plugin declares
+///               its name in a `nvim-plugin-name` custom section. Plugins without a name are
+///               shown as "[bytes]", and have neither a log file nor a store. Plugins loaded
+///               by WASM plugins cannot have a name, and a name used by a loaded plugin from
+///               a file is refused.
+/// @param[out] err Error details, if any
+/// @return The instance ID
+Integer nvim_wasm_load_bytes(String blob, Dict(wasm_load_bytes) *opts, Error *err)
+  FUNC_API_SINCE(99)
+{
+  const char *name = NULL;
+  if (HAS_KEY(opts->name)) {
+    VALIDATE_T("name", kObjectTypeString, opts->name.type, {
+      return -1;
+    });
+    name = opts->name.data.string.data;
+  }
+  return wasm_load_blob(blob, name, true, err);
+}
+
+/// Manages the trust database of WASM plugins, see |:WasmTrust|.
+///
+/// Components are identified by their SHA-256 hash. Denied components are never loaded, and
+/// untrusted components found by Nvim rather than loaded by the user prompt before loading.
+/// WASM plugins cannot change the trust database.
+///
+/// @param file   The WASM file.
+/// @param opts   Optional parameters.
+///             - action: "allow" to trust the current contents of {file} (default), "deny" to
+///               never load them, or "remove" to remove {file} from the trust database.
+///             - capabilities: List of capabilities granted to {file}, only with "allow".
+///               "process" lets it import the `nvim:api/process` interface.
+/// @param[out] err Error details, if any
+void nvim_wasm_trust(String file, Dict(wasm_trust) *opts, Error *err)
+  FUNC_API_SINCE(99)
+{
+  const char *action = "allow";
+  if (HAS_KEY(opts->action)) {
+    VALIDATE_T("action", kObjectTypeString, opts->action.type, {
+      return;
+    });
+    action = opts->action.data.string.data;
+    VALIDATE_S(strequal(action, "allow") || strequal(action, "deny")
+               || strequal(action, "remove"), "action", action, {
+      return;
+    });
+  }
+
+  garray_T capabilities;
+  ga_init(&capabilities, 1, 32);
+  if (HAS_KEY(opts->capabilities)) {
+    VALIDATE_T("capabilities", kObjectTypeArray, opts->capabilities.type, {
+      return;
+    });
+    VALIDATE(strequal(action, "allow"), "%s", "'capabilities' requires action \"allow\"", {
+      return;
+    });
+    Array array = opts->capabilities.data.array;
+    for (size_t i = 0; i < array.size; i++) {
+      VALIDATE_T("capability", kObjectTypeString, array.items[i].type, {
+        ga_clear(&capabilities);
+        return;
+      });
+      if (i > 0) {
+        ga_append(&capabilities, ',');
+      }
+      ga_concat(&capabilities, array.items[i].data.string.data);
+    }
+  }
+  ga_append(&capabilities, NUL);
+  wasm_trust(file.data, action, capabilities.ga_data, err);
+  ga_clear(&capabilities);
+}
+
+/// Sets how the entries logged by WASM plugins with the `nvim:api/log` interface are handled.
+///
+/// Entries are written to `stdpath('log')/wasm/<plugin>.log`, where `<plugin>` is the name of the
+/// plugin: the name it declares in its `nvim-plugin-name` custom section, or else the name of its
+/// file without extension.
+///
+/// @param opts   Optional parameters. Settings not given are left unchanged.
+///             - level: Level from |vim.log.levels| below which entries are dropped. Defaults to
+///               `vim.log.levels.WARN`. `vim.log.levels.OFF` drops all entries.
+///             - notify: Also show warnings and errors with |vim.notify()|. Defaults to false.
+/// @param[out] err Error details, if any
+void nvim_wasm_set_log(Dict(wasm_set_log) *opts, Error *err)
+  FUNC_API_SINCE(99)
+{
+  if (HAS_KEY(opts->level)) {
+    VALIDATE_T("level", kObjectTypeInteger, opts->level.type, {
+      return;
+    });
+    Integer level = opts->level.data.integer;
+    VALIDATE_RANGE(level >= 0 && level <= 5, "level", {
+      return;
+    });
+  }
+  if (HAS_KEY(opts->notify)) {
+    VALIDATE_T("notify", kObjectTypeBoolean, opts->notify.type, {
+      return;
+    });
+  }
+
+  if (HAS_KEY(opts->level)) {
+    wasm_set_log_level(opts->level.data.integer);
+  }
+  if (HAS_KEY(opts->notify)) {
+    wasm_set_log_notify(opts->notify.data.boolean);
+  }
+}
+
+/// Gets information about the WASM runtime and the loaded plugins, e.g. to check why a plugin
+/// fails to load. See |:checkhealth| vim.wasm.
+///
+/// @return Dictionary with these keys:
+///       - wasmtime_version: Version of Wasmtime, the WASM runtime.
+///       - proposals: WebAssembly proposals enabled besides WebAssembly 2.0.
+///       - api_version: Version of the `nvim:api` WIT package provided to plugins.
+///       - api_level: API level of the `nvim:api` package, see |api-level|.
+///       - cache: Status of the cache of compiled plugins. "enabled" is false: plugins are
+///         compiled when loaded, unless precompiled.
+///       - instances: List of the loaded plugins, each with these keys:
+///         - id: Instance ID.
+///         - source: Where the plugin comes from, e.g. "file plugin.wasm".
+///         - memory: Size of the linear memories of the plugin, in bytes.
+///         - api_version: Version of the `nvim:api` package imported by the plugin, if any.
+Dictionary nvim_wasm_get_runtime_info(void)
+  FUNC_API_SINCE(99)
+{
+  return wasm_get_runtime_info();
+}
+
+/// Profiles the calls to and from WASM plugins, see |:WasmProfile|.
+///
+/// Records the calls to the functions exported by each plugin and the API functions called by the
+/// plugin. When Nvim is started with `NVIM_WASM_PROFILER=fuel`, plugins are metered and the fuel
+/// they consume, i.e. roughly the number of WASM instructions they run, is recorded too.
+///
+/// @param action "start" to clear the profiles and start recording, "stop" to stop recording, or
+///               "dump" to get the profiles.
+/// @param[out] err Error details, if any
+/// @return List of the profiles of the loaded plugins, recorded since profiling last started. Each
+///         profile has these keys:
+///         - id: Instance ID.
+///         - source: Where the plugin comes from, e.g. "file plugin.wasm".
+///         - exports: Calls to the functions exported by the plugin, by function name.
+///         - api_calls: Calls of the plugin to API functions, by function name.
+///         - fuel: Fuel consumed by the calls to the plugin, only with `NVIM_WASM_PROFILER=fuel`.
+///         Calls are dictionaries with the keys "count" and "time", the total time in nanoseconds.
+///         The time of the calls to an exported function includes the API calls they make.
+Array nvim_wasm_profile(String action, Error *err)
+  FUNC_API_SINCE(99)
+{
+  if (strequal(action.data, "start")) {
+    wasm_profile_start();
+  } else if (strequal(action.data, "stop")) {
+    wasm_profile_stop();
+  } else {
+    VALIDATE_S(strequal(action.data, "dump"), "action", action.data, {
+      return (Array)ARRAY_DICT_INIT;
+    });
   }
-  return instance_id;
+  return wasm_get_profiles();
 }
 
 Object nvim_wasm_call_func(Integer instance_id, String func_name, Array args, Error *error)
   FUNC_API_SINCE(99)
 {
-  const char *errmsg = NULL;
-  Object result = wasm_call_func(instance_id, func_name.data, args, &errmsg);
-  if (errmsg != NULL) {
-    api_set_error(error, kErrorTypeException, "%s", errmsg);
+  return wasm_call((int32_t)instance_id, func_name.data, args, error);
+}
+
+/// Unloads a WASM plugin, freeing its memory and stopping the timers and processes it started.
+///
+/// Instance IDs are not reused. Autocommands, mappings, user commands, decoration providers and
+/// buffer callbacks set by the plugin are removed, without calling the `on_detach` callbacks.
+///
+/// A running plugin cannot be unloaded, nor a plugin exporting interfaces to other plugins. Not
+/// allowed under |textlock|, e.g. in buffer callbacks and decoration providers.
+///
+/// @param instance_id Instance ID returned by |nvim_wasm_load()|
+/// @param[out] err Error details, if any
+void nvim_wasm_unload(Integer instance_id, Error *err)
+  FUNC_API_SINCE(99)
+{
+  VALIDATE_INT((instance_id >= 0 && instance_id <= INT32_MAX), "instance_id", instance_id, {
+    return;
+  });
+  wasm_unload((int32_t)instance_id, err);
+}
+
+/// Makes a buffer the current buffer, until |nvim_wasm_restore_context()| is called.
+///
+/// This is the WASM version of |nvim_buf_call()|. A running WASM plugin cannot be called back, so
+/// instead of passing a callback, the plugin enters the buffer context, runs its code and restores
+/// the context. Contexts that are not restored are restored when the plugin call returns.
+///
+/// Can only be called from WASM plugins.
+///
+/// @param buffer     Buffer handle, or 0 for current buffer
+/// @param[out] err   Error details, if any
+/// @return           Context handle for |nvim_wasm_restore_context()|
+Integer nvim_wasm_buf_enter(Buffer buffer, Error *err)
+  FUNC_API_SINCE(99)
+{
+  VALIDATE(wasm_current_instance() >= 0, "%s", "Can only be called from WASM plugins", {
+    return 0;
+  });
+  buf_T *buf = find_buffer_by_handle(buffer, err);
+  if (!buf) {
+    return 0;
+  }
+  return wasm_enter_buf(buf);
+}
+
+/// Makes a window the current window, until |nvim_wasm_restore_context()| is called.
+///
+/// This is the WASM version of |nvim_win_call()|, see |nvim_wasm_buf_enter()|.
+///
+/// Can only be called from WASM plugins.
+///
+/// @param window     Window handle, or 0 for current window
+/// @param[out] err   Error details, if any
+/// @return           Context handle for |nvim_wasm_restore_context()|
+Integer nvim_wasm_win_enter(Window window, Error *err)
+  FUNC_API_SINCE(99)
+{
+  VALIDATE(wasm_current_instance() >= 0, "%s", "Can only be called from WASM plugins", {
+    return 0;
+  });
+  win_T *win = find_window_by_handle(window, err);
+  if (!win) {
+    return 0;
+  }
+  return wasm_enter_win(win, win_find_tabpage(win), err);
+}
+
+/// Restores the context entered by |nvim_wasm_buf_enter()| or |nvim_wasm_win_enter()|.
+///
+/// Contexts must be restored in the reverse order they are entered, by the plugin call that
+/// entered them: a plugin cannot restore the contexts of the plugin calling it.
+///
+/// @param context    Context handle
+/// @param[out] err   Error details, if any
+void nvim_wasm_restore_context(Integer context, Error *err)
+  FUNC_API_SINCE(99)
+{
+  VALIDATE_INT(wasm_restore_context(context), "context (not the innermost one of the call)",
+               context, {
+    return;
+  });
+}
+
+/// Sets or changes a decoration provider implemented by the calling WASM plugin.
+///
+/// This is the WASM version of |nvim_set_decoration_provider()|. The callbacks are the names of
+/// functions exported by the plugin. They are called directly from the redraw loop, with the same
+/// arguments as the Lua callbacks (without the leading event name) as `s64`, and return a `bool`.
+/// Fails if the plugin exports no such function of this type.
+/// Use |nvim_wasm_add_ephemeral_hl()| to highlight the redrawn lines.
+///
+/// Can only be called from WASM plugins.
+///
+/// @param ns_id  Namespace id from |nvim_create_namespace()|
+/// @param opts  Table of export names:
+///             - on_start: `func(tick: s64) -> bool`
+///             - on_buf: `func(bufnr: s64, tick: s64) -> bool`, the result is ignored
+///             - on_win: `func(winid: s64, bufnr: s64, toprow: s64, botrow: s64) -> bool`
+///             - on_line: `func(winid: s64, bufnr: s64, row: s64) -> bool`
+///             - on_end: `func(tick: s64) -> bool`, the result is ignored
+/// @param[out] err Error details, if any
+void nvim_wasm_set_decoration_provider(Integer ns_id, Dict(wasm_decoration_provider) *opts,
+                                       Error *err)
+  FUNC_API_SINCE(99)
+{
+  int32_t instance_id = wasm_current_instance();
+  VALIDATE(instance_id >= 0, "%s", "Can only be called from WASM plugins", {
+    return;
+  });
+  VALIDATE_INT(ns_initialized((uint32_t)ns_id), "ns_id", ns_id, {
+    return;
+  });
+
+  DecorProvider *p = get_decor_provider((NS)ns_id, true);
+  assert(p != NULL);
+  decor_provider_clear(p);
+
+  redraw_all_later(UPD_NOT_VALID);
+
+  struct {
+    const char *name;
+    Object *source;
+    int *dest;
+    size_t args_len;
+  } cbs[] = {
+    { "on_start", &opts->on_start, &p->wasm_redraw_start, 1 },
+    { "on_buf", &opts->on_buf, &p->wasm_redraw_buf, 2 },
+    { "on_win", &opts->on_win, &p->wasm_redraw_win, 4 },
+    { "on_line", &opts->on_line, &p->wasm_redraw_line, 3 },
+    { "on_end", &opts->on_end, &p->wasm_redraw_end, 1 },
+    { NULL, NULL, NULL, 0 },
+  };
+
+  for (size_t i = 0; cbs[i].name; i++) {
+    Object *v = cbs[i].source;
+    if (v->type == kObjectTypeNil) {
+      continue;
+    }
+
+    VALIDATE_T(cbs[i].name, kObjectTypeString, v->type, {
+      goto error;
+    });
+
+    // Looked up once here rather than for every redrawn line.
+    *(cbs[i].dest) = wasm_find_hook_func(instance_id, v->data.string.data, cbs[i].args_len, err);
+    if (ERROR_SET(err)) {
+      goto error;
+    }
+  }
+
+  p->wasm_instance = instance_id;
+  p->active = true;
+  p->hl_valid++;
+  p->hl_cached = false;
+  return;
+error:
+  decor_provider_clear(p);
+}
+
+/// Activates buffer-update events delivered to functions exported by the calling WASM plugin.
+///
+/// This is the WASM version of |nvim_buf_attach()| with Lua callbacks. The callbacks are the names
+/// of functions exported by the plugin, called with the event as a typed record. They return `true`
+/// to detach, and are detached after failing 3 times.
+///
+/// Can only be called from WASM plugins.
+///
+/// @param buffer Buffer handle, or 0 for current buffer
+/// @param opts   Optional parameters.
+///             - on_lines: `func(event: buf-lines-event) -> bool`
+///             - on_bytes: `func(event: buf-bytes-event) -> bool`
+///             - on_changedtick: `func(bufnr: s64, changedtick: s64) -> bool`
+///             - on_detach: `func(bufnr: s64) -> bool`, the result is ignored
+///             - utf_sizes: include UTF-32 and UTF-16 size of the replaced
+///               region in the `on_lines` event.
+///             - preview: also attach to command preview (i.e. 'inccommand')
+///               events.
+/// @param[out] err Error details, if any
+/// @return False if attach failed (invalid parameter, or buffer isn't loaded);
+///         otherwise True.
+Boolean nvim_wasm_buf_attach(Buffer buffer, Dict(wasm_buf_attach) *opts, Error *err)
+  FUNC_API_SINCE(99)
+{
+  int32_t instance_id = wasm_current_instance();
+  VALIDATE(instance_id >= 0, "%s", "Can only be called from WASM plugins", {
+    return false;
+  });
+  buf_T *buf = find_buffer_by_handle(buffer, err);
+  if (!buf) {
+    return false;
+  }
+
+  BufUpdateCallbacks cb = BUF_UPDATE_CALLBACKS_INIT;
+  cb.wasm_instance = instance_id;
+  struct {
+    const char *name;
+    Object *source;
+    char **dest;
+  } cbs[] = {
+    { "on_lines", &opts->on_lines, &cb.wasm_on_lines },
+    { "on_bytes", &opts->on_bytes, &cb.wasm_on_bytes },
+    { "on_changedtick", &opts->on_changedtick, &cb.wasm_on_changedtick },
+    { "on_detach", &opts->on_detach, &cb.wasm_on_detach },
+    { NULL, NULL, NULL },
+  };
+
+  for (size_t i = 0; cbs[i].name; i++) {
+    Object *v = cbs[i].source;
+    if (v->type == kObjectTypeNil) {
+      continue;
+    }
+
+    VALIDATE_T(cbs[i].name, kObjectTypeString, v->type, {
+      goto error;
+    });
+
+    *(cbs[i].dest) = string_to_cstr(v->data.string);
+  }
+
+  if (HAS_KEY(opts->utf_sizes)) {
+    VALIDATE_T("utf_sizes", kObjectTypeBoolean, opts->utf_sizes.type, {
+      goto error;
+    });
+    cb.utf_sizes = opts->utf_sizes.data.boolean;
+  }
+  if (HAS_KEY(opts->preview)) {
+    VALIDATE_T("preview", kObjectTypeBoolean, opts->preview.type, {
+      goto error;
+    });
+    cb.preview = opts->preview.data.boolean;
   }
-  return result;
+
+  // Registered as callbacks, like the Lua callbacks of nvim_buf_attach().
+  if (buf_updates_register(buf, LUA_INTERNAL_CALL, cb, false)) {
+    return true;
+  }
+
+error:
+  buffer_update_callbacks_free(cb);
+  return false;
+}
+
+/// Highlights a range of the buffer being redrawn, for the current screen redraw only.
+///
+/// This is a fast version of |nvim_buf_set_extmark()| with `ephemeral` and `hl_group`, for the
+/// `on_win` and `on_line` callbacks of |nvim_wasm_set_decoration_provider()|.
+///
+/// @param ns_id    Namespace id from |nvim_create_namespace()|
+/// @param line     Line where to place the mark, 0-based
+/// @param col      Column where to place the mark, 0-based
+/// @param end_row  Ending line of the mark, 0-based inclusive
+/// @param end_col  Ending col of the mark, 0-based exclusive
+/// @param hl_id    Highlight group id, see |nvim_get_hl_id_by_name()|
+/// @param priority Priority of the highlight, see |nvim_buf_set_extmark()|
+/// @param[out] err Error details, if any
+void nvim_wasm_add_ephemeral_hl(Integer ns_id, Integer line, Integer col, Integer end_row,
+                                Integer end_col, Integer hl_id, Integer priority, Error *err)
+  FUNC_API_SINCE(99)
+{
+  VALIDATE(provider_active && decor_state.win != NULL, "%s",
+           "Can only be called from decoration providers", {
+    return;
+  });
+  VALIDATE_INT(ns_initialized((uint32_t)ns_id), "ns_id", ns_id, {
+    return;
+  });
+  VALIDATE_RANGE((line >= 0 && line <= MAXLNUM && col >= 0 && col <= MAXCOL), "line or col", {
+    return;
+  });
+  VALIDATE_RANGE((end_row >= line && end_row <= MAXLNUM && end_col >= 0 && end_col <= MAXCOL),
+                 "end_row or end_col", {
+    return;
+  });
+  VALIDATE_INT((hl_id > 0 && hl_id <= highlight_num_groups()), "hl_id", hl_id, {
+    return;
+  });
+  VALIDATE_RANGE((priority >= 0 && priority <= UINT16_MAX), "priority", {
+    return;
+  });
+
+  Decoration decor = DECORATION_INIT;
+  decor.hl_id = (int)hl_id;
+  decor.priority = (DecorPriority)priority;
+  decor_add_ephemeral((int)line, (int)col, (int)end_row, (int)end_col, &decor, (uint64_t)ns_id, 0);
 }
@@ -2388,6 +2388,22 @@ bool autocmd_delete_id(int64_t id)
   return success;
 }
 
+/// Deletes the autocmds defined by a channel, e.g. by a WASM plugin which is unloaded.
+void autocmd_delete_chan(uint64_t channel_id)
+{
+  FOR_ALL_AUEVENTS(event) {
+    AutoCmdVec *const acs = &autocmds[(int)event];
+    for (size_t i = 0; i < kv_size(*acs); i++) {
+      AutoCmd *const ac = &kv_A(*acs, i);
+      if (ac->pat != NULL && ac->script_ctx.sc_chan == channel_id) {
+        aucmd_del(ac);
+      }
+    }
+  }
+
+  au_cleanup();
+}
+
 // ===========================================================================
 //  AucmdExecutable Functions
 // ===========================================================================
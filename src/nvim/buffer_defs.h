@@ -438,9 +438,19 @@ typedef struct {
   LuaRef on_reload;
   bool utf_sizes;
   bool preview;
+  /// The WASM instance exporting the callbacks below, or -1. See nvim_wasm_buf_attach().
+  int wasm_instance;
+  /// Names of the exports called instead of the LuaRefs, or NULL.
+  char *wasm_on_lines;
+  char *wasm_on_bytes;
+  char *wasm_on_changedtick;
+  char *wasm_on_detach;
+  /// The number of times the WASM callbacks failed, detached after 3 like timers.
+  int wasm_errors;
 } BufUpdateCallbacks;
 #define BUF_UPDATE_CALLBACKS_INIT { LUA_NOREF, LUA_NOREF, LUA_NOREF, \
-                                    LUA_NOREF, LUA_NOREF, false, false }
+                                    LUA_NOREF, LUA_NOREF, false, false, \
+                                    -1, NULL, NULL, NULL, NULL, 0 }
 
 EXTERN int curbuf_splice_pending INIT(= 0);
 
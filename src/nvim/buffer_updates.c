@@ -4,6 +4,7 @@
 #include <inttypes.h>
 #include <stdbool.h>
 #include <stddef.h>
+#include <stdint.h>
 
 #include "klib/kvec.h"
 #include "lauxlib.h"
@@ -18,11 +19,14 @@
 #include "nvim/globals.h"
 #include "nvim/log.h"
 #include "nvim/lua/executor.h"
+#include "nvim/macros.h"
 #include "nvim/memline.h"
 #include "nvim/memory.h"
+#include "nvim/message.h"
 #include "nvim/msgpack_rpc/channel.h"
 #include "nvim/pos.h"
 #include "nvim/types.h"
+#include "nvim/wasm/executor.h"
 
 #ifdef INCLUDE_GENERATED_DECLARATIONS
 # include "buffer_updates.c.generated.h"  // IWYU pragma: export
@@ -191,10 +195,15 @@ void buf_updates_unload(buf_T *buf, bool can_reload)
       TEXTLOCK_WRAP({
         nlua_call_ref(thecb, keep ? "reload" : "detach", args, false, NULL);
       });
+    } else if (!keep && cb.wasm_on_detach != NULL) {
+      int64_t args[] = { buf->handle };
+      TEXTLOCK_WRAP({
+        (void)buf_updates_call_wasm_hook(&cb, cb.wasm_on_detach, args, ARRAY_SIZE(args));
+      });
     }
 
     if (keep) {
-      kv_A(buf->update_callbacks, j++) = kv_A(buf->update_callbacks, i);
+      kv_A(buf->update_callbacks, j++) = cb;
     } else {
       buffer_update_callbacks_free(cb);
     }
@@ -277,7 +286,32 @@ void buf_updates_send_changes(buf_T *buf, linenr_T firstline, int64_t num_added,
   for (size_t i = 0; i < kv_size(buf->update_callbacks); i++) {
     BufUpdateCallbacks cb = kv_A(buf->update_callbacks, i);
     bool keep = true;
-    if (cb.on_lines != LUA_NOREF && (cb.preview || !cmdpreview)) {
+    if (cb.wasm_on_lines != NULL && (cb.preview || !cmdpreview)) {
+      WasmBufLinesEvent event = {
+        .buffer = buf->handle,
+        .changedtick = send_tick ? buf_get_changedtick(buf) : 0,
+        .has_changedtick = send_tick,
+        .first_line = firstline - 1,
+        .last_line = firstline - 1 + num_removed,
+        .last_line_updated = firstline - 1 + num_added,
+        .byte_count = (int64_t)deleted_bytes,
+        .deleted_codepoints = (int64_t)deleted_codepoints,
+        .deleted_codeunits = (int64_t)deleted_codeunits,
+        .has_utf_sizes = cb.utf_sizes,
+      };
+
+      Error err = ERROR_INIT;
+      bool detach;
+      TEXTLOCK_WRAP({
+        detach = wasm_call_buf_lines_func(cb.wasm_instance, cb.wasm_on_lines, &event, &err);
+      });
+      detach |= buf_updates_report_wasm_error(&cb, &err);
+
+      if (detach) {
+        buffer_update_callbacks_free(cb);
+        keep = false;
+      }
+    } else if (cb.on_lines != LUA_NOREF && (cb.preview || !cmdpreview)) {
       Array args = ARRAY_DICT_INIT;
       Object items[8];
       args.size = 6;  // may be increased to 8 below
@@ -317,7 +351,7 @@ void buf_updates_send_changes(buf_T *buf, linenr_T firstline, int64_t num_added,
       }
     }
     if (keep) {
-      kv_A(buf->update_callbacks, j++) = kv_A(buf->update_callbacks, i);
+      kv_A(buf->update_callbacks, j++) = cb;
     }
   }
   kv_size(buf->update_callbacks) = j;
@@ -337,7 +371,33 @@ void buf_updates_send_splice(buf_T *buf, int start_row, colnr_T start_col, bcoun
   for (size_t i = 0; i < kv_size(buf->update_callbacks); i++) {
     BufUpdateCallbacks cb = kv_A(buf->update_callbacks, i);
     bool keep = true;
-    if (cb.on_bytes != LUA_NOREF && (cb.preview || !cmdpreview)) {
+    if (cb.wasm_on_bytes != NULL && (cb.preview || !cmdpreview)) {
+      WasmBufBytesEvent event = {
+        .buffer = buf->handle,
+        .changedtick = buf_get_changedtick(buf),
+        .start_row = start_row,
+        .start_col = start_col,
+        .start_byte = start_byte,
+        .old_end_row = old_row,
+        .old_end_col = old_col,
+        .old_end_byte = old_byte,
+        .new_end_row = new_row,
+        .new_end_col = new_col,
+        .new_end_byte = new_byte,
+      };
+
+      Error err = ERROR_INIT;
+      bool detach;
+      TEXTLOCK_WRAP({
+        detach = wasm_call_buf_bytes_func(cb.wasm_instance, cb.wasm_on_bytes, &event, &err);
+      });
+      detach |= buf_updates_report_wasm_error(&cb, &err);
+
+      if (detach) {
+        buffer_update_callbacks_free(cb);
+        keep = false;
+      }
+    } else if (cb.on_bytes != LUA_NOREF && (cb.preview || !cmdpreview)) {
       MAXSIZE_TEMP_ARRAY(args, 11);
 
       // the first argument is always the buffer handle
@@ -367,7 +427,7 @@ void buf_updates_send_splice(buf_T *buf, int start_row, colnr_T start_col, bcoun
       }
     }
     if (keep) {
-      kv_A(buf->update_callbacks, j++) = kv_A(buf->update_callbacks, i);
+      kv_A(buf->update_callbacks, j++) = cb;
     }
   }
   kv_size(buf->update_callbacks) = j;
@@ -383,7 +443,18 @@ void buf_updates_changedtick(buf_T *buf)
   for (size_t i = 0; i < kv_size(buf->update_callbacks); i++) {
     BufUpdateCallbacks cb = kv_A(buf->update_callbacks, i);
     bool keep = true;
-    if (cb.on_changedtick != LUA_NOREF) {
+    if (cb.wasm_on_changedtick != NULL) {
+      int64_t args[] = { buf->handle, buf_get_changedtick(buf) };
+      bool detach;
+      TEXTLOCK_WRAP({
+        detach = buf_updates_call_wasm_hook(&cb, cb.wasm_on_changedtick, args, ARRAY_SIZE(args));
+      });
+
+      if (detach) {
+        buffer_update_callbacks_free(cb);
+        keep = false;
+      }
+    } else if (cb.on_changedtick != LUA_NOREF) {
       MAXSIZE_TEMP_ARRAY(args, 2);
 
       // the first argument is always the buffer handle
@@ -403,7 +474,7 @@ void buf_updates_changedtick(buf_T *buf)
       }
     }
     if (keep) {
-      kv_A(buf->update_callbacks, j++) = kv_A(buf->update_callbacks, i);
+      kv_A(buf->update_callbacks, j++) = cb;
     }
   }
   kv_size(buf->update_callbacks) = j;
@@ -430,4 +501,56 @@ void buffer_update_callbacks_free(BufUpdateCallbacks cb)
   api_free_luaref(cb.on_changedtick);
   api_free_luaref(cb.on_reload);
   api_free_luaref(cb.on_detach);
+  xfree(cb.wasm_on_lines);
+  xfree(cb.wasm_on_bytes);
+  xfree(cb.wasm_on_changedtick);
+  xfree(cb.wasm_on_detach);
+}
+
+/// Detaches the callbacks of a WASM instance which is unloaded from all buffers, without calling
+/// their on_detach callback. Not allowed while the callbacks of a buffer are called, which holds
+/// textlock.
+void buf_updates_unregister_wasm(int instance_id)
+{
+  FOR_ALL_BUFFERS(buf) {
+    size_t j = 0;
+    for (size_t i = 0; i < kv_size(buf->update_callbacks); i++) {
+      BufUpdateCallbacks cb = kv_A(buf->update_callbacks, i);
+      if (cb.wasm_instance == instance_id) {
+        buffer_update_callbacks_free(cb);
+      } else {
+        kv_A(buf->update_callbacks, j++) = cb;
+      }
+    }
+    kv_size(buf->update_callbacks) = j;
+    if (kv_size(buf->update_callbacks) == 0) {
+      kv_destroy(buf->update_callbacks);
+      kv_init(buf->update_callbacks);
+    }
+  }
+}
+
+/// Calls a WASM callback taking integer arguments, see nvim_wasm_buf_attach().
+///
+/// @return true to detach from the buffer.
+static bool buf_updates_call_wasm_hook(BufUpdateCallbacks *cb, const char *func_name,
+                                       const int64_t *args, size_t args_len)
+{
+  Error err = ERROR_INIT;
+  bool detach = wasm_call_named_hook_func(cb->wasm_instance, func_name, args, args_len, &err);
+  return buf_updates_report_wasm_error(cb, &err) || detach;
+}
+
+/// Reports the error of a WASM callback, like nlua_call_ref() does for Lua callbacks.
+///
+/// @return true to detach the callbacks from the buffer, after they failed 3 times: e.g. the
+///         callbacks of a plugin whose state is broken would fail on every change.
+static bool buf_updates_report_wasm_error(BufUpdateCallbacks *cb, Error *err)
+{
+  if (!ERROR_SET(err)) {
+    return false;
+  }
+  emsg_multiline(err->msg, /*multiline=*/true);
+  api_clear_error(err);
+  return ++cb->wasm_errors >= 3;
 }
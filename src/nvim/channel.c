@@ -34,6 +34,7 @@
 #include "nvim/os/shell.h"
 #include "nvim/path.h"
 #include "nvim/rbuffer.h"
+#include "nvim/wasm/process.h"
 
 #ifdef MSWIN
 # include "nvim/os/fs.h"
@@ -201,6 +202,7 @@ Channel *channel_alloc(ChannelStreamType type)
   chan->events = multiqueue_new_child(main_loop.events);
   chan->refcount = 1;
   chan->exit_status = -1;
+  chan->wasm_instance = -1;
   chan->streamtype = type;
   assert(chan->id <= VARNUMBER_MAX);
   pmap_put(uint64_t)(&channels, chan->id, chan);
@@ -733,7 +735,7 @@ static void channel_process_exit_cb(Process *proc, int status, void *data)
 
   // If process did not exit, we only closed the handle of a detached process.
   bool exited = (status >= 0);
-  if (exited && chan->on_exit.type != kCallbackNone) {
+  if (exited && (chan->on_exit.type != kCallbackNone || chan->wasm_instance >= 0)) {
     schedule_channel_event(chan);
     chan->exit_status = status;
   }
@@ -743,6 +745,11 @@ static void channel_process_exit_cb(Process *proc, int status, void *data)
 
 static void channel_callback_call(Channel *chan, CallbackReader *reader)
 {
+  if (chan->wasm_instance >= 0) {
+    wasm_process_callback(chan, reader);
+    return;
+  }
+
   Callback *cb;
   typval_T argv[4];
 
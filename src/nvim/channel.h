@@ -65,6 +65,7 @@ typedef struct {
   bool eof;
   bool buffered;
   bool fwd_err;
+  bool wasm;  ///< Read for the WASM instance owning the channel, see wasm_process_spawn().
   const char *type;
 } CallbackReader;
 
@@ -73,10 +74,11 @@ typedef struct {
                                                 .buffer = GA_EMPTY_INIT_VALUE, \
                                                 .buffered = false, \
                                                 .fwd_err = false, \
+                                                .wasm = false, \
                                                 .type = NULL })
 static inline bool callback_reader_set(CallbackReader reader)
 {
-  return reader.cb.type != kCallbackNone || reader.self;
+  return reader.cb.type != kCallbackNone || reader.self || reader.wasm;
 }
 
 struct Channel {
@@ -103,6 +105,9 @@ struct Channel {
   CallbackReader on_stderr;
   Callback on_exit;
   int exit_status;
+  /// The WASM instance owning the process of the channel, or -1. Its exports are called instead
+  /// of the callbacks, see wasm_process_callback().
+  int32_t wasm_instance;
 
   bool callback_busy;
   bool callback_scheduled;
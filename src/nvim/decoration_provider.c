@@ -18,22 +18,46 @@
 #include "nvim/lua/executor.h"
 #include "nvim/memory.h"
 #include "nvim/pos.h"
+#include "nvim/wasm/executor.h"
 
 static kvec_t(DecorProvider) decor_providers = KV_INITIAL_VALUE;
 
 #define DECORATION_PROVIDER_INIT(ns_id) (DecorProvider) \
   { ns_id, false, LUA_NOREF, LUA_NOREF, \
     LUA_NOREF, LUA_NOREF, LUA_NOREF, \
-    LUA_NOREF, -1, false, false }
+    LUA_NOREF, -1, false, false, \
+    -1, -1, -1, -1, -1, -1 }
 
-static bool decor_provider_invoke(NS ns_id, const char *name, LuaRef ref, Array args,
-                                  bool default_true, char **perr)
+/// Invokes a provider callback: the WASM hook `wasm_hook` if it is set, the LuaRef `ref` otherwise.
+static bool decor_provider_invoke(DecorProvider *p, const char *name, LuaRef ref, int wasm_hook,
+                                  Array args, bool default_true, char **perr)
 {
   Error err = ERROR_INIT;
+  NS ns_id = p->ns_id;
 
   textlock++;
   provider_active = true;
-  Object ret = nlua_call_ref(ref, name, args, true, &err);
+  Object ret;
+  if (wasm_hook >= 0) {
+    // WASM hooks take the integers directly, see nvim_wasm_set_decoration_provider().
+    int64_t int_args[4];
+    assert(args.size <= ARRAY_SIZE(int_args));
+    for (size_t i = 0; i < args.size; i++) {
+      ObjectType type = args.items[i].type;
+      if (type != kObjectTypeInteger && type != kObjectTypeBuffer && type != kObjectTypeWindow) {
+        api_set_error(&err, kErrorTypeValidation, "Argument %zu of %s is not an integer", i + 1,
+                      name);
+        break;
+      }
+      int_args[i] = args.items[i].data.integer;
+    }
+    ret = ERROR_SET(&err)
+          ? NIL
+          : BOOLEAN_OBJ(wasm_call_hook_func(p->wasm_instance, wasm_hook, int_args, args.size,
+                                            &err));
+  } else {
+    ret = nlua_call_ref(ref, name, args, true, &err);
+  }
   provider_active = false;
   textlock--;
 
@@ -74,7 +98,7 @@ void decor_providers_invoke_spell(win_T *wp, int start_row, int start_col, int e
       ADD_C(args, INTEGER_OBJ(start_col));
       ADD_C(args, INTEGER_OBJ(end_row));
       ADD_C(args, INTEGER_OBJ(end_col));
-      decor_provider_invoke(p->ns_id, "spell", p->spell_nav, args, true, err);
+      decor_provider_invoke(p, "spell", p->spell_nav, -1, args, true, err);
     }
   }
 }
@@ -94,10 +118,11 @@ void decor_providers_start(DecorProviders *providers, char **err)
     }
 
     bool active;
-    if (p->redraw_start != LUA_NOREF) {
+    if (p->redraw_start != LUA_NOREF || p->wasm_redraw_start >= 0) {
       MAXSIZE_TEMP_ARRAY(args, 2);
       ADD_C(args, INTEGER_OBJ((int)display_tick));
-      active = decor_provider_invoke(p->ns_id, "start", p->redraw_start, args, true, err);
+      active = decor_provider_invoke(p, "start", p->redraw_start, p->wasm_redraw_start, args,
+                                     true, err);
     } else {
       active = true;
     }
@@ -126,14 +151,14 @@ void decor_providers_invoke_win(win_T *wp, DecorProviders *providers,
 
   for (size_t k = 0; k < kv_size(*providers); k++) {
     DecorProvider *p = kv_A(*providers, k);
-    if (p && p->redraw_win != LUA_NOREF) {
+    if (p && (p->redraw_win != LUA_NOREF || p->wasm_redraw_win >= 0)) {
       MAXSIZE_TEMP_ARRAY(args, 4);
       ADD_C(args, WINDOW_OBJ(wp->handle));
       ADD_C(args, BUFFER_OBJ(wp->w_buffer->handle));
       // TODO(bfredl): we are not using this, but should be first drawn line?
       ADD_C(args, INTEGER_OBJ(wp->w_topline - 1));
       ADD_C(args, INTEGER_OBJ(knownmax));
-      if (decor_provider_invoke(p->ns_id, "win", p->redraw_win, args, true, err)) {
+      if (decor_provider_invoke(p, "win", p->redraw_win, p->wasm_redraw_win, args, true, err)) {
         kvi_push(*line_providers, p);
       }
     }
@@ -153,12 +178,13 @@ void decor_providers_invoke_line(win_T *wp, DecorProviders *providers, int row,
   decor_state.running_on_lines = true;
   for (size_t k = 0; k < kv_size(*providers); k++) {
     DecorProvider *p = kv_A(*providers, k);
-    if (p && p->redraw_line != LUA_NOREF) {
+    if (p && (p->redraw_line != LUA_NOREF || p->wasm_redraw_line >= 0)) {
       MAXSIZE_TEMP_ARRAY(args, 3);
       ADD_C(args, WINDOW_OBJ(wp->handle));
       ADD_C(args, BUFFER_OBJ(wp->w_buffer->handle));
       ADD_C(args, INTEGER_OBJ(row));
-      if (decor_provider_invoke(p->ns_id, "line", p->redraw_line, args, true, err)) {
+      if (decor_provider_invoke(p, "line", p->redraw_line, p->wasm_redraw_line, args, true,
+                                err)) {
         *has_decor = true;
       } else {
         // return 'false' or error: skip rest of this window
@@ -180,11 +206,11 @@ void decor_providers_invoke_buf(buf_T *buf, DecorProviders *providers, char **er
 {
   for (size_t i = 0; i < kv_size(*providers); i++) {
     DecorProvider *p = kv_A(*providers, i);
-    if (p && p->redraw_buf != LUA_NOREF) {
+    if (p && (p->redraw_buf != LUA_NOREF || p->wasm_redraw_buf >= 0)) {
       MAXSIZE_TEMP_ARRAY(args, 2);
       ADD_C(args, BUFFER_OBJ(buf->handle));
       ADD_C(args, INTEGER_OBJ((int64_t)display_tick));
-      decor_provider_invoke(p->ns_id, "buf", p->redraw_buf, args, true, err);
+      decor_provider_invoke(p, "buf", p->redraw_buf, p->wasm_redraw_buf, args, true, err);
     }
   }
 }
@@ -198,10 +224,10 @@ void decor_providers_invoke_end(DecorProviders *providers, char **err)
 {
   for (size_t i = 0; i < kv_size(*providers); i++) {
     DecorProvider *p = kv_A(*providers, i);
-    if (p && p->active && p->redraw_end != LUA_NOREF) {
+    if (p && p->active && (p->redraw_end != LUA_NOREF || p->wasm_redraw_end >= 0)) {
       MAXSIZE_TEMP_ARRAY(args, 1);
       ADD_C(args, INTEGER_OBJ((int)display_tick));
-      decor_provider_invoke(p->ns_id, "end", p->redraw_end, args, true, err);
+      decor_provider_invoke(p, "end", p->redraw_end, p->wasm_redraw_end, args, true, err);
     }
   }
 }
@@ -269,9 +295,26 @@ void decor_provider_clear(DecorProvider *p)
   NLUA_CLEAR_REF(p->redraw_line);
   NLUA_CLEAR_REF(p->redraw_end);
   NLUA_CLEAR_REF(p->spell_nav);
+  p->wasm_instance = -1;
+  p->wasm_redraw_start = -1;
+  p->wasm_redraw_buf = -1;
+  p->wasm_redraw_win = -1;
+  p->wasm_redraw_line = -1;
+  p->wasm_redraw_end = -1;
   p->active = false;
 }
 
+/// Clears the decoration providers set by a WASM instance which is unloaded.
+void decor_provider_clear_wasm(int instance_id)
+{
+  for (size_t i = 0; i < kv_size(decor_providers); i++) {
+    DecorProvider *p = &kv_A(decor_providers, i);
+    if (p->wasm_instance == instance_id) {
+      decor_provider_clear(p);
+    }
+  }
+}
+
 void decor_free_all_mem(void)
 {
   for (size_t i = 0; i < kv_size(decor_providers); i++) {
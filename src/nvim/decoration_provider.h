@@ -20,6 +20,16 @@ typedef struct {
   LuaRef spell_nav;
   int hl_valid;
   bool hl_cached;
+
+  /// The WASM instance exporting the callbacks below, or -1. See
+  /// nvim_wasm_set_decoration_provider().
+  int wasm_instance;
+  /// Hooks called instead of the LuaRefs, see wasm_find_hook_func(), or -1.
+  int wasm_redraw_start;
+  int wasm_redraw_buf;
+  int wasm_redraw_win;
+  int wasm_redraw_line;
+  int wasm_redraw_end;
 } DecorProvider;
 
 typedef kvec_withinit_t(DecorProvider *, 4) DecorProviders;
@@ -8025,7 +8025,7 @@ void last_set_msg(sctx_T script_ctx)
 {
   const LastSet last_set = (LastSet){
     .script_ctx = script_ctx,
-    .channel_id = 0,
+    .channel_id = script_ctx.sc_chan,
   };
   option_last_set_msg(last_set);
 }
@@ -3913,8 +3913,11 @@ static const char *required_env_vars[] = {
   NULL
 };
 
-static dict_T *create_environment(const dictitem_T *job_env, const bool clear_env, const bool pty,
-                                  const char * const pty_term_name)
+/// Builds the environment of a job started by jobstart() or wasm_process_spawn().
+///
+/// @param job_env  The "env" option of the job, added to Nvim's environment. Can be NULL.
+dict_T *create_environment(const dictitem_T *job_env, const bool clear_env, const bool pty,
+                           const char * const pty_term_name)
 {
   dict_T *env = tv_dict_alloc();
 
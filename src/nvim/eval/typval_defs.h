@@ -271,6 +271,7 @@ typedef struct {
   scid_T sc_sid;     ///< script ID
   int sc_seq;        ///< sourcing sequence number
   linenr_T sc_lnum;  ///< line number
+  uint64_t sc_chan;  ///< channel ID, only used when sc_sid is SID_API_CLIENT
 } sctx_T;
 
 /// Maximum number of function arguments
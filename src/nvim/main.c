@@ -89,6 +89,7 @@
 #include "nvim/version.h"
 #include "nvim/vim.h"
 #include "nvim/wasm/executor.h"
+#include "nvim/wasm/timer.h"
 #include "nvim/window.h"
 #ifdef MSWIN
 # include "nvim/os/os_win_console.h"
@@ -166,6 +167,7 @@ bool event_teardown(void)
   channel_teardown();
   process_teardown(&main_loop);
   timer_teardown();
+  wasm_timer_teardown();
   server_teardown();
   signal_teardown();
   terminal_teardown();
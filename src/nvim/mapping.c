@@ -1062,6 +1062,36 @@ void map_clear_mode(buf_T *buf, int mode, bool local, bool abbr)
   }
 }
 
+/// Delete the entries of the list "mpp" defined by channel "channel_id".
+static void map_clear_list_chan(mapblock_T **mpp, uint64_t channel_id)
+{
+  while (*mpp != NULL) {
+    if ((*mpp)->m_script_ctx.sc_chan == channel_id) {
+      mapblock_free(mpp);
+    } else {
+      mpp = &(*mpp)->m_next;
+    }
+  }
+}
+
+/// Clear all mappings and abbreviations defined by a channel, in all modes and
+/// buffers, e.g. those of a WASM plugin which is unloaded.
+///
+/// @param channel_id  channel which defined the mappings
+void map_clear_chan(uint64_t channel_id)
+{
+  map_clear_list_chan(&first_abbr, channel_id);
+  for (int hash = 0; hash < 256; hash++) {
+    map_clear_list_chan(&maphash[hash], channel_id);
+  }
+  FOR_ALL_BUFFERS(buf) {
+    map_clear_list_chan(&buf->b_first_abbr, channel_id);
+    for (int hash = 0; hash < 256; hash++) {
+      map_clear_list_chan(&buf->b_maphash[hash], channel_id);
+    }
+  }
+}
+
 /// Check if a map exists that has given string in the rhs
 ///
 /// Also checks mappings local to the current buffer.
@@ -49,6 +49,7 @@
 #include "nvim/strings.h"
 #include "nvim/usercmd.h"
 #include "nvim/vim.h"
+#include "nvim/wasm/executor.h"
 
 /// Structure used to store info for each sourced file.
 /// It is shared between do_source() and getsourceline().
@@ -298,7 +299,8 @@ static bool source_callback_vim_lua(int num_fnames, char **fnames, bool all, voi
 }
 
 /// Source all files in "fnames" with .vim files sourced first, .lua files
-/// sourced second, and any remaining files sourced last.
+/// sourced second, and any remaining files sourced last. WASM plugins (.wasm
+/// files) are run like with ":wasm", but must be trusted, see wasm_load().
 static bool source_callback(int num_fnames, char **fnames, bool all, void *cookie)
 {
   bool did_one = source_callback_vim_lua(num_fnames, fnames, all, cookie);
@@ -308,8 +310,14 @@ static bool source_callback(int num_fnames, char **fnames, bool all, void *cooki
   }
 
   for (int i = 0; i < num_fnames; i++) {
-    if (!path_with_extension(fnames[i], "vim")
-        && !path_with_extension(fnames[i], "lua")) {
+    if (path_with_extension(fnames[i], "wasm")) {
+      wasm_run_file(fnames[i], false);
+      did_one = true;
+      if (!all) {
+        return true;
+      }
+    } else if (!path_with_extension(fnames[i], "vim")
+               && !path_with_extension(fnames[i], "lua")) {
       (void)do_source(fnames[i], false, DOSO_NONE, cookie);
       did_one = true;
       if (!all) {
@@ -1251,6 +1259,9 @@ void load_plugins(void)
 
     source_runtime_vim_lua(plugin_pattern, DIP_ALL | DIP_AFTER);
     TIME_MSG("loading after plugins");
+
+    wasm_load_plugins();
+    TIME_MSG("loading WASM plugins");
   }
 }
 
@@ -2378,9 +2389,16 @@ char *get_scriptname(LastSet last_set, bool *should_free)
     return _("changed window size");
   case SID_LUA:
     return _("Lua");
-  case SID_API_CLIENT:
-    snprintf(IObuff, IOSIZE, _("API client (channel id %" PRIu64 ")"), last_set.channel_id);
+  case SID_API_CLIENT: {
+    const char *const wasm_file = wasm_channel_file(last_set.channel_id);
+    if (wasm_file != NULL) {
+      snprintf(IObuff, IOSIZE, _("WASM plugin %s (channel id %" PRIu64 ")"), wasm_file,
+               last_set.channel_id);
+    } else {
+      snprintf(IObuff, IOSIZE, _("API client (channel id %" PRIu64 ")"), last_set.channel_id);
+    }
     return IObuff;
+  }
   case SID_STR:
     return _("anonymous :source");
   default: {
@@ -2,17 +2,22 @@
 ///
 /// Use `cargo run --bin gen-wit` to preview the outputs.
 /// Use `cargo run --bin gen-wit <path>` to generate `keysets.wit` and `api.wit` under the
-/// directory <path>.
+/// directory <path>, and update the package version of the hand-written WIT files.
+/// Use `cargo run --bin gen-wit -- --check <path> [<copy>...]` to list the API functions that are
+/// missing from the WASM interface, and fail if a keyset field has no type, the files under <path>
+/// are out of date, or the directories <copy> (e.g. the `wit/deps/nvim` of the examples) are not
+/// copies of them.
+use std::{fmt::Display, fs::File, io::Write, path::Path, process::ExitCode};
 
-use std::{fmt::Display, fs::File, io::Write, path::Path};
-
-use anyhow::Result;
+use anyhow::{bail, Result};
 use gen_api_func_lib::{
-    api_functions, api_keysets, ApiArrayType, ApiDictionaryType, ApiFunc, ApiKeyset, ApiType,
+    api_functions, api_keysets, api_package_version, api_skipped_functions,
+    keyset_field_type_errors, ApiArrayType, ApiDictionaryType, ApiFunc, ApiKeyset, ApiType,
+    ApiVersion, NvimVersion,
 };
 use indoc::indoc;
 
-fn main() {
+fn main() -> ExitCode {
     let args = std::env::args().skip(1).collect::<Vec<_>>();
     let keysets = api_keysets();
     let functions = api_functions();
@@ -39,25 +44,151 @@ fn main() {
                 )
             });
             write_keysets(&mut keyset_file, &keysets);
-            write_funcs(&mut func_file, &functions, &keysets)
+            write_funcs(&mut func_file, &functions, &keysets);
+
+            for name in HAND_WRITTEN_FILES {
+                let file_path = Path::new(path).join(name);
+                let wit = std::fs::read_to_string(&file_path)
+                    .unwrap_or_else(|_| panic!("Failed to read {}", file_path.display()));
+                std::fs::write(&file_path, with_package(&wit))
+                    .unwrap_or_else(|_| panic!("Failed to write {}", file_path.display()));
+            }
+        }
+        [flag, path, copies @ ..] if flag == "--check" => {
+            let copies = copies.iter().map(Path::new).collect::<Vec<_>>();
+            return check(Path::new(path), &copies, &functions, &keysets);
         }
         _ => {
             panic!(
                 "There should be exactly one argument which is the directory \
                    the wit files will be generated, or no argument, which the \
-                   content will be printed to stdout"
+                   content will be printed to stdout, or `--check` followed by \
+                   the directory to check and its copies"
             )
         }
     };
+
+    ExitCode::SUCCESS
+}
+
+/// Reports the API functions missing from the WASM interface, and checks that the keyset fields
+/// all have a type and that the WIT files under `path` and under each of `copies` are the same as
+/// the generated ones.
+fn check(path: &Path, copies: &[&Path], functions: &[ApiFunc], keysets: &[ApiKeyset]) -> ExitCode {
+    for func in api_skipped_functions() {
+        eprintln!("skipped `{}`: {:#}", func.name, func.error);
+    }
+    for func in functions {
+        if let Err(err) = WitFuncItem::new(func) {
+            eprintln!("skipped `{}`: {err:#}", func.name);
+        }
+    }
+
+    let keyset_errors = keyset_field_type_errors(keysets);
+    for error in &keyset_errors {
+        eprintln!("{error}");
+    }
+    if !keyset_errors.is_empty() {
+        eprintln!(
+            "Update `KEYSET_FIELD_TYPES` in gen-api-func-lib/src/lib.rs, using `Object` for fields \
+             accepting several types"
+        );
+        return ExitCode::FAILURE;
+    }
+
+    let mut keysets_wit = vec![];
+    write_keysets(&mut keysets_wit, keysets);
+    let mut api_wit = vec![];
+    write_funcs(&mut api_wit, functions, keysets);
+
+    let mut expected_files = vec![("keysets.wit", keysets_wit), ("api.wit", api_wit)];
+    for name in HAND_WRITTEN_FILES {
+        let wit = std::fs::read_to_string(path.join(name))
+            .map(|wit| with_package(&wit).into_bytes())
+            .unwrap_or_default();
+        expected_files.push((name, wit));
+    }
+
+    if !check_files(path, &expected_files) {
+        eprintln!(
+            "Run `cargo run --bin gen-wit -- {}` to regenerate the WIT files",
+            path.display()
+        );
+        return ExitCode::FAILURE;
+    }
+    let mut copies_up_to_date = true;
+    for copy in copies {
+        if !check_files(copy, &expected_files) {
+            eprintln!(
+                "Copy the WIT files of {} to {}",
+                path.display(),
+                copy.display()
+            );
+            copies_up_to_date = false;
+        }
+    }
+    if !copies_up_to_date {
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Returns true if the directory `dir` has the files `expected`, as `(name, content)`, reporting
+/// the ones that differ.
+fn check_files(dir: &Path, expected: &[(&str, Vec<u8>)]) -> bool {
+    let mut up_to_date = true;
+    for (name, expected) in expected {
+        let file_path = dir.join(name);
+        match std::fs::read(&file_path) {
+            Ok(actual) if actual == *expected => (),
+            Ok(_) => {
+                eprintln!("{} is out of date", file_path.display());
+                up_to_date = false;
+            }
+            Err(err) => {
+                eprintln!("Failed to read {}: {err}", file_path.display());
+                up_to_date = false;
+            }
+        }
+    }
+    up_to_date
+}
+
+/// Returns the `package` declaration shared by all the WIT files.
+///
+/// The package is versioned with the API level of the Neovim being built, see
+/// [`api_package_version`], so that plugins record the API they are built against.
+fn package_decl() -> String {
+    format!("package nvim:api@{}", api_package_version())
+}
+
+/// The WIT files written by hand, only their package version is generated.
+const HAND_WRITTEN_FILES: [&str; 6] = [
+    "types.wit",
+    "buffer.wit",
+    "log.wit",
+    "timer.wit",
+    "process.wit",
+    "storage.wit",
+];
+
+/// Returns `wit` with its `package` declaration replaced by [`package_decl`].
+fn with_package(wit: &str) -> String {
+    match wit.split_once('\n') {
+        Some((first, rest)) if first.starts_with("package ") => {
+            format!("{}\n{rest}", package_decl())
+        }
+        _ => wit.to_owned(),
+    }
 }
 
 fn write_keysets<W: Write>(w: &mut W, keysets: &[ApiKeyset]) {
+    writeln!(w, "{}\n", package_decl()).unwrap();
     write!(
         w,
         "{}",
         indoc! {"
-            package nvim:api
-
             interface nvim-keysets {
               use nvim-types.{object}
         "}
@@ -70,17 +201,46 @@ fn write_keysets<W: Write>(w: &mut W, keysets: &[ApiKeyset]) {
 }
 
 fn write_funcs<W: Write>(w: &mut W, functions: &[ApiFunc], keysets: &[ApiKeyset]) {
-    write!(
+    let (deprecated, current): (Vec<_>, Vec<_>) = functions
+        .iter()
+        .filter_map(|func| WitFuncItem::new(func).ok())
+        .partition(|item| item.deprecated.is_some());
+
+    writeln!(w, "{}\n", package_decl()).unwrap();
+    write_interface(w, "nvim-api", &current, keysets);
+    writeln!(w, "\n/// Deprecated API functions, see `:help deprecated`.").unwrap();
+    write_interface(w, "nvim-api-deprecated", &deprecated, keysets);
+    writeln!(
         w,
         "{}",
         indoc! {"
-            package nvim:api
 
-            interface nvim-api {
-              use nvim-types.{primitive, array, dictionary, object, buffer, window, tabpage}
+            world plugin {
+              import nvim-api
+              import nvim-api-deprecated
+              import nvim-buffer
+              import log
+              import timer
+              import process
+              import storage
+            }
         "}
     )
     .unwrap();
+}
+
+fn write_interface<W: Write>(
+    w: &mut W,
+    name: &str,
+    functions: &[WitFuncItem],
+    keysets: &[ApiKeyset],
+) {
+    writeln!(w, "interface {name} {{").unwrap();
+    writeln!(
+        w,
+        "  use nvim-types.{{primitive, array, dictionary, object, buffer, window, tabpage}}"
+    )
+    .unwrap();
 
     let keysets_concat = keysets
         .iter()
@@ -94,23 +254,10 @@ fn write_funcs<W: Write>(w: &mut W, functions: &[ApiFunc], keysets: &[ApiKeyset]
     )
     .unwrap();
 
-    for func in functions {
-        if let Ok(item) = WitFuncItem::new(func) {
-            writeln!(w, "\n{}", item).unwrap();
-        }
+    for item in functions {
+        writeln!(w, "\n{}", item).unwrap();
     }
-    writeln!(
-        w,
-        "{}",
-        indoc! {"
-            }
-
-            world plugin {
-              import nvim-api
-            }
-        "}
-    )
-    .unwrap();
+    writeln!(w, "}}").unwrap();
 }
 
 struct WitKeysetItem<'a>(&'a ApiKeyset);
@@ -119,30 +266,61 @@ impl<'a> Display for WitKeysetItem<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "  record %{name} {{", name = &self.0.wit_name()).unwrap();
         for field in &self.0.fields {
-            writeln!(f, "    %{field}: object,", field = field.wit_name(),)?;
+            writeln!(
+                f,
+                "    %{field}: option<{type}>,",
+                field = field.wit_name(),
+                type = DisplayType(&field.type_)
+            )?;
         }
         write!(f, "  }}").unwrap();
         Ok(())
     }
 }
 
-struct WitFuncItem<'a>(&'a ApiFunc);
+struct WitFuncItem<'a> {
+    func: &'a ApiFunc,
+    /// The `nvim:api` package version where the function was introduced.
+    since: Option<NvimVersion>,
+    /// The `nvim:api` package version where the function was deprecated.
+    deprecated: Option<NvimVersion>,
+}
 
 impl<'a> WitFuncItem<'a> {
     fn new(func: &'a ApiFunc) -> Result<Self> {
-        Ok(Self(func))
+        if let Some(exclusion) = func.wasm_exclusion() {
+            bail!("`{}` {exclusion}", func.name);
+        }
+        if !func.is_wasm_compatible() {
+            bail!("`{}` has types that cannot be passed to WASM", func.name);
+        }
+        Ok(Self {
+            func,
+            since: func.attrs.since.as_ref().map(ApiVersion::package_version),
+            deprecated: func
+                .attrs
+                .deprecated_since
+                .as_ref()
+                .map(ApiVersion::package_version),
+        })
     }
 }
 
 impl<'a> Display for WitFuncItem<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "  /// Corresponds to `{name}`.", name = self.0.name).unwrap();
+        writeln!(f, "  /// Corresponds to `{name}`.", name = self.func.name).unwrap();
         writeln!(f, "  ///").unwrap();
-        writeln!(f, "  /// See `:help {name}`.", name = self.0.name).unwrap();
-        write!(f, "  %{name}: func", name = self.0.wit_name()).unwrap();
+        writeln!(f, "  /// See `:help {name}`.", name = self.func.name).unwrap();
+        if let Some(since) = self.since {
+            writeln!(f, "  @since(version = {since})").unwrap();
+        }
+        if let Some(deprecated) = self.deprecated {
+            writeln!(f, "  @deprecated(version = {deprecated})").unwrap();
+        }
+        write!(f, "  %{name}: func", name = self.func.wit_name()).unwrap();
         {
             write!(f, "(").unwrap();
-            let args = &self.0.args;
+            let args = &self.func.args;
             let mut first = true;
             for arg in &args.args {
                 if first {
@@ -156,7 +334,7 @@ impl<'a> Display for WitFuncItem<'a> {
             write!(f, ")").unwrap();
         }
         {
-            let return_ = &self.0.return_;
+            let return_ = &self.func.return_;
             match (return_.type_.as_ref(), return_.has_error) {
                 (None, false) => (),
                 (None, true) => {
@@ -184,8 +362,17 @@ impl<'a> Display for DisplayType<'a> {
             Integer => write!(f, "s64"),
             Float => write!(f, "float64"),
             String => write!(f, "string"),
+            Array(ApiArrayType {
+                inner_type,
+                len: Some(len),
+            }) => {
+                let inner_type = inner_type.as_deref().unwrap_or(&Object);
+                let items = vec![DisplayType(inner_type).to_string(); *len];
+                write!(f, "tuple<{}>", items.join(", "))
+            }
             Array(ApiArrayType {
                 inner_type: Some(inner_type),
+                len: None,
             }) => write!(f, "list<{}>", DisplayType(inner_type)),
             Array(_) => write!(f, "list<object>"),
             Dictionary(ApiDictionaryType {
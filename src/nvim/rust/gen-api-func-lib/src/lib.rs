@@ -1,41 +1,100 @@
-use std::{cell::RefCell, collections::HashMap};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+/// The environment variable pointing to the Neovim build directory, see [`nvim_build_dir`].
+pub const NVIM_BUILD_DIR_ENV: &str = "NVIM_BUILD_DIR";
+
+/// Returns the Neovim build directory, i.e. the CMake binary directory.
+///
+/// It is read from the `NVIM_BUILD_DIR` environment variable, which the CMake build sets when
+/// building the Rust crates, and defaults to the `build` directory used by `make`.
+pub fn nvim_build_dir() -> PathBuf {
+    std::env::var_os(NVIM_BUILD_DIR_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../../build"))
+}
+
+/// Returns the path of `api_metadata.mpack`, the API metadata generated by
+/// `src/nvim/generators/gen_api_dispatch.lua` while building Neovim.
+pub fn api_metadata_path() -> PathBuf {
+    nvim_build_dir().join("api_metadata.mpack")
+}
 
 /// Generates API data for all functions.
+///
+/// The functions are read from the API metadata, see [`api_metadata_path`]. Functions that
+/// cannot be converted are not included, see [`api_skipped_functions`].
 pub fn api_functions() -> Vec<ApiFunc> {
-    let keysets = parse_keysets(include_str!("../headers/keysets.h"));
-    let keyset_field_map = keysets
+    parse_api_metadata().0
+}
+
+/// Returns the API functions of the metadata that cannot be converted, and thus are missing from
+/// [`api_functions`].
+pub fn api_skipped_functions() -> Vec<SkippedApiFunc> {
+    parse_api_metadata().1
+}
+
+fn parse_api_metadata() -> (Vec<ApiFunc>, Vec<SkippedApiFunc>) {
+    let keyset_field_map = api_keysets()
         .into_iter()
         .map(|keyset| (keyset.name.clone(), keyset))
         .collect::<HashMap<_, _>>();
 
-    let api_headers = [
-        include_str!("../headers/autocmd.h.generated.h"),
-        include_str!("../headers/buffer.h.generated.h"),
-        include_str!("../headers/command.h.generated.h"),
-        include_str!("../headers/deprecated.h.generated.h"),
-        include_str!("../headers/extmark.h.generated.h"),
-        include_str!("../headers/options.h.generated.h"),
-        include_str!("../headers/tabpage.h.generated.h"),
-        include_str!("../headers/ui.h.generated.h"),
-        include_str!("../headers/vim.h.generated.h"),
-        include_str!("../headers/vimscript.h.generated.h"),
-        include_str!("../headers/wasm.h.generated.h"),
-        include_str!("../headers/win_config.h.generated.h"),
-        include_str!("../headers/window.h.generated.h"),
-    ];
-    api_headers
-        .iter()
-        .flat_map(|header| parse_funcs(header, &keyset_field_map))
-        .collect()
+    let path = api_metadata_path();
+    let metadata = std::fs::read(&path).unwrap_or_else(|err| {
+        panic!(
+            "Failed to read the API metadata {}: {err}. Build Neovim first, or set \
+             {NVIM_BUILD_DIR_ENV} to the Neovim build directory",
+            path.display()
+        )
+    });
+    let metadata: Vec<FuncMetadata> =
+        rmp_serde::from_slice(&metadata).expect("Failed to parse the API metadata");
+
+    let mut funcs = vec![];
+    let mut skipped = vec![];
+    for func in metadata {
+        match parse_func(&func, &keyset_field_map) {
+            Ok(func) => funcs.push(func),
+            Err(error) => skipped.push(SkippedApiFunc {
+                name: func.name,
+                error,
+            }),
+        }
+    }
+
+    (funcs, skipped)
+}
+
+/// Returns the API level of the Neovim being built, see `:help api-level`.
+///
+/// The level is read from the top-level `CMakeLists.txt`.
+pub fn nvim_api_level() -> i32 {
+    nvim_cmake_var("NVIM_API_LEVEL")
+}
+
+/// Returns the version of the `nvim:api` WIT package, `1.<api level>.0`.
+///
+/// Wasmtime links an import to the definition of the same package with the highest compatible
+/// version. The minor versions of a 0.x package are not compatible, so the package is versioned by
+/// API level under a stable major version: plugins built against an older API level still load.
+pub fn api_package_version() -> NvimVersion {
+    NvimVersion::new(1, nvim_cmake_var("NVIM_API_LEVEL"), 0)
 }
 
 /// Generates the information for all API Keysets.
 ///
 /// Keysets are dictionaries with fixed keys. See `src/nvim/api/keysets.h`.
 pub fn api_keysets() -> Vec<ApiKeyset> {
-    parse_keysets(include_str!("../headers/keysets.h"))
+    parse_keysets(include_str!("../../../api/keysets.h"))
 }
 
 /// Represents an API function.
@@ -56,6 +115,135 @@ impl ApiFunc {
     pub fn wit_name(&self) -> String {
         normalized_wit_identifier(&self.name)
     }
+
+    /// Returns true if all the argument types and the return type can be passed between the host
+    /// and WASM guests. See [`ApiType::is_wasm_compatible`].
+    ///
+    /// The return type must not nest fixed-length arrays either, see
+    /// [`ApiType::nests_fixed_len_array`].
+    pub fn is_wasm_compatible(&self) -> bool {
+        self.args
+            .args
+            .iter()
+            .map(|arg| &arg.type_)
+            .chain(&self.return_.type_)
+            .all(ApiType::is_wasm_compatible)
+            && !self
+                .return_
+                .type_
+                .as_ref()
+                .is_some_and(ApiType::nests_fixed_len_array)
+    }
+
+    /// Returns the function replacing this one for WASM guests, if any.
+    ///
+    /// Some Lua only functions take Lua callbacks, which WASM guests cannot pass. They have WASM
+    /// versions in `src/nvim/api/wasm.c` instead.
+    pub fn wasm_variant(&self) -> Option<&'static str> {
+        WASM_VARIANTS
+            .iter()
+            .find(|(name, _)| *name == self.name)
+            .map(|(_, variant)| *variant)
+    }
+
+    /// Returns why the function is left out of the WASM interface, if it is.
+    pub fn wasm_exclusion(&self) -> Option<WasmExclusion> {
+        if let Some(variant) = self.wasm_variant() {
+            Some(WasmExclusion::ReplacedBy(variant))
+        } else if CHANNEL_BOUND_FUNCTIONS.contains(&self.name.as_str()) {
+            Some(WasmExclusion::ChannelBound)
+        } else if self.name.starts_with("nvim_ui_")
+            || UI_PROTOCOL_FUNCTIONS.contains(&self.name.as_str())
+        {
+            Some(WasmExclusion::UiProtocol)
+        } else if TRUST_FUNCTIONS.contains(&self.name.as_str()) {
+            Some(WasmExclusion::Trust)
+        } else if SETTINGS_FUNCTIONS.contains(&self.name.as_str()) {
+            Some(WasmExclusion::Settings)
+        } else if self.attrs.is_rpc_only() {
+            Some(WasmExclusion::RemoteOnly)
+        } else {
+            None
+        }
+    }
+}
+
+/// The reason why an API function is left out of the WASM interface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WasmExclusion {
+    /// The function has a WASM version, see [`ApiFunc::wasm_variant`].
+    ReplacedBy(&'static str),
+    /// The function acts on the state of the calling RPC channel, e.g. its event subscriptions or
+    /// client info. WASM plugins do not have a channel.
+    ChannelBound,
+    /// The function is part of the UI protocol, for external UIs attached with `nvim_ui_attach`.
+    /// See `:help ui`.
+    UiProtocol,
+    /// The function manages the trust database of WASM plugins, which plugins must not change.
+    Trust,
+    /// The function changes the settings of the WASM runtime, e.g. the log level of plugins, which
+    /// are up to the user.
+    Settings,
+    /// The function is only available to RPC channels, see [`ApiFuncAttrs::is_rpc_only`].
+    RemoteOnly,
+}
+
+impl Display for WasmExclusion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WasmExclusion::ReplacedBy(variant) => write!(f, "is replaced by `{variant}` for WASM"),
+            WasmExclusion::ChannelBound => write!(f, "acts on the calling RPC channel"),
+            WasmExclusion::UiProtocol => write!(f, "is part of the UI protocol for external UIs"),
+            WasmExclusion::Trust => write!(f, "manages the trust of WASM plugins"),
+            WasmExclusion::Settings => write!(f, "changes the settings of the WASM runtime"),
+            WasmExclusion::RemoteOnly => write!(f, "is only available to RPC channels"),
+        }
+    }
+}
+
+/// Functions acting on the state of the calling channel.
+const CHANNEL_BOUND_FUNCTIONS: &[&str] = &[
+    "nvim_buf_detach",
+    "nvim_call_atomic",
+    "nvim_error_event",
+    "nvim_get_api_info",
+    "nvim_set_client_info",
+    "nvim_subscribe",
+    "nvim_unsubscribe",
+];
+
+/// Functions of the UI protocol, besides the `nvim_ui_*` ones. `ui_attach` is the deprecated
+/// version of `nvim_ui_attach`; the other deprecated UI functions are aliases, which are skipped.
+const UI_PROTOCOL_FUNCTIONS: &[&str] = &[
+    "nvim_input_mouse",
+    "nvim_select_popupmenu_item",
+    "ui_attach",
+];
+
+/// Functions managing the trust database of WASM plugins, see `:WasmTrust`.
+const TRUST_FUNCTIONS: &[&str] = &["nvim_wasm_trust"];
+
+/// Functions changing the settings of the WASM runtime.
+const SETTINGS_FUNCTIONS: &[&str] = &["nvim_wasm_set_log"];
+
+/// The WASM versions of API functions, as `(function, WASM version)`.
+const WASM_VARIANTS: &[(&str, &str)] = &[
+    ("nvim_buf_attach", "nvim_wasm_buf_attach"),
+    ("nvim_buf_call", "nvim_wasm_buf_enter"),
+    ("nvim_win_call", "nvim_wasm_win_enter"),
+    (
+        "nvim_set_decoration_provider",
+        "nvim_wasm_set_decoration_provider",
+    ),
+];
+
+/// Represents an API function of the metadata that cannot be converted.
+#[derive(Debug)]
+pub struct SkippedApiFunc {
+    /// The function name.
+    pub name: String,
+    /// The reason why the function cannot be converted.
+    pub error: anyhow::Error,
 }
 
 /// Represents the function's arguments.
@@ -67,9 +255,15 @@ pub struct ApiFuncArgs {
     /// If true, channel ID should be passed as the first argument when calling the native
     /// function.
     pub has_channel_id: bool,
+    /// If a Lua state is accepted.
+    /// If true, a Lua state should be passed as the argument just after all normal arguments when
+    /// calling the native function. Passing NULL makes the function return its result instead of
+    /// pushing it to the Lua stack.
+    pub has_lua_state: bool,
     /// If an arena is required.
-    /// If true, an arena should be passed as the argument just after all normal arguments when
-    /// calling the native function. The returned value will be allocated in the arena.
+    /// If true, an arena should be passed as the argument just after all normal arguments and the
+    /// Lua state when calling the native function. The returned value will be allocated in the
+    /// arena.
     pub has_arena: bool,
 }
 
@@ -101,7 +295,7 @@ pub struct ApiFuncReturn {
 }
 
 /// Represents all possible API types.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum ApiType {
     Boolean,
     Integer,
@@ -118,7 +312,7 @@ pub enum ApiType {
 }
 
 /// Represents an Array type.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ApiArrayType {
     /// The inner type of the array.
     ///
@@ -126,10 +320,22 @@ pub struct ApiArrayType {
     /// If `None`, the type is opaque. Arrays with an inner type is annotate with `ArrayOf(...)`,
     /// see `src/nvim/api/private/defs.h`.
     pub inner_type: Option<Box<ApiType>>,
+    /// The fixed length of the array.
+    ///
+    /// Arrays with a fixed length are annotated with `ArrayOf(type, len)`, e.g. the `(row, col)`
+    /// tuple `ArrayOf(Integer, 2)`. If `None`, the array can have any length. The length is one of
+    /// [`TUPLE_LENGTHS`].
+    pub len: Option<usize>,
 }
 
+/// The lengths of the fixed-length arrays, which are passed to WASM guests as tuples.
+///
+/// It must match the tuple conversions implemented by `impl_wasm_conversion_for_tuple!` in
+/// `nvim-wasm/src/types.rs`.
+pub const TUPLE_LENGTHS: std::ops::RangeInclusive<usize> = 2..=4;
+
 /// Represents an Dictionary type.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ApiDictionaryType {
     /// The type of the values of the dictionary.
     ///
@@ -141,7 +347,8 @@ pub struct ApiDictionaryType {
 /// Represents an API keyset.
 ///
 /// Keysets are dictionaries with fixed keys. See `src/nvim/api/keysets.h`. The fields are always
-/// `Object`s.
+/// stored as `Object`s, but each field also carries the type the API function expects, see
+/// [`ApiField::type_`].
 #[derive(Debug, Clone)]
 pub struct ApiKeyset {
     /// The original type name of the keyset.
@@ -162,6 +369,12 @@ impl ApiKeyset {
 pub struct ApiField {
     /// The original name of the field.
     pub name: String,
+    /// The intended type of the field.
+    ///
+    /// Notice that it is only a *type hint*. The field is always stored as an `Object` in the
+    /// native keyset struct. Fields that accept multiple types, or whose type is unknown, are
+    /// `ApiType::Object`.
+    pub type_: ApiType,
 }
 
 impl ApiField {
@@ -176,20 +389,83 @@ impl ApiField {
 pub struct ApiFuncAttrs {
     /// API level where the function was introduced.
     pub since: Option<ApiVersion>,
+    /// API level where the function was deprecated.
+    pub deprecated_since: Option<ApiVersion>,
     /// True if this is an api-fast function. See `:help api-fast`.
     pub fast: bool,
     /// True if this is a remote only function.
     pub remote_only: bool,
+    /// True if this is a Lua only function, i.e. it is not available to Vimscript.
+    pub lua_only: bool,
     /// True if the function is not allowed when textlock is active. See `:textlock`.
     pub check_text_lock: bool,
 }
 
+impl ApiFuncAttrs {
+    /// Returns true if the function is only available to RPC channels.
+    ///
+    /// Functions that are both remote only and Lua only (e.g. `nvim_chan_send`) are available to
+    /// RPC channels and Lua, and only hidden from Vimscript.
+    pub fn is_rpc_only(&self) -> bool {
+        self.remote_only && !self.lua_only
+    }
+}
+
 /// Represents an API level.
 #[derive(Debug)]
 pub struct ApiVersion {
     pub version: i32,
 }
 
+impl ApiVersion {
+    /// Returns the version of the `nvim:api` package where this API level was introduced, see
+    /// [`api_package_version`].
+    ///
+    /// Levels higher than the current one, which mark functions that are not in upstream Neovim yet
+    /// (e.g. `nvim_wasm_*`), map to the current package version.
+    pub fn package_version(&self) -> NvimVersion {
+        let level = u32::try_from(self.version).expect("API levels are non-negative");
+        NvimVersion::new(1, level, 0).min(api_package_version())
+    }
+}
+
+/// Represents the version of the `nvim:api` package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct NvimVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl NvimVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl Display for NvimVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Returns the value of the variable `name` set in the top-level `CMakeLists.txt`, e.g.
+/// `set(NVIM_API_LEVEL 12)`.
+fn nvim_cmake_var<T: FromStr>(name: &str) -> T {
+    include_str!("../../../../../CMakeLists.txt")
+        .lines()
+        .find_map(|line| {
+            let value = line.trim().strip_prefix("set(")?.strip_prefix(name)?;
+            let (value, _) = value.strip_prefix(' ')?.split_once(')')?;
+            value.trim().parse().ok()
+        })
+        .unwrap_or_else(|| panic!("Cannot find `{name}` in CMakeLists.txt"))
+}
+
 struct TokenIterator {
     text: String,
     state: RefCell<TokenIteratorState>,
@@ -251,7 +527,6 @@ impl TokenIteratorState {
 }
 
 const SPECIAL_CHARS: [char; 5] = ['(', ')', ',', '*', ';'];
-const DLLEXPORT: &str = "DLLEXPORT";
 
 impl TokenIterator {
     fn new(text: String) -> Self {
@@ -354,19 +629,11 @@ fn parse_type(it: &TokenIterator) -> Result<ParseTypeCase> {
     let name = it.next_identifier()?;
     let type_ = match name {
         "void" => ParseTypeCase::Void,
-        "uint64_t" => ParseTypeCase::ChannelId,
-        "Error" => ParseTypeCase::Error,
-        "Arena" => ParseTypeCase::Arena,
         "Dict" => {
             let keyset = parse_single_arg(it)?;
             ParseTypeCase::Keyset(keyset.to_owned())
         }
-        tp if ApiType::str_is_nested_type(tp) => {
-            let inner_type = parse_single_arg(it)?;
-            ParseTypeCase::Normal(ApiType::nested_type_from_strs(name, inner_type).context(
-                format!("Type `{inner_type}` is not a valid inner type for `{name}`"),
-            )?)
-        }
+        tp if ApiType::str_is_nested_type(tp) => ParseTypeCase::Normal(parse_nested_type(it, tp)?),
         tp => ParseTypeCase::Normal(
             ApiType::simple_type_from_str(name).context(format!("Unknown type `{tp}`"))?,
         ),
@@ -375,133 +642,97 @@ fn parse_type(it: &TokenIterator) -> Result<ParseTypeCase> {
     Ok(type_)
 }
 
-fn parse_arg(it: &TokenIterator) -> Result<ParseApiArg> {
-    let type_ = parse_type(it)?;
-    let name = match &type_ {
-        ParseTypeCase::Void => "",
-        t if t.arg_is_pointer() => {
-            it.expect_next_token("*")?;
-            it.next_identifier()?
-        }
-        _ => it.next_identifier()?,
-    }
-    .to_owned();
+/// Parses the type arguments of a typed container, e.g. `(String)` of `ArrayOf(String)` or
+/// `(Integer, 2)` of `ArrayOf(Integer, 2)`. The inner type can itself be a typed container.
+fn parse_nested_type(it: &TokenIterator, name: &str) -> Result<ApiType> {
+    it.expect_next_token("(")?;
+    let inner_type = match parse_type(it)? {
+        ParseTypeCase::Normal(type_) => type_,
+        type_ => bail!("Type `{type_:?}` is not a valid inner type for `{name}`"),
+    };
+    let len = if it.peek_token() == Some(",") {
+        let _ = it.next_token().unwrap();
+        let len = it.next_number()?;
+        Some(usize::try_from(len).context(format!("Invalid length {len} for `{name}`"))?)
+    } else {
+        None
+    };
+    it.expect_next_token(")")?;
 
-    Ok(ParseApiArg { type_, name })
+    ApiType::nested_type(name, inner_type, len)
 }
 
-fn parse_args(it: &TokenIterator) -> Result<Vec<ParseApiArg>> {
-    it.expect_next_token("(")?;
-    let mut args = Vec::new();
-    loop {
-        args.push(parse_arg(it)?);
-        let token = it.next_token().context("Unexpected end of line")?;
-        if token == ")" {
-            return Ok(args);
-        }
-        if token != "," {
-            bail!("Expect ',' or ')', got '{token}'");
+/// Parses a type as written in the API metadata, e.g. `ArrayOf(Integer, 2)` or `Dict(keymap) *`.
+///
+/// Returns `None` for `void`.
+fn parse_metadata_type(type_: &str, keyset_field_map: &KeysetFieldMap) -> Result<Option<ApiType>> {
+    let it = TokenIterator::new(type_.to_owned());
+    let result = match parse_type(&it)? {
+        ParseTypeCase::Normal(type_) => Some(type_),
+        ParseTypeCase::Void => None,
+        ParseTypeCase::Keyset(keyset) => {
+            it.expect_next_token("*")?;
+            let info = keyset_field_map
+                .get(&keyset)
+                .context(format!("Unknown keyset type `{keyset}`"))?;
+            Some(ApiType::Keyset(info.clone()))
         }
+    };
+    if let Some(token) = it.next_token() {
+        bail!("Unexpected '{token}' after type `{type_}`");
     }
-}
-
-fn parse_attributes(it: &TokenIterator) -> Result<ParseAttribute> {
-    let attr = it.next_identifier()?;
-    Ok(match attr {
-        "FUNC_API_SINCE" => {
-            it.expect_next_token("(")?;
-            let version = it.next_number()?;
-            it.expect_next_token(")")?;
 
-            ParseAttribute::Since(version)
-        }
-        "FUNC_API_FAST" => ParseAttribute::Fast,
-        "FUNC_API_REMOTE_ONLY" => ParseAttribute::RemoteOnly,
-        "FUNC_API_CHECK_TEXTLOCK" => ParseAttribute::CheckTextLock,
-        _ => bail!("Unknown attribute: {attr}"),
-    })
+    Ok(result)
 }
 
-fn parse_line(line: &str, keyset_field_map: &KeysetFieldMap) -> Result<ApiFunc> {
-    let it = TokenIterator::new(line.to_owned());
-    let first_token = it.peek_token().context("Unexpected end of line")?;
-    if first_token == DLLEXPORT {
-        let _ = it.next_token().unwrap();
+fn parse_func(func: &FuncMetadata, keyset_field_map: &KeysetFieldMap) -> Result<ApiFunc> {
+    if let Some(impl_name) = &func.impl_name {
+        bail!("Deprecated alias of `{impl_name}`");
     }
-    let return_type = match parse_type(&it)? {
-        ParseTypeCase::Normal(type_) => Some(type_),
-        ParseTypeCase::Void => None,
-        t => bail!("Unexpected return type: {t:?}"),
-    };
-    let name = it.next_identifier()?;
-    let parsed_args = parse_args(&it)?;
-
-    let mut attrs = ApiFuncAttrs::default();
-    while it
-        .peek_token()
-        .context("Expect ';' or attributes, got end of line")?
-        != ";"
-    {
-        let attr = parse_attributes(&it)?;
-        match attr {
-            ParseAttribute::Since(version) => {
-                attrs.since = Some(ApiVersion { version });
-            }
-            ParseAttribute::Fast => attrs.fast = true,
-            ParseAttribute::RemoteOnly => attrs.remote_only = true,
-            ParseAttribute::CheckTextLock => attrs.check_text_lock = true,
-        }
+    if func.receives_array_args {
+        bail!("Takes the arguments of the RPC call as an `Array`");
     }
 
-    let mut args = ApiFuncArgs {
-        args: vec![],
-        has_channel_id: false,
-        has_arena: false,
-    };
-    let mut return_ = ApiFuncReturn {
-        type_: return_type,
-        has_error: false,
-    };
-    let len = parsed_args.len();
-    for arg in parsed_args {
-        match arg.type_ {
-            ParseTypeCase::Void => {
-                if len > 1 {
-                    bail!("If the argument is void, then it must be the only argument, but there are more");
-                }
-            }
-            ParseTypeCase::ChannelId => {
-                args.has_channel_id = true;
-            }
-            ParseTypeCase::Arena => {
-                args.has_arena = true;
-            }
-            ParseTypeCase::Error => {
-                return_.has_error = true;
-            }
-            ParseTypeCase::Normal(type_) => {
-                args.args.push(ApiArg {
-                    type_,
-                    name: arg.name,
-                });
-            }
-            ParseTypeCase::Keyset(keyset) => {
-                let info = keyset_field_map
-                    .get(&keyset)
-                    .context("Unknown keyset type {keyset}")?;
-                args.args.push(ApiArg {
-                    name: arg.name,
-                    type_: ApiType::Keyset(info.clone()),
-                })
-            }
-        }
-    }
+    let args = func
+        .parameters
+        .iter()
+        .map(|(type_, name)| {
+            let type_ = parse_metadata_type(type_, keyset_field_map)
+                .context(format!("Invalid type for parameter `{name}`"))?
+                .context(format!("Parameter `{name}` cannot be void"))?;
+            Ok(ApiArg {
+                name: name.clone(),
+                type_,
+            })
+        })
+        .collect::<Result<_>>()?;
+    let return_type =
+        parse_metadata_type(&func.return_type, keyset_field_map).context("Invalid return type")?;
 
     Ok(ApiFunc {
-        name: name.to_owned(),
-        args,
-        return_,
-        attrs,
+        name: func.name.clone(),
+        args: ApiFuncArgs {
+            args,
+            has_channel_id: func.receives_channel_id,
+            has_lua_state: func.has_lua_imp,
+            has_arena: func.arena_return,
+        },
+        return_: ApiFuncReturn {
+            type_: return_type,
+            has_error: func.can_fail,
+        },
+        attrs: ApiFuncAttrs {
+            // Internal functions (`nvim__*`) have a negative API level.
+            since: func
+                .since
+                .filter(|version| *version >= 0)
+                .map(|version| ApiVersion { version }),
+            deprecated_since: func.deprecated_since.map(|version| ApiVersion { version }),
+            fast: func.fast,
+            remote_only: func.remote_only,
+            lua_only: func.lua_only,
+            check_text_lock: func.textlock || func.textlock_allow_cmdwin,
+        },
     })
 }
 
@@ -533,13 +764,18 @@ fn parse_keyset(it: &TokenIterator) -> Result<Option<ApiKeyset>> {
     it.expect_next_token("}")?;
 
     match parse_type(it)? {
-        ParseTypeCase::Keyset(name) => Ok(Some(ApiKeyset {
-            name,
-            fields: keys
+        ParseTypeCase::Keyset(name) => {
+            let fields = keys
                 .into_iter()
-                .map(|x| ApiField { name: x.to_owned() })
-                .collect(),
-        })),
+                .map(|field| {
+                    Ok(ApiField {
+                        name: field.to_owned(),
+                        type_: keyset_field_type(&name, field)?.unwrap_or(ApiType::Object),
+                    })
+                })
+                .collect::<Result<_>>()?;
+            Ok(Some(ApiKeyset { name, fields }))
+        }
         type_ => {
             bail!("Expect a keyset type (i.e., `Dict(...)`), got {type_:?}");
         }
@@ -565,15 +801,275 @@ fn parse_keysets(header: &str) -> Vec<ApiKeyset> {
     result
 }
 
-type KeysetFieldMap = HashMap<String, ApiKeyset>;
+/// The intended types of keyset fields, as `(keyset, field, type)`.
+///
+/// Keyset fields are declared as `Object` in `src/nvim/api/keysets.h`, so the types here are taken
+/// from the validation code of the API functions consuming the keysets. Fields that accept more
+/// than one type (e.g. a group name or ID) are listed as `Object`. Every field must be listed, so
+/// that new fields get a type, see [`keyset_field_type_errors`].
+const KEYSET_FIELD_TYPES: &[(&str, &str, &str)] = &[
+    ("context", "types", "ArrayOf(String)"),
+    ("set_extmark", "id", "Integer"),
+    ("set_extmark", "end_line", "Integer"),
+    ("set_extmark", "end_row", "Integer"),
+    ("set_extmark", "end_col", "Integer"),
+    ("set_extmark", "virt_text", "Array"),
+    ("set_extmark", "virt_text_pos", "String"),
+    ("set_extmark", "virt_text_win_col", "Integer"),
+    ("set_extmark", "virt_text_hide", "Boolean"),
+    ("set_extmark", "hl_eol", "Boolean"),
+    ("set_extmark", "hl_mode", "String"),
+    ("set_extmark", "ephemeral", "Boolean"),
+    ("set_extmark", "priority", "Integer"),
+    ("set_extmark", "right_gravity", "Boolean"),
+    ("set_extmark", "end_right_gravity", "Boolean"),
+    ("set_extmark", "virt_lines", "Array"),
+    ("set_extmark", "virt_lines_above", "Boolean"),
+    ("set_extmark", "virt_lines_leftcol", "Boolean"),
+    ("set_extmark", "strict", "Boolean"),
+    ("set_extmark", "sign_text", "String"),
+    ("set_extmark", "conceal", "String"),
+    ("set_extmark", "spell", "Boolean"),
+    ("set_extmark", "ui_watched", "Boolean"),
+    ("set_extmark", "hl_group", "Object"),
+    ("set_extmark", "sign_hl_group", "Object"),
+    ("set_extmark", "number_hl_group", "Object"),
+    ("set_extmark", "line_hl_group", "Object"),
+    ("set_extmark", "cursorline_hl_group", "Object"),
+    ("keymap", "noremap", "Boolean"),
+    ("keymap", "nowait", "Boolean"),
+    ("keymap", "silent", "Boolean"),
+    ("keymap", "script", "Boolean"),
+    ("keymap", "expr", "Boolean"),
+    ("keymap", "unique", "Boolean"),
+    ("keymap", "desc", "String"),
+    ("keymap", "replace_keycodes", "Boolean"),
+    ("keymap", "callback", "Object"),
+    ("get_commands", "builtin", "Boolean"),
+    ("user_command", "addr", "String"),
+    ("user_command", "bang", "Boolean"),
+    ("user_command", "bar", "Boolean"),
+    ("user_command", "desc", "String"),
+    ("user_command", "force", "Boolean"),
+    ("user_command", "keepscript", "Boolean"),
+    ("user_command", "register_", "Boolean"),
+    ("user_command", "complete", "Object"),
+    ("user_command", "count", "Object"),
+    ("user_command", "nargs", "Object"),
+    ("user_command", "preview", "Object"),
+    ("user_command", "range", "Object"),
+    ("float_config", "row", "Float"),
+    ("float_config", "col", "Float"),
+    ("float_config", "width", "Integer"),
+    ("float_config", "height", "Integer"),
+    ("float_config", "anchor", "String"),
+    ("float_config", "relative", "String"),
+    ("float_config", "win", "Integer"),
+    ("float_config", "bufpos", "ArrayOf(Integer, 2)"),
+    ("float_config", "external", "Boolean"),
+    ("float_config", "focusable", "Boolean"),
+    ("float_config", "zindex", "Integer"),
+    ("float_config", "title_pos", "String"),
+    ("float_config", "style", "String"),
+    ("float_config", "noautocmd", "Boolean"),
+    ("float_config", "border", "Object"),
+    ("float_config", "title", "Object"),
+    ("runtime", "is_lua", "Boolean"),
+    ("runtime", "do_source", "Boolean"),
+    ("eval_statusline", "winid", "Integer"),
+    ("eval_statusline", "maxwidth", "Integer"),
+    ("eval_statusline", "fillchar", "String"),
+    ("eval_statusline", "highlights", "Boolean"),
+    ("eval_statusline", "use_winbar", "Boolean"),
+    ("eval_statusline", "use_tabline", "Boolean"),
+    ("eval_statusline", "use_statuscol_lnum", "Integer"),
+    ("option", "scope", "String"),
+    ("option", "win", "Integer"),
+    ("option", "buf", "Integer"),
+    ("option", "filetype", "String"),
+    ("highlight", "bold", "Boolean"),
+    ("highlight", "standout", "Boolean"),
+    ("highlight", "strikethrough", "Boolean"),
+    ("highlight", "underline", "Boolean"),
+    ("highlight", "undercurl", "Boolean"),
+    ("highlight", "underdouble", "Boolean"),
+    ("highlight", "underdotted", "Boolean"),
+    ("highlight", "underdashed", "Boolean"),
+    ("highlight", "italic", "Boolean"),
+    ("highlight", "reverse", "Boolean"),
+    ("highlight", "altfont", "Boolean"),
+    ("highlight", "nocombine", "Boolean"),
+    ("highlight", "default_", "Boolean"),
+    ("highlight", "link", "String"),
+    ("highlight", "global_link", "String"),
+    ("highlight", "fallback", "Boolean"),
+    ("highlight", "blend", "Integer"),
+    ("highlight", "fg_indexed", "Boolean"),
+    ("highlight", "bg_indexed", "Boolean"),
+    ("highlight", "cterm", "Object"),
+    ("highlight", "foreground", "Object"),
+    ("highlight", "fg", "Object"),
+    ("highlight", "background", "Object"),
+    ("highlight", "bg", "Object"),
+    ("highlight", "ctermfg", "Object"),
+    ("highlight", "ctermbg", "Object"),
+    ("highlight", "special", "Object"),
+    ("highlight", "sp", "Object"),
+    ("highlight_cterm", "bold", "Boolean"),
+    ("highlight_cterm", "standout", "Boolean"),
+    ("highlight_cterm", "strikethrough", "Boolean"),
+    ("highlight_cterm", "underline", "Boolean"),
+    ("highlight_cterm", "undercurl", "Boolean"),
+    ("highlight_cterm", "underdouble", "Boolean"),
+    ("highlight_cterm", "underdotted", "Boolean"),
+    ("highlight_cterm", "underdashed", "Boolean"),
+    ("highlight_cterm", "italic", "Boolean"),
+    ("highlight_cterm", "reverse", "Boolean"),
+    ("highlight_cterm", "altfont", "Boolean"),
+    ("highlight_cterm", "nocombine", "Boolean"),
+    ("get_highlight", "id", "Integer"),
+    ("get_highlight", "name", "String"),
+    ("get_highlight", "link", "Boolean"),
+    ("set_decoration_provider", "on_start", "Object"),
+    ("set_decoration_provider", "on_buf", "Object"),
+    ("set_decoration_provider", "on_win", "Object"),
+    ("set_decoration_provider", "on_line", "Object"),
+    ("set_decoration_provider", "on_end", "Object"),
+    ("set_decoration_provider", "_on_hl_def", "Object"),
+    ("set_decoration_provider", "_on_spell_nav", "Object"),
+    ("wasm_decoration_provider", "on_start", "String"),
+    ("wasm_decoration_provider", "on_buf", "String"),
+    ("wasm_decoration_provider", "on_win", "String"),
+    ("wasm_decoration_provider", "on_line", "String"),
+    ("wasm_decoration_provider", "on_end", "String"),
+    ("wasm_buf_attach", "on_lines", "String"),
+    ("wasm_buf_attach", "on_bytes", "String"),
+    ("wasm_buf_attach", "on_changedtick", "String"),
+    ("wasm_buf_attach", "on_detach", "String"),
+    ("wasm_buf_attach", "utf_sizes", "Boolean"),
+    ("wasm_buf_attach", "preview", "Boolean"),
+    ("wasm_load_bytes", "name", "String"),
+    ("wasm_set_log", "level", "Integer"),
+    ("wasm_set_log", "notify", "Boolean"),
+    ("wasm_trust", "action", "String"),
+    ("wasm_trust", "capabilities", "Array"),
+    ("win_text_height", "start_row", "Integer"),
+    ("win_text_height", "end_row", "Integer"),
+    ("win_text_height", "start_vcol", "Integer"),
+    ("win_text_height", "end_vcol", "Integer"),
+    ("clear_autocmds", "buffer", "Integer"),
+    ("clear_autocmds", "event", "Object"),
+    ("clear_autocmds", "group", "Object"),
+    ("clear_autocmds", "pattern", "Object"),
+    ("create_autocmd", "buffer", "Integer"),
+    ("create_autocmd", "command", "String"),
+    ("create_autocmd", "desc", "String"),
+    ("create_autocmd", "nested", "Boolean"),
+    ("create_autocmd", "once", "Boolean"),
+    ("create_autocmd", "callback", "Object"),
+    ("create_autocmd", "group", "Object"),
+    ("create_autocmd", "pattern", "Object"),
+    ("exec_autocmds", "buffer", "Integer"),
+    ("exec_autocmds", "modeline", "Boolean"),
+    ("exec_autocmds", "group", "Object"),
+    ("exec_autocmds", "pattern", "Object"),
+    ("exec_autocmds", "data", "Object"),
+    ("get_autocmds", "event", "Object"),
+    ("get_autocmds", "group", "Object"),
+    ("get_autocmds", "pattern", "Object"),
+    ("get_autocmds", "buffer", "Object"),
+    ("create_augroup", "clear", "Boolean"),
+    ("cmd", "cmd", "String"),
+    ("cmd", "range", "Array"),
+    ("cmd", "count", "Integer"),
+    ("cmd", "reg", "String"),
+    ("cmd", "bang", "Boolean"),
+    ("cmd", "args", "Array"),
+    ("cmd", "addr", "String"),
+    ("cmd", "nextcmd", "String"),
+    ("cmd", "magic", "Object"),
+    ("cmd", "mods", "Object"),
+    ("cmd", "nargs", "Object"),
+    ("cmd_magic", "file", "Boolean"),
+    ("cmd_magic", "bar", "Boolean"),
+    ("cmd_mods", "silent", "Boolean"),
+    ("cmd_mods", "emsg_silent", "Boolean"),
+    ("cmd_mods", "unsilent", "Boolean"),
+    ("cmd_mods", "sandbox", "Boolean"),
+    ("cmd_mods", "noautocmd", "Boolean"),
+    ("cmd_mods", "browse", "Boolean"),
+    ("cmd_mods", "confirm", "Boolean"),
+    ("cmd_mods", "hide", "Boolean"),
+    ("cmd_mods", "horizontal", "Boolean"),
+    ("cmd_mods", "keepalt", "Boolean"),
+    ("cmd_mods", "keepjumps", "Boolean"),
+    ("cmd_mods", "keepmarks", "Boolean"),
+    ("cmd_mods", "keeppatterns", "Boolean"),
+    ("cmd_mods", "lockmarks", "Boolean"),
+    ("cmd_mods", "noswapfile", "Boolean"),
+    ("cmd_mods", "tab", "Integer"),
+    ("cmd_mods", "verbose", "Integer"),
+    ("cmd_mods", "vertical", "Boolean"),
+    ("cmd_mods", "split", "String"),
+    ("cmd_mods", "filter", "Object"),
+    ("cmd_mods_filter", "pattern", "String"),
+    ("cmd_mods_filter", "force", "Boolean"),
+    ("cmd_opts", "output", "Boolean"),
+    ("echo_opts", "verbose", "Boolean"),
+    ("exec_opts", "output", "Boolean"),
+];
+
+/// Returns the intended type of the field `field` of keyset `keyset`, or `None` if it is missing
+/// from [`KEYSET_FIELD_TYPES`].
+fn keyset_field_type(keyset: &str, field: &str) -> Result<Option<ApiType>> {
+    let Some((_, _, type_)) = KEYSET_FIELD_TYPES
+        .iter()
+        .find(|(k, f, _)| *k == keyset && *f == field)
+    else {
+        return Ok(None);
+    };
+    let it = TokenIterator::new(type_.to_string());
+    match parse_type(&it)? {
+        ParseTypeCase::Normal(type_) => Ok(Some(type_)),
+        type_ => bail!(
+            "Expect the type of keyset field {keyset}.{field} to be a normal type, got {type_:?}"
+        ),
+    }
+}
 
-fn parse_funcs(header: &str, keyset_field_map: &KeysetFieldMap) -> Vec<ApiFunc> {
-    header
-        .lines()
-        .filter_map(|line| parse_line(line, keyset_field_map).ok())
-        .collect()
+/// Returns the mismatches between `keysets` and [`KEYSET_FIELD_TYPES`]: the fields missing from
+/// the table, which are typed `object`, and the entries of the table matching no field, e.g.
+/// because of a typo or a removed field.
+pub fn keyset_field_type_errors(keysets: &[ApiKeyset]) -> Vec<String> {
+    let mut errors = vec![];
+    for keyset in keysets {
+        for field in &keyset.fields {
+            if !KEYSET_FIELD_TYPES
+                .iter()
+                .any(|(k, f, _)| *k == keyset.name && *f == field.name)
+            {
+                errors.push(format!(
+                    "keyset field `{}.{}` has no type in `KEYSET_FIELD_TYPES`",
+                    keyset.name, field.name
+                ));
+            }
+        }
+    }
+    for (keyset, field, _) in KEYSET_FIELD_TYPES {
+        if !keysets
+            .iter()
+            .any(|k| k.name == *keyset && k.fields.iter().any(|f| f.name == *field))
+        {
+            errors.push(format!(
+                "`KEYSET_FIELD_TYPES` lists `{keyset}.{field}`, which is not a keyset field"
+            ));
+        }
+    }
+    errors
 }
 
+type KeysetFieldMap = HashMap<String, ApiKeyset>;
+
 impl ApiType {
     fn simple_type_from_str(name: &str) -> Option<Self> {
         Some(match name {
@@ -581,7 +1077,10 @@ impl ApiType {
             "Integer" => Self::Integer,
             "Float" => Self::Float,
             "String" => Self::String,
-            "Array" => Self::Array(ApiArrayType { inner_type: None }),
+            "Array" => Self::Array(ApiArrayType {
+                inner_type: None,
+                len: None,
+            }),
             "Dictionary" => Self::Dictionary(ApiDictionaryType { inner_type: None }),
             "LuaRef" => Self::LuaRef,
             "Buffer" => Self::Buffer,
@@ -593,21 +1092,72 @@ impl ApiType {
     }
 
     fn str_is_nested_type(name: &str) -> bool {
-        const NESTED_TYPES: [&str; 2] = ["ArrayOf", "Dict"];
+        const NESTED_TYPES: [&str; 2] = ["ArrayOf", "DictionaryOf"];
         NESTED_TYPES.contains(&name)
     }
 
-    fn nested_type_from_strs(name: &str, inner_type: &str) -> Option<Self> {
-        Some(match name {
-            "ArrayOf" => Self::Array(ApiArrayType {
-                inner_type: Some(Box::new(ApiType::simple_type_from_str(inner_type)?)),
+    fn nested_type(name: &str, inner_type: ApiType, len: Option<usize>) -> Result<Self> {
+        Ok(match (name, len) {
+            ("ArrayOf", Some(len)) if !TUPLE_LENGTHS.contains(&len) => bail!(
+                "`ArrayOf` can only have a fixed length of {} to {}, got {len}",
+                TUPLE_LENGTHS.start(),
+                TUPLE_LENGTHS.end()
+            ),
+            ("ArrayOf", len) => Self::Array(ApiArrayType {
+                inner_type: Some(Box::new(inner_type)),
+                len,
             }),
-            "DictionaryOf" => Self::Dictionary(ApiDictionaryType {
-                inner_type: Some(Box::new(ApiType::simple_type_from_str(inner_type)?)),
+            ("DictionaryOf", None) => Self::Dictionary(ApiDictionaryType {
+                inner_type: Some(Box::new(inner_type)),
             }),
-            _ => return None,
+            ("DictionaryOf", Some(len)) => {
+                bail!("`DictionaryOf` cannot have a fixed length, got {len}")
+            }
+            _ => bail!("Unknown nested type `{name}`"),
         })
     }
+
+    /// Returns true if values of this type can be passed between the host and WASM guests.
+    ///
+    /// `LuaRef`s only make sense to Lua and have no WASM counterpart.
+    pub fn is_wasm_compatible(&self) -> bool {
+        match self {
+            Self::LuaRef => false,
+            Self::Array(ApiArrayType {
+                inner_type: Some(inner_type),
+                ..
+            })
+            | Self::Dictionary(ApiDictionaryType {
+                inner_type: Some(inner_type),
+            }) => inner_type.is_wasm_compatible(),
+            Self::Keyset(keyset) => keyset
+                .fields
+                .iter()
+                .all(|field| field.type_.is_wasm_compatible()),
+            _ => true,
+        }
+    }
+
+    /// Returns true if a fixed-length array is nested in this type, e.g. in
+    /// `ArrayOf(ArrayOf(Integer, 2))`.
+    ///
+    /// Such types can be passed by WASM guests, but not returned to them: the host converts nested
+    /// `Object`s to lists and dictionaries only, see `nvim-wasm/src/types.rs`.
+    pub fn nests_fixed_len_array(&self) -> bool {
+        match self {
+            Self::Array(ApiArrayType {
+                inner_type: Some(inner_type),
+                ..
+            })
+            | Self::Dictionary(ApiDictionaryType {
+                inner_type: Some(inner_type),
+            }) => {
+                matches!(**inner_type, Self::Array(ApiArrayType { len: Some(_), .. }))
+                    || inner_type.nests_fixed_len_array()
+            }
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -615,27 +1165,43 @@ enum ParseTypeCase {
     Normal(ApiType),
     Keyset(String),
     Void,
-    ChannelId,
-    Arena,
-    Error,
-}
-
-impl ParseTypeCase {
-    fn arg_is_pointer(&self) -> bool {
-        matches!(self, Self::Keyset(_) | Self::Arena | Self::Error)
-    }
 }
 
-struct ParseApiArg {
-    type_: ParseTypeCase,
+/// A function of the API metadata.
+///
+/// This is the function table built by `add_function` in
+/// `src/nvim/generators/gen_api_dispatch.lua`. The `channel_id`, `Arena *`, `Error *` and
+/// `lua_State *` parameters are already removed from `parameters` and recorded as flags.
+#[derive(Debug, Deserialize)]
+struct FuncMetadata {
     name: String,
-}
-
-enum ParseAttribute {
-    Since(i32),
-    Fast,
-    RemoteOnly,
-    CheckTextLock,
+    return_type: String,
+    /// The parameters, as `(type, name)`.
+    parameters: Vec<(String, String)>,
+    since: Option<i32>,
+    deprecated_since: Option<i32>,
+    /// The implementing function of a deprecated alias, see `src/nvim/api/dispatch_deprecated.lua`.
+    impl_name: Option<String>,
+    #[serde(default)]
+    receives_channel_id: bool,
+    #[serde(default)]
+    receives_array_args: bool,
+    #[serde(default)]
+    can_fail: bool,
+    #[serde(default)]
+    arena_return: bool,
+    #[serde(default)]
+    has_lua_imp: bool,
+    #[serde(default)]
+    fast: bool,
+    #[serde(default)]
+    remote_only: bool,
+    #[serde(default)]
+    lua_only: bool,
+    #[serde(default)]
+    textlock: bool,
+    #[serde(default)]
+    textlock_allow_cmdwin: bool,
 }
 
 fn normalized_wit_identifier(s: &str) -> String {
@@ -647,3 +1213,223 @@ fn normalized_wit_identifier(s: &str) -> String {
         .join("-");
     s
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes `type_` back in the notation of the API metadata, for comparisons.
+    fn type_str(type_: &ApiType) -> String {
+        match type_ {
+            ApiType::Array(ApiArrayType {
+                inner_type: Some(inner_type),
+                len: Some(len),
+            }) => format!("ArrayOf({}, {len})", type_str(inner_type)),
+            ApiType::Array(ApiArrayType {
+                inner_type: Some(inner_type),
+                len: None,
+            }) => format!("ArrayOf({})", type_str(inner_type)),
+            ApiType::Dictionary(ApiDictionaryType {
+                inner_type: Some(inner_type),
+            }) => format!("DictionaryOf({})", type_str(inner_type)),
+            ApiType::Keyset(keyset) => format!("Dict({})", keyset.name),
+            type_ => format!("{type_:?}"),
+        }
+    }
+
+    fn parse(type_: &str) -> Result<Option<String>> {
+        let keysets = parse_keysets("typedef struct { Object desc; } Dict(keymap);")
+            .into_iter()
+            .map(|keyset| (keyset.name.clone(), keyset))
+            .collect();
+        Ok(parse_metadata_type(type_, &keysets)?.as_ref().map(type_str))
+    }
+
+    fn parse_err(type_: &str) -> String {
+        format!("{:#}", parse(type_).unwrap_err())
+    }
+
+    #[test]
+    fn tokens() {
+        let it = TokenIterator::new("  ArrayOf(Integer,2) *".to_owned());
+        assert_eq!(it.peek_token(), Some("ArrayOf"));
+        let mut tokens = vec![];
+        while let Some(token) = it.next_token() {
+            tokens.push(token.to_owned());
+        }
+        assert_eq!(tokens, ["ArrayOf", "(", "Integer", ",", "2", ")", "*"]);
+    }
+
+    #[test]
+    fn simple_types() {
+        assert_eq!(parse("void").unwrap(), None);
+        assert_eq!(parse("Integer").unwrap().as_deref(), Some("Integer"));
+        assert_eq!(parse("Buffer").unwrap().as_deref(), Some("Buffer"));
+        assert_eq!(
+            parse("Dict(keymap) *").unwrap().as_deref(),
+            Some("Dict(keymap)")
+        );
+        assert_eq!(parse_err("Integr"), "Unknown type `Integr`");
+        assert_eq!(parse_err("Dict(nope) *"), "Unknown keyset type `nope`");
+        assert_eq!(parse_err("Dict(keymap)"), "Expect '*', got end of line");
+        assert_eq!(
+            parse_err("Integer Float"),
+            "Unexpected 'Float' after type `Integer Float`"
+        );
+    }
+
+    #[test]
+    fn nested_types() {
+        for type_ in [
+            "ArrayOf(String)",
+            "ArrayOf(Integer, 2)",
+            "ArrayOf(ArrayOf(Integer, 2))",
+            "DictionaryOf(ArrayOf(String))",
+            "ArrayOf(DictionaryOf(Float), 3)",
+        ] {
+            assert_eq!(parse(type_).unwrap().as_deref(), Some(type_));
+        }
+        assert_eq!(
+            parse("ArrayOf( Integer ,2 )").unwrap().as_deref(),
+            Some("ArrayOf(Integer, 2)")
+        );
+        assert_eq!(
+            parse_err("DictionaryOf(Integer, 2)"),
+            "`DictionaryOf` cannot have a fixed length, got 2"
+        );
+        assert!(parse_err("ArrayOf(Integer, -1)").starts_with("Invalid length -1 for `ArrayOf`"));
+        assert_eq!(
+            parse_err("ArrayOf(Integer, 1)"),
+            "`ArrayOf` can only have a fixed length of 2 to 4, got 1"
+        );
+        assert_eq!(
+            parse_err("ArrayOf(Integer, 5)"),
+            "`ArrayOf` can only have a fixed length of 2 to 4, got 5"
+        );
+        assert!(parse_err("ArrayOf(Integer, x)").starts_with("Expect a number, got 'x'"));
+        assert_eq!(parse_err("ArrayOf(Integer"), "Expect ')', got end of line");
+        assert_eq!(
+            parse_err("ArrayOf(void)"),
+            "Type `Void` is not a valid inner type for `ArrayOf`"
+        );
+        assert_eq!(
+            parse_err("ArrayOf(Dict(keymap))"),
+            "Type `Keyset(\"keymap\")` is not a valid inner type for `ArrayOf`"
+        );
+    }
+
+    #[test]
+    fn nested_fixed_len_arrays() {
+        let keysets = HashMap::new();
+        let nests = |type_: &str| {
+            parse_metadata_type(type_, &keysets)
+                .unwrap()
+                .unwrap()
+                .nests_fixed_len_array()
+        };
+        assert!(!nests("ArrayOf(Integer, 2)"));
+        assert!(!nests("ArrayOf(ArrayOf(String))"));
+        assert!(nests("ArrayOf(ArrayOf(Integer, 2))"));
+        assert!(nests("ArrayOf(ArrayOf(Integer, 2), 3)"));
+        assert!(nests("DictionaryOf(ArrayOf(ArrayOf(Float, 4)))"));
+    }
+
+    #[test]
+    fn keysets() {
+        let keysets = parse_keysets(
+            "#include <foo.h>\n\
+             typedef struct {\n  Object noremap;\n} Dict(keymap);\n\n\
+             typedef struct {\n  Object bufpos;\n  Object border;\n} Dict(float_config);\n",
+        );
+        let fields = keysets
+            .iter()
+            .map(|keyset| {
+                let fields = keyset
+                    .fields
+                    .iter()
+                    .map(|field| format!("{}: {}", field.wit_name(), type_str(&field.type_)));
+                (keyset.wit_name(), fields.collect::<Vec<_>>())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            [
+                (
+                    "keyset-keymap".to_owned(),
+                    vec!["noremap: Boolean".to_owned()]
+                ),
+                (
+                    "keyset-float-config".to_owned(),
+                    vec![
+                        "bufpos: ArrayOf(Integer, 2)".to_owned(),
+                        "border: Object".to_owned()
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn keyset_field_types() {
+        // Every field of `keysets.h` has an entry, and every entry a field.
+        assert_eq!(
+            keyset_field_type_errors(&api_keysets()),
+            Vec::<String>::new()
+        );
+
+        let mut keysets = api_keysets();
+        let keymap = keysets.iter_mut().find(|k| k.name == "keymap").unwrap();
+        keymap.fields.retain(|field| field.name != "desc");
+        keymap.fields.push(ApiField {
+            name: "new_field".to_owned(),
+            type_: ApiType::Object,
+        });
+        assert_eq!(
+            keyset_field_type_errors(&keysets),
+            [
+                "keyset field `keymap.new_field` has no type in `KEYSET_FIELD_TYPES`",
+                "`KEYSET_FIELD_TYPES` lists `keymap.desc`, which is not a keyset field",
+            ]
+        );
+    }
+
+    #[test]
+    fn wasm_exclusions() {
+        let func = |name: &str| ApiFunc {
+            name: name.to_owned(),
+            args: ApiFuncArgs {
+                args: vec![],
+                has_channel_id: true,
+                has_lua_state: false,
+                has_arena: false,
+            },
+            return_: ApiFuncReturn {
+                type_: None,
+                has_error: true,
+            },
+            attrs: ApiFuncAttrs {
+                remote_only: true,
+                ..Default::default()
+            },
+        };
+        for name in ["nvim_ui_attach", "nvim_input_mouse", "ui_attach"] {
+            assert_eq!(
+                func(name).wasm_exclusion(),
+                Some(WasmExclusion::UiProtocol),
+                "{name}"
+            );
+        }
+        assert_eq!(
+            func("nvim_get_api_info").wasm_exclusion(),
+            Some(WasmExclusion::ChannelBound)
+        );
+        assert_eq!(
+            func("nvim_buf_call").wasm_exclusion(),
+            Some(WasmExclusion::ReplacedBy("nvim_wasm_buf_enter"))
+        );
+        assert_eq!(
+            func("nvim_exec_lua").wasm_exclusion(),
+            Some(WasmExclusion::RemoteOnly)
+        );
+    }
+}
@@ -3,16 +3,25 @@ use std::path::PathBuf;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("cargo:rerun-if-changed=");
+    println!(
+        "cargo:rerun-if-changed={}",
+        gen_api_func_lib::api_metadata_path().display()
+    );
+    println!(
+        "cargo:rerun-if-env-changed={}",
+        gen_api_func_lib::NVIM_BUILD_DIR_ENV
+    );
     let _out_dir = PathBuf::from(env::var("OUT_DIR")?);
     let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR")?);
-    // This is... ugly...
-    // TODO: Figure out how to generate those headers when running build.
+    // The generated declarations and config headers live in the Neovim build directory, which is
+    // passed by the CMake build.
+    let build_dir = gen_api_func_lib::nvim_build_dir();
     let dirs = [
         crate_dir.join("headers"),
         crate_dir.join("../../../"),
-        crate_dir.join("../../../../build/src/nvim/auto"),
-        crate_dir.join("../../../../build/cmake.config"),
-        crate_dir.join("../../../../build/include"),
+        build_dir.join("src/nvim/auto"),
+        build_dir.join("cmake.config"),
+        build_dir.join("include"),
     ];
 
     let mut builder = bindgen::Builder::default()
@@ -52,7 +61,26 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         "emsg_multiline",
         "msg",
         "preserve_exit",
+        "sha256_bytes",
+        "stdpaths_user_data_subpath",
+        "stdpaths_user_state_subpath",
         "try_to_free_memory",
+        "wasm_buf_chunk_capacity",
+        "wasm_buf_handle",
+        "wasm_buf_read_chunk",
+        "wasm_current_channel_id",
+        "wasm_current_instance",
+        "wasm_linked_call_enter",
+        "wasm_linked_call_leave",
+        "wasm_log_time",
+        "wasm_process_close_stdin",
+        "wasm_process_kill",
+        "wasm_process_spawn",
+        "wasm_process_write",
+        "wasm_stop_instance",
+        "wasm_timer_start",
+        "wasm_timer_stop",
+        "wasm_trust_prompt",
         "xcalloc",
         "xfree",
         "xmalloc",
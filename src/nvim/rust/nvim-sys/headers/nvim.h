@@ -18,3 +18,9 @@
 #include "nvim/main.h"
 #include "nvim/memory.h"
 #include "nvim/message.h"
+#include "nvim/os/os.h"
+#include "nvim/sha256.h"
+#include "nvim/wasm/buffer.h"
+#include "nvim/wasm/executor.h"
+#include "nvim/wasm/process.h"
+#include "nvim/wasm/timer.h"
@@ -0,0 +1,39 @@
+/// Precompiles a WASM plugin for the machine it runs on, so that Nvim loads it without compiling.
+///
+/// Use `nvim-wasm-precompile <input> [<output>]` to write the precompiled component to <output>,
+/// which defaults to <input> with the `.cwasm` extension. <input> can be any plugin Nvim loads:
+/// a component or a core module, in binary or text format.
+///
+/// The precompiled component can only be loaded by an Nvim built with the same version of
+/// Wasmtime, for the same target. It keeps the name declared in the `nvim-plugin-name` section.
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use nvim_wasm_compile::{append_precompiled_name, component_binary, plugin_name, wasm_config};
+
+fn main() -> Result<()> {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let (input, output) = match args.as_slice() {
+        [input] => (Path::new(input), Path::new(input).with_extension("cwasm")),
+        [input, output] => (Path::new(input), PathBuf::from(output)),
+        _ => bail!("Usage: nvim-wasm-precompile <input> [<output>]"),
+    };
+
+    let engine = wasmtime::Engine::new(&wasm_config())?;
+    let bytes =
+        std::fs::read(input).with_context(|| format!("Failed to read {}", input.display()))?;
+    let component = component_binary(&bytes)
+        .with_context(|| format!("Failed to load the WASM file {}", input.display()))?;
+    let name = plugin_name(&component)?;
+    let mut precompiled = engine
+        .precompile_component(&component)
+        .with_context(|| format!("Failed to compile the WASM file {}", input.display()))?;
+    // Wasmtime does not keep the custom sections, Nvim reads the name after the component.
+    if let Some(name) = name {
+        append_precompiled_name(&mut precompiled, &name);
+    }
+    std::fs::write(&output, precompiled)
+        .with_context(|| format!("Failed to write {}", output.display()))?;
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use wasi_preview1_component_adapter_provider::{
+    WASI_SNAPSHOT_PREVIEW1_ADAPTER_NAME, WASI_SNAPSHOT_PREVIEW1_REACTOR_ADAPTER,
+};
+use wasmparser::{Parser, Payload};
+use wit_component::{ComponentEncoder, StringEncoding};
+use wit_parser::{Resolve, SourceMap, WorldId};
+
+/// The WIT files of the `nvim:api` package.
+const WIT_FILES: [(&str, &str); 8] = [
+    ("api.wit", include_str!("../../nvim-wasm/wit/api.wit")),
+    ("buffer.wit", include_str!("../../nvim-wasm/wit/buffer.wit")),
+    (
+        "keysets.wit",
+        include_str!("../../nvim-wasm/wit/keysets.wit"),
+    ),
+    ("log.wit", include_str!("../../nvim-wasm/wit/log.wit")),
+    (
+        "process.wit",
+        include_str!("../../nvim-wasm/wit/process.wit"),
+    ),
+    (
+        "storage.wit",
+        include_str!("../../nvim-wasm/wit/storage.wit"),
+    ),
+    ("timer.wit", include_str!("../../nvim-wasm/wit/timer.wit")),
+    ("types.wit", include_str!("../../nvim-wasm/wit/types.wit")),
+];
+
+/// Returns true if `bytes` is a core WASM module rather than a component.
+pub(crate) fn is_core_module(bytes: &[u8]) -> bool {
+    Parser::is_core_wasm(bytes)
+}
+
+/// Wraps the core module `module` in a component.
+///
+/// Modules built with `wit-bindgen` embed the WIT world they target. Other modules are given the
+/// `plugin` world, so they can only import the Nvim interfaces. Modules importing WASI preview 1,
+/// e.g. built for `wasm32-wasi`, are adapted to WASI preview 2 with the reactor adapter.
+pub(crate) fn componentize(module: &[u8]) -> Result<Vec<u8>> {
+    let mut has_component_type = false;
+    let mut imports_wasi = false;
+    for payload in Parser::new(0).parse_all(module) {
+        match payload? {
+            Payload::CustomSection(section) => {
+                has_component_type |= section.name().starts_with("component-type");
+            }
+            Payload::ImportSection(imports) => {
+                for import in imports {
+                    imports_wasi |= import?.module == WASI_SNAPSHOT_PREVIEW1_ADAPTER_NAME;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut module = module.to_vec();
+    if !has_component_type {
+        let (resolve, world) = plugin_world()?;
+        wit_component::embed_component_metadata(
+            &mut module,
+            &resolve,
+            world,
+            StringEncoding::UTF8,
+        )?;
+    }
+    let mut encoder = ComponentEncoder::default().validate(true).module(&module)?;
+    if imports_wasi {
+        encoder = encoder.adapter(
+            WASI_SNAPSHOT_PREVIEW1_ADAPTER_NAME,
+            WASI_SNAPSHOT_PREVIEW1_REACTOR_ADAPTER,
+        )?;
+    }
+    encoder
+        .encode()
+        .context("Failed to wrap the core module in a component")
+}
+
+/// Returns the `plugin` world of the WIT shipped with Nvim.
+fn plugin_world() -> Result<(Resolve, WorldId)> {
+    let mut sources = SourceMap::new();
+    for (name, contents) in WIT_FILES {
+        sources.push(Path::new(name), contents);
+    }
+    let mut resolve = Resolve::default();
+    let package = resolve.push_group(sources.parse()?)?;
+    let world = resolve.select_world(package, Some("plugin"))?;
+    Ok((resolve, world))
+}
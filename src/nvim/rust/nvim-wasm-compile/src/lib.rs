@@ -0,0 +1,123 @@
+//! Compiles WASM plugins, for Nvim and for the `nvim-wasm-precompile` tool.
+
+use std::borrow::Cow;
+
+use anyhow::{bail, Result};
+use wasmparser::{Parser, Payload};
+use wasmtime::Config;
+
+mod componentize;
+
+/// A method of `wasmtime::Config` enabling a WebAssembly proposal.
+pub type EnableProposal = fn(&mut Config, bool) -> &mut Config;
+
+/// The WebAssembly proposals enabled for plugins besides WebAssembly 2.0.
+///
+/// They are enabled explicitly rather than left to the defaults of Wasmtime, so that this list is
+/// what `nvim_wasm_get_runtime_info` reports.
+pub const WASM_PROPOSALS: &[(&str, EnableProposal)] = &[
+    ("component-model", Config::wasm_component_model),
+    ("extended-const", Config::wasm_extended_const),
+    ("memory64", Config::wasm_memory64),
+    ("multi-memory", Config::wasm_multi_memory),
+    ("relaxed-simd", Config::wasm_relaxed_simd),
+    ("tail-call", Config::wasm_tail_call),
+];
+
+/// The custom section in which a plugin declares its name, e.g. with
+/// `#[link_section = "nvim-plugin-name"]` in Rust or `(@custom "nvim-plugin-name" "name")` in WAT.
+pub const PLUGIN_NAME_SECTION: &str = "nvim-plugin-name";
+
+/// Ends the trailer in which `nvim-wasm-precompile` keeps the name declared by the plugin, which
+/// Wasmtime does not keep in precompiled components: the name, its length as a little-endian
+/// `u32`, then this magic.
+const PRECOMPILED_NAME_MAGIC: &[u8; 8] = b"nvimname";
+
+/// Returns the config for creating the WASM engine.
+///
+/// A component precompiled by `nvim-wasm-precompile` can only be loaded by an engine created with
+/// the same config.
+pub fn wasm_config() -> Config {
+    let mut config = Config::new();
+    for (_, enable) in WASM_PROPOSALS {
+        enable(&mut config, true);
+    }
+    config
+}
+
+/// Returns the binary of the component `bytes`.
+///
+/// `bytes` can be a component or a core module, in binary or text format. Core modules are wrapped
+/// in a component.
+pub fn component_binary(bytes: &[u8]) -> Result<Cow<'_, [u8]>> {
+    let bytes = wat::parse_bytes(bytes)?;
+    if componentize::is_core_module(&bytes) {
+        return Ok(Cow::Owned(componentize::componentize(&bytes)?));
+    }
+    Ok(bytes)
+}
+
+/// Returns the name declared by the component `binary` in its `nvim-plugin-name` custom section, if
+/// any.
+///
+/// The section is also looked up in the core modules of the component, where it lands when a
+/// module built by a compiler is wrapped in a component. The name is used as a file name, so it
+/// may only contain ASCII letters, digits, `-`, `_` and `.`, and must not start with `.`.
+pub fn plugin_name(binary: &[u8]) -> Result<Option<String>> {
+    for payload in Parser::new(0).parse_all(binary) {
+        let Payload::CustomSection(section) = payload? else {
+            continue;
+        };
+        if section.name() != PLUGIN_NAME_SECTION {
+            continue;
+        }
+        let name = std::str::from_utf8(section.data())
+            .ok()
+            .filter(|name| is_valid_plugin_name(name));
+        match name {
+            Some(name) => return Ok(Some(name.to_string())),
+            None => bail!(
+                "Invalid plugin name in the {PLUGIN_NAME_SECTION} section: {:?}",
+                String::from_utf8_lossy(section.data())
+            ),
+        }
+    }
+    Ok(None)
+}
+
+/// Appends the name declared by the plugin to its precompiled component, see
+/// `split_precompiled_name`.
+pub fn append_precompiled_name(precompiled: &mut Vec<u8>, name: &str) {
+    let len = u32::try_from(name.len()).expect("Plugin name is too long");
+    precompiled.extend_from_slice(name.as_bytes());
+    precompiled.extend_from_slice(&len.to_le_bytes());
+    precompiled.extend_from_slice(PRECOMPILED_NAME_MAGIC);
+}
+
+/// Splits the file `bytes` written by `nvim-wasm-precompile` into the precompiled component and
+/// the name declared by the plugin, if any, see `append_precompiled_name`. Other files are
+/// returned as is.
+pub fn split_precompiled_name(bytes: &[u8]) -> Result<(&[u8], Option<String>)> {
+    let Some(rest) = bytes.strip_suffix(PRECOMPILED_NAME_MAGIC) else {
+        return Ok((bytes, None));
+    };
+    let name = rest.split_last_chunk::<4>().and_then(|(rest, len)| {
+        let start = rest.len().checked_sub(u32::from_le_bytes(*len) as usize)?;
+        let (component, name) = rest.split_at(start);
+        let name = std::str::from_utf8(name).ok()?;
+        is_valid_plugin_name(name).then(|| (component, name.to_string()))
+    });
+    match name {
+        Some((component, name)) => Ok((component, Some(name))),
+        None => bail!("Invalid plugin name after the precompiled component"),
+    }
+}
+
+/// Returns true if `name` can be the name of a plugin, see `plugin_name`.
+fn is_valid_plugin_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
@@ -1,6 +1,6 @@
 use gen_api_func_lib::{
-    api_functions, ApiArg, ApiArrayType, ApiDictionaryType, ApiField, ApiFunc, ApiFuncArgs,
-    ApiFuncReturn, ApiKeyset, ApiType,
+    api_functions, api_package_version, nvim_api_level, ApiArg, ApiArrayType, ApiDictionaryType,
+    ApiField, ApiFunc, ApiFuncArgs, ApiFuncReturn, ApiKeyset, ApiType,
 };
 
 use proc_macro2::{Ident, TokenStream};
@@ -8,20 +8,47 @@ use quote::{format_ident, quote};
 use std::{env, fs::File, io::Write, path::Path};
 
 fn main() {
+    println!(
+        "cargo:rerun-if-changed={}",
+        gen_api_func_lib::api_metadata_path().display()
+    );
+    println!(
+        "cargo:rerun-if-env-changed={}",
+        gen_api_func_lib::NVIM_BUILD_DIR_ENV
+    );
     let out_dir = env::var("OUT_DIR").unwrap();
     let mut out_file = File::create(Path::new(&out_dir).join("api_impl.rs")).unwrap();
 
     let api_funcs = api_functions();
-    let funcs_impls = api_funcs.iter().map(gen_fn_impl);
+    // Same functions as the WIT files: functions left out of the WASM interface (remote-only,
+    // channel-bound, UI protocol, trust and settings functions, and the ones with WASM versions,
+    // see `ApiFunc::wasm_exclusion`) or with types WASM cannot pass are not implemented.
+    let (deprecated_funcs, funcs): (Vec<_>, Vec<_>) = api_funcs
+        .iter()
+        .filter(|func| func.wasm_exclusion().is_none() && func.is_wasm_compatible())
+        .partition(|func| func.attrs.deprecated_since.is_some());
+    let funcs_impls = funcs.into_iter().map(gen_fn_impl);
+    let deprecated_funcs_impls = deprecated_funcs.into_iter().map(gen_fn_impl);
+
+    let version = api_package_version();
+    let (major, minor, patch) = (version.major, version.minor, version.patch);
+    let api_level = nvim_api_level();
 
     let api_impl = quote! {
-        // The channel IDjused by all WASM calls.
-        const WASM_INTERNAL_CALL: u64 = (1u64 << 63) + 2;
+        /// The version of the `nvim:api` WIT package implemented by the host.
+        const NVIM_API_PACKAGE_VERSION: (u32, u32, u32) = (#major, #minor, #patch);
+        /// The API level implemented by the host, see `:help api-level`.
+        const NVIM_API_LEVEL: i32 = #api_level;
 
         #[allow(non_snake_case, clippy::let_unit_value)]
         impl nvim_api::Host for NvimHost {
             #(#funcs_impls)*
         }
+
+        #[allow(non_snake_case, clippy::let_unit_value)]
+        impl nvim_api_deprecated::Host for NvimHost {
+            #(#deprecated_funcs_impls)*
+        }
     };
 
     writeln!(out_file, "{api_impl}").unwrap();
@@ -43,15 +70,20 @@ fn main() {
 ///     name: String,
 ///     opts: nvim_keysets::KeysetCreateAugroup,
 /// ) -> wasmtime::Result<Result<i64, String>> {
+///     let __api_func_profile_var =
+///         profile::ApiCallTimer::start(&self.profile, "nvim_create_augroup");
 ///     let name = <nvim_rs::NvimString>::from_wasm_type(name);
-///     let mut opts__clear = <nvim_rs::NvimObject>::from_wasm_type(opts.clear);
+///     let mut opts__clear = opts
+///         .clear
+///         .map(<nvim_rs::NvimObject>::from_wasm_type)
+///         .unwrap_or_else(<nvim_rs::NvimObject>::nil);
 ///     let mut opts = nvim_sys::KeyDict_create_augroup {
 ///         clear: opts__clear.as_mut_borrowed_ffi(),
 ///     };
 ///     let mut __api_func_error_var = nvim_rs::NvimResult::new_ok();
 ///     let __api_func_result_var = unsafe {
 ///         nvim_sys::nvim_create_augroup(
-///             WASM_INTERNAL_CALL,
+///             nvim_sys::wasm_current_channel_id(),
 ///             name.as_borrowed_ffi(),
 ///             &mut opts,
 ///             __api_func_error_var.as_ffi_mut(),
@@ -103,10 +135,12 @@ fn gen_fn_return_type(return_: &ApiFuncReturn) -> TokenStream {
 }
 
 fn gen_fn_body(func: &ApiFunc) -> TokenStream {
+    let profile_timer = gen_profile_timer(func);
     let args_conversion = gen_args_conversion(&func.args);
     let extra_vars_definition = gen_extra_vars_definition(func);
     let call_api_function = gen_call_api_function(func);
     let mut ts = quote! {
+        #profile_timer
         #args_conversion
         #extra_vars_definition
         #call_api_function
@@ -122,6 +156,16 @@ fn gen_fn_body(func: &ApiFunc) -> TokenStream {
     ts
 }
 
+/// Generates the timer recording the call when profiling, see `profile.rs`. The call is recorded when
+/// the timer is dropped, i.e. when the function returns.
+fn gen_profile_timer(func: &ApiFunc) -> TokenStream {
+    let profile_var = profile_var_name();
+    let func_name = &func.name;
+    quote! {
+        let #profile_var = profile::ApiCallTimer::start(&self.profile, #func_name);
+    }
+}
+
 fn gen_args_conversion(args: &ApiFuncArgs) -> TokenStream {
     let args_conversion = args.args.iter().map(gen_arg_conversion);
     quote!(#(#args_conversion)*)
@@ -157,8 +201,12 @@ fn gen_keyset_fields_conversion(arg_name: &str, keyset: &ApiKeyset) -> TokenStre
         let arg_var_name = format_ident!("r#{}", wit_name_to_snake(arg_name));
         let field_var_name = keyset_field_var_name(arg_name, field);
 
+        // Keys that are not provided are left as nil, which the API function treats as unset.
         quote! {
-            let mut #field_var_name = <#field_type>::from_wasm_type(#arg_var_name.#field_name);
+            let mut #field_var_name = #arg_var_name
+                .#field_name
+                .map(<#field_type>::from_wasm_type)
+                .unwrap_or_else(<#field_type>::nil);
         }
     });
     quote! {
@@ -227,8 +275,9 @@ fn gen_call_api_function(func: &ApiFunc) -> TokenStream {
 fn gen_host_args_expression(args: &ApiFuncArgs) -> TokenStream {
     let mut ts = TokenStream::new();
     if args.has_channel_id {
+        // Each instance has its own pseudo-channel, so Nvim knows which plugin made the call.
         ts.extend([quote! {
-            WASM_INTERNAL_CALL,
+            nvim_sys::wasm_current_channel_id(),
         }])
     }
     let args_expression = args.args.iter().map(|arg| {
@@ -254,6 +303,12 @@ fn gen_host_args_expression(args: &ApiFuncArgs) -> TokenStream {
     });
     ts.extend(args_expression);
 
+    // WASM calls have no Lua state, so the function returns its result as API objects.
+    if args.has_lua_state {
+        ts.extend([quote! {
+            std::ptr::null_mut(),
+        }])
+    }
     if args.has_arena {
         let arena_var = arena_var_name();
         ts.extend([quote! {
@@ -323,6 +378,10 @@ fn error_var_name() -> Ident {
     format_ident!("__api_func_error_var")
 }
 
+fn profile_var_name() -> Ident {
+    format_ident!("__api_func_profile_var")
+}
+
 fn result_var_name() -> Ident {
     format_ident!("__api_func_result_var")
 }
@@ -334,14 +393,20 @@ fn wasm_type(arg: &ApiType) -> TokenStream {
         Integer => quote!(i64),
         Float => quote!(f64),
         String => quote!(String),
-        Array(ApiArrayType { inner_type }) => {
+        Array(ApiArrayType { inner_type, len }) => {
             let inner_wasm_type = wasm_type(
                 inner_type
                     .as_ref()
                     .map(|x| &**x)
                     .unwrap_or(&ApiType::Object),
             );
-            quote!(Vec<#inner_wasm_type>)
+            match len {
+                Some(len) => {
+                    let items = std::iter::repeat(inner_wasm_type).take(*len);
+                    quote!((#(#items,)*))
+                }
+                None => quote!(Vec<#inner_wasm_type>),
+            }
         }
         Dictionary(ApiDictionaryType { inner_type }) => {
             let inner_wasm_type = wasm_type(
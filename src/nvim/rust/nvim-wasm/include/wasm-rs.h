@@ -1,3 +1,6 @@
+#ifndef NVIM_RUST_NVIM_WASM_INCLUDE_WASM_RS_H
+#define NVIM_RUST_NVIM_WASM_INCLUDE_WASM_RS_H
+
 #include <stdarg.h>
 #include <stdbool.h>
 #include <stddef.h>
@@ -17,15 +20,186 @@
 void wasm_rs_init(void);
 
 /**
- * Loads the WASM binary to the global store and returns the instance ID.
+ * Loads the WASM binary to a new store and returns the instance ID.
+ *
+ * `user_initiated` is false when the plugin is loaded without the user asking for it, e.g. when it
+ * is found in 'runtimepath'. The user is then asked whether to trust the plugin, see `trust.rs`.
+ *
+ * Returns -1 on error, or -2 when the plugin imports an interface that no loaded plugin exports:
+ * loading it again may then succeed after loading other plugins.
  *
  * # Safety
  *
  * The `file_path` pointer must be a valid UTF-8 CString.
  */
-int32_t wasm_load_file(const char *file_path, const char **errmsg);
+int32_t wasm_load_file(const char *file_path, bool user_initiated, const char **errmsg);
+
+/**
+ * Loads a WASM binary or WAT text from memory to a new store and returns the instance ID.
+ *
+ * # Safety
+ *
+ * `bytes` should point to `len` bytes, and `name` must be null or a valid UTF-8 CString.
+ */
+int32_t wasm_load_bytes(const char *bytes,
+                        size_t len,
+                        const char *name,
+                        bool user_initiated,
+                        const char **errmsg);
+
+/**
+ * Sets the trust of the WASM file `file_path` in the trust database, see `:WasmTrust`.
+ *
+ * # Safety
+ *
+ * `file_path`, `action` and `capabilities` must be valid UTF-8 CStrings.
+ */
+void wasm_trust_file(const char *file_path,
+                     const char *action,
+                     const char *capabilities,
+                     const char **errmsg);
+
+/**
+ * Unloads the instance with ID `instance_id`, dropping its store.
+ *
+ * Returns false on error, e.g. when the instance is running or exports interfaces to other
+ * instances.
+ */
+bool wasm_unload_instance(int32_t instance_id, const char **errmsg);
+
+/**
+ * Returns information about the WASM runtime and the loaded instances, see
+ * `nvim_wasm_get_runtime_info`.
+ */
+Dictionary wasm_get_runtime_info(void);
+
+/**
+ * Starts profiling the calls to and from the instances, clearing the previous profiles.
+ */
+void wasm_profile_start(void);
+
+/**
+ * Stops profiling the calls to and from the instances.
+ */
+void wasm_profile_stop(void);
+
+/**
+ * Returns the profiles of the instances, see `nvim_wasm_profile`.
+ */
+Array wasm_get_profiles(void);
+
+/**
+ * Sets the level below which the entries logged by plugins are dropped, as `vim.log.levels`.
+ */
+void wasm_set_log_level(int64_t level);
+
+/**
+ * Sets whether the warnings and errors logged by plugins are also shown with `vim.notify()`.
+ */
+void wasm_set_log_notify(bool notify);
 
 /**
  * Calls a function from a WASM instance
  */
 Object wasm_call_func(int32_t instance_id, const char *func_name, Array args, const char **errmsg);
+
+/**
+ * Returns the index of the hook `func_name` exported by a WASM instance, i.e. a function taking
+ * `args_len` `s64` arguments and returning a `bool`, to be called with `wasm_call_hook`.
+ */
+int32_t wasm_find_hook(int32_t instance_id,
+                       const char *func_name,
+                       size_t args_len,
+                       const char **errmsg);
+
+/**
+ * Calls a hook exported by a WASM instance, found by `wasm_find_hook`.
+ */
+bool wasm_call_hook(int32_t instance_id,
+                    int32_t hook,
+                    const int64_t *args,
+                    size_t args_len,
+                    const char **errmsg);
+
+/**
+ * The `on_lines` event of a buffer, see `:help api-buffer-updates-lua`.
+ */
+typedef struct {
+  int64_t buffer;
+  int64_t changedtick;
+  bool has_changedtick;
+  int64_t first_line;
+  int64_t last_line;
+  int64_t last_line_updated;
+  int64_t byte_count;
+  int64_t deleted_codepoints;
+  int64_t deleted_codeunits;
+  bool has_utf_sizes;
+} WasmBufLinesEvent;
+
+/**
+ * The `on_bytes` event of a buffer, see `:help api-buffer-updates-lua`.
+ */
+typedef struct {
+  int64_t buffer;
+  int64_t changedtick;
+  int64_t start_row;
+  int64_t start_col;
+  int64_t start_byte;
+  int64_t old_end_row;
+  int64_t old_end_col;
+  int64_t old_end_byte;
+  int64_t new_end_row;
+  int64_t new_end_col;
+  int64_t new_end_byte;
+} WasmBufBytesEvent;
+
+/**
+ * Calls the `on_lines` callback exported by a WASM instance, of type
+ * `func(event: buf-lines-event) -> bool`.
+ */
+bool wasm_call_buf_lines(int32_t instance_id,
+                         const char *func_name,
+                         const WasmBufLinesEvent *event,
+                         const char **errmsg);
+
+/**
+ * Calls the `on_bytes` callback exported by a WASM instance, of type
+ * `func(event: buf-bytes-event) -> bool`.
+ */
+bool wasm_call_buf_bytes(int32_t instance_id,
+                         const char *func_name,
+                         const WasmBufBytesEvent *event,
+                         const char **errmsg);
+
+/**
+ * Calls the hook exported by a WASM instance receiving the output of its process, of type
+ * `func(process: s64, data: list<u8>)`.
+ *
+ * # Safety
+ *
+ * `func_name` must be a valid UTF-8 CString, `data` should point to `len` bytes, and `errmsg`
+ * should be non-null.
+ */
+void wasm_call_process_output(int32_t instance_id,
+                              const char *func_name,
+                              int64_t process,
+                              const char *data,
+                              size_t len,
+                              const char **errmsg);
+
+/**
+ * Calls the hook exported by a WASM instance receiving the exit status of its process, of type
+ * `func(process: s64, status: s32)`.
+ *
+ * # Safety
+ *
+ * `func_name` must be a valid UTF-8 CString, and `errmsg` should be non-null.
+ */
+void wasm_call_process_exit(int32_t instance_id,
+                            const char *func_name,
+                            int64_t process,
+                            int32_t status,
+                            const char **errmsg);
+
+#endif /* NVIM_RUST_NVIM_WASM_INCLUDE_WASM_RS_H */
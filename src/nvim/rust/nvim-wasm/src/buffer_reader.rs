@@ -0,0 +1,69 @@
+use nvim_rs::NvimResult;
+use wasmtime::component::Resource;
+
+use crate::{
+    nvim::api::nvim_buffer::{self, BufferReader},
+    nvim_types, NvimHost,
+};
+
+impl nvim_buffer::Host for NvimHost {}
+
+/// Implements the `buffer-reader` resource.
+///
+/// Readers hold no state: the representation of a reader is the handle of its buffer.
+impl nvim_buffer::HostBufferReader for NvimHost {
+    fn open(
+        &mut self,
+        buffer: nvim_types::Buffer,
+    ) -> wasmtime::Result<Result<Resource<BufferReader>, String>> {
+        let mut error = NvimResult::new_ok();
+        let handle = unsafe { nvim_sys::wasm_buf_handle(buffer as i32, error.as_ffi_mut()) };
+        if let Err(err) = error.into_result() {
+            return Ok(Err(err.to_string()));
+        }
+        Ok(Ok(Resource::new_own(handle as u32)))
+    }
+
+    fn read_chunk(
+        &mut self,
+        reader: Resource<BufferReader>,
+        start_line: i64,
+        max_bytes: u32,
+    ) -> wasmtime::Result<Result<Vec<u8>, String>> {
+        let buffer = reader.rep() as i32;
+        let mut error = NvimResult::new_ok();
+        let capacity = unsafe {
+            nvim_sys::wasm_buf_chunk_capacity(
+                buffer,
+                start_line,
+                max_bytes as usize,
+                error.as_ffi_mut(),
+            )
+        };
+        if let Err(err) = error.into_result() {
+            return Ok(Err(err.to_string()));
+        }
+        if capacity == 0 {
+            return Ok(Ok(Vec::new()));
+        }
+
+        // One allocation per chunk, the lines being read once from the memline. The list is then
+        // copied into the guest memory when lowered. A first line longer than `max_bytes` is read
+        // again into a larger allocation.
+        let read = |chunk: &mut Vec<u8>, capacity: usize| unsafe {
+            nvim_sys::wasm_buf_read_chunk(buffer, start_line, chunk.as_mut_ptr().cast(), capacity)
+        };
+        let mut chunk = Vec::<u8>::with_capacity(capacity);
+        let mut size = read(&mut chunk, capacity);
+        if size > capacity {
+            chunk = Vec::with_capacity(size);
+            size = read(&mut chunk, size);
+        }
+        unsafe { chunk.set_len(size) };
+        Ok(Ok(chunk))
+    }
+
+    fn drop(&mut self, _reader: Resource<BufferReader>) -> wasmtime::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,145 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use wasmtime::{
+    component::{
+        types::ComponentItem, Component, ComponentNamedList, Instance, Lower, Type, TypedFunc,
+    },
+    Engine, Store,
+};
+
+use crate::{profile, NvimHost};
+
+/// The hooks exported by an instance, i.e. its functions taking 1 to 4 `s64` arguments and
+/// returning a `bool`, like the decoration providers of WASM plugins.
+///
+/// Decoration providers are called for every redrawn line, so the hooks are looked up and type
+/// checked once when the instance is loaded, and registered by their index, see `wasm_find_hook`.
+pub(crate) struct Hooks(Vec<Hook>);
+
+/// A hook exported by an instance.
+#[derive(Clone)]
+pub(crate) struct Hook {
+    name: Arc<str>,
+    func: HookFunc,
+}
+
+#[derive(Clone, Copy)]
+enum HookFunc {
+    Args1(TypedFunc<(i64,), (bool,)>),
+    Args2(TypedFunc<(i64, i64), (bool,)>),
+    Args3(TypedFunc<(i64, i64, i64), (bool,)>),
+    Args4(TypedFunc<(i64, i64, i64, i64), (bool,)>),
+}
+
+impl Hooks {
+    /// Returns the hooks exported by `instance`, an instance of `component`.
+    pub(crate) fn new(
+        engine: &Engine,
+        component: &Component,
+        instance: &Instance,
+        store: &mut Store<NvimHost>,
+    ) -> Result<Self> {
+        let mut hooks = Vec::new();
+        for (name, item) in component.component_type().exports(engine) {
+            let ComponentItem::ComponentFunc(ty) = item else {
+                continue;
+            };
+            let is_hook =
+                ty.params().all(|(_, ty)| ty == Type::S64) && ty.results().eq([Type::Bool]);
+            if !is_hook {
+                continue;
+            }
+            let func = instance
+                .get_func(&mut *store, name)
+                .with_context(|| format!("Cannot find function {name}"))?;
+            let func = match ty.params().len() {
+                1 => HookFunc::Args1(func.typed(&mut *store)?),
+                2 => HookFunc::Args2(func.typed(&mut *store)?),
+                3 => HookFunc::Args3(func.typed(&mut *store)?),
+                4 => HookFunc::Args4(func.typed(&mut *store)?),
+                _ => continue,
+            };
+            hooks.push(Hook {
+                name: name.into(),
+                func,
+            });
+        }
+
+        Ok(Self(hooks))
+    }
+
+    /// Returns the index of the hook `name` taking `args_len` arguments.
+    pub(crate) fn find(&self, name: &str, args_len: usize) -> Result<i32> {
+        let index = self
+            .0
+            .iter()
+            .position(|hook| &*hook.name == name && hook.func.args_len() == args_len);
+        match index {
+            Some(index) => Ok(index as i32),
+            None => bail!(
+                "The plugin exports no function {name} of type func({}) -> bool",
+                vec!["s64"; args_len].join(", ")
+            ),
+        }
+    }
+
+    /// Returns the hook with index `hook`, see `Hooks::find`.
+    pub(crate) fn get(&self, hook: i32) -> Result<Hook> {
+        usize::try_from(hook)
+            .ok()
+            .and_then(|hook| self.0.get(hook))
+            .cloned()
+            .with_context(|| format!("Invalid hook {hook}"))
+    }
+}
+
+impl Hook {
+    /// Calls the hook with the arguments `args`, which must be as many as the hook takes.
+    pub(crate) fn call(&self, store: &mut Store<NvimHost>, args: &[i64]) -> Result<bool> {
+        let name = &*self.name;
+        let (result,) = match (self.func, args) {
+            (HookFunc::Args1(func), &[a]) => call(store, name, func, (a,)),
+            (HookFunc::Args2(func), &[a, b]) => call(store, name, func, (a, b)),
+            (HookFunc::Args3(func), &[a, b, c]) => call(store, name, func, (a, b, c)),
+            (HookFunc::Args4(func), &[a, b, c, d]) => call(store, name, func, (a, b, c, d)),
+            _ => bail!(
+                "The hook {name} takes {} arguments, got {}",
+                self.func.args_len(),
+                args.len()
+            ),
+        }?;
+        Ok(result)
+    }
+}
+
+impl HookFunc {
+    fn args_len(self) -> usize {
+        match self {
+            HookFunc::Args1(_) => 1,
+            HookFunc::Args2(_) => 2,
+            HookFunc::Args3(_) => 3,
+            HookFunc::Args4(_) => 4,
+        }
+    }
+}
+
+/// Calls the hook `func` named `name` with `params`.
+fn call<P>(
+    store: &mut Store<NvimHost>,
+    name: &str,
+    func: TypedFunc<P, (bool,)>,
+    params: P,
+) -> Result<(bool,)>
+where
+    P: ComponentNamedList + Lower,
+{
+    let results = profile::profile_export_call(&mut *store, name, |store| func.call(store, params))
+        .with_context(|| {
+            format!(
+                "The function call to {name} trapped (an runtime exception is raised) or failed"
+            )
+        })?;
+    func.post_return(store)?;
+    Ok(results)
+}
@@ -1,18 +1,42 @@
 use std::{
+    collections::BTreeMap,
     ffi::{c_char, CStr, CString},
-    sync::{Mutex, OnceLock},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, MutexGuard, OnceLock, TryLockError,
+    },
 };
 
 use anyhow::{bail, Context, Result};
-use nvim::api::{nvim_api, nvim_keysets, nvim_types};
-use nvim_rs::{slice_from_ffi_ref, types::NvimObject};
-use slab::Slab;
-use types::{FromWasmType, TryIntoWasmType};
+use hooks::Hooks;
+use linking::{MissingProvider, PluginLinker};
+use log::PluginLog;
+use nvim::api::{nvim_api, nvim_api_deprecated, nvim_keysets, nvim_types};
+use nvim_rs::{
+    slice_from_ffi_ref,
+    types::{NvimDictionary, NvimObject},
+};
+use profile::SharedProfile;
+use storage::PluginStorage;
+use types::{FromWasmType, TryIntoWasmType, WasmBufBytesEvent, WasmBufLinesEvent};
 use wasmtime::{
-    component::{Component, Instance, Linker, TypedFunc},
-    Engine, Store,
+    component::{Component, Func, Instance, Linker, ResourceTable, TypedFunc},
+    Engine, Precompiled, ResourceLimiter, Store,
 };
+use wasmtime_wasi::{IoView, WasiCtx, WasiCtxBuilder, WasiView};
 
+mod buffer_reader;
+mod hooks;
+mod linking;
+mod log;
+mod paths;
+mod process;
+mod profile;
+mod runtime_info;
+mod storage;
+mod timer;
+mod trust;
 mod types;
 
 /// Initializes the Nvim WASM module.
@@ -24,11 +48,18 @@ mod types;
 /// Panics when failing to create the wasm engine.
 #[no_mangle]
 pub extern "C" fn wasm_rs_init() {
-    let config = wasm_config();
+    let mut config = nvim_wasm_compile::wasm_config();
+    profile::configure(&mut config);
     init_wasm_state(&config);
 }
 
-/// Loads the WASM binary to the global store and returns the instance ID.
+/// Loads the WASM binary to a new store and returns the instance ID.
+///
+/// `user_initiated` is false when the plugin is loaded without the user asking for it, e.g. when it
+/// is found in 'runtimepath'. The user is then asked whether to trust the plugin, see `trust.rs`.
+///
+/// Returns -1 on error, or -2 when the plugin imports an interface that no loaded plugin exports:
+/// loading it again may then succeed after loading other plugins.
 ///
 /// # Safety
 /// The `file_path` pointer must be a valid UTF-8 CString.
@@ -38,16 +69,105 @@ pub extern "C" fn wasm_rs_init() {
 #[no_mangle]
 pub unsafe extern "C" fn wasm_load_file(
     file_path: *const c_char,
+    user_initiated: bool,
     errmsg: *mut *const c_char,
 ) -> i32 {
     let file_path = unsafe { CStr::from_ptr(file_path) }
         .to_str()
         .expect("File path is not a valid utf-8 string");
-    let result = wasm_load_file_impl(file_path);
+    let result = wasm_load_file_impl(file_path, user_initiated);
+    let err_val = match &result {
+        Err(err) if err.is::<MissingProvider>() => -2,
+        _ => -1,
+    };
+
+    unwrap_or_set_error_and_return(result, errmsg, err_val)
+}
+
+/// Loads a WASM binary or WAT text from memory to a new store and returns the instance ID.
+///
+/// # Arguments
+/// * `bytes` - The component or core module.
+/// * `len` - The length of `bytes`.
+/// * `name` - The name of the plugin, shown in error messages, or null. It names the log file and
+///   the store of the plugin, unless the plugin declares another name: plugins without a name have
+///   neither. Plugins loaded by WASM plugins cannot have a name, which could be the name of
+///   another plugin.
+/// * `user_initiated` - False if the user did not ask for loading the plugin, see `wasm_load_file`.
+/// * `errmsg` - If errored, a string describing the error will be stored.
+///
+/// # Safety
+/// `bytes` should point to `len` bytes, and `name` must be null or a valid UTF-8 CString.
+#[no_mangle]
+pub unsafe extern "C" fn wasm_load_bytes(
+    bytes: *const c_char,
+    len: usize,
+    name: *const c_char,
+    user_initiated: bool,
+    errmsg: *mut *const c_char,
+) -> i32 {
+    let bytes = if len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(bytes as *const u8, len)
+    };
+    let name = (!name.is_null()).then(|| {
+        CStr::from_ptr(name)
+            .to_str()
+            .expect("Plugin name is not a valid utf-8 string")
+    });
+    let display_name = name.unwrap_or(UNNAMED_PLUGIN);
+    let source = format!("plugin {display_name}");
+    let owner = (nvim_sys::wasm_current_instance() < 0).then_some(MEMORY_OWNER);
+    let result = trust::check_trust(bytes, display_name, user_initiated)
+        .with_context(|| format!("Failed to load the WASM {source}"))
+        .and_then(|grant| {
+            let precompiled = Err("Precompiled components can only be loaded from files");
+            wasm_load_impl(
+                bytes,
+                name,
+                owner,
+                &source,
+                &grant.capabilities,
+                precompiled,
+            )
+        });
 
     unwrap_or_set_error_and_return(result, errmsg, -1)
 }
 
+/// Sets the trust of the WASM file `file_path` in the trust database, see `:WasmTrust`.
+///
+/// # Arguments
+/// * `file_path` - The WASM file.
+/// * `action` - "allow" to trust the current contents of the file, "deny" to never run them, or
+///   "remove" to remove the file from the trust database.
+/// * `capabilities` - The comma-separated capabilities granted to the file, e.g. "process".
+/// * `errmsg` - If errored, a string describing the error will be stored.
+///
+/// # Safety
+/// `file_path`, `action` and `capabilities` must be valid UTF-8 CStrings.
+#[no_mangle]
+pub unsafe extern "C" fn wasm_trust_file(
+    file_path: *const c_char,
+    action: *const c_char,
+    capabilities: *const c_char,
+    errmsg: *mut *const c_char,
+) {
+    let file_path = CStr::from_ptr(file_path)
+        .to_str()
+        .expect("File path is not a valid utf-8 string");
+    let action = CStr::from_ptr(action)
+        .to_str()
+        .expect("Action is not a valid utf-8 string");
+    let capabilities = CStr::from_ptr(capabilities)
+        .to_str()
+        .expect("Capabilities are not a valid utf-8 string");
+    let result = wasm_trust_file_impl(file_path, action, capabilities);
+
+    unwrap_or_set_error_and_return(result, errmsg, ())
+}
+
 /// Calls a function exported by a WASM instance.
 ///
 /// # Arguments
@@ -75,6 +195,216 @@ pub unsafe extern "C" fn wasm_call_func(
     unwrap_or_set_error_and_return(result, errmsg, NvimObject::nil()).into_ffi()
 }
 
+/// Returns the index of the hook `func_name` exported by a WASM instance, i.e. a function taking
+/// `args_len` `s64` arguments and returning a `bool`, to be called with `wasm_call_hook`.
+///
+/// The hooks are looked up when the instance is loaded, so this also works while the instance is
+/// running, e.g. when it calls `nvim_wasm_set_decoration_provider`.
+///
+/// # Arguments
+/// * `instance_id` - The instance ID returned by `wasm_load_file`.
+/// * `func_name` - The function name.
+/// * `args_len` - The number of arguments, at most 4.
+/// * `errmsg` - If errored, a string describing the error will be stored.
+///
+/// # Safety
+/// `func_name` must be a valid UTF-8 CString, and `errmsg` should be non-null.
+#[no_mangle]
+pub unsafe extern "C" fn wasm_find_hook(
+    instance_id: i32,
+    func_name: *const c_char,
+    args_len: usize,
+    errmsg: *mut *const c_char,
+) -> i32 {
+    let func_name = CStr::from_ptr(func_name)
+        .to_str()
+        .expect("Function name is not a valid utf-8 string");
+    let result = lock_instances()
+        .get(instance_id)
+        .with_context(|| format!("Cannot find instance with ID = {instance_id}"))
+        .and_then(|loaded| loaded.info.hooks.find(func_name, args_len));
+
+    unwrap_or_set_error_and_return(result, errmsg, -1)
+}
+
+/// Calls a hook exported by a WASM instance, found by `wasm_find_hook`.
+///
+/// Unlike `wasm_call_func`, the hook is not looked up by name, and the arguments and the result
+/// are not converted from and to API objects, so this is fast enough for the decoration providers
+/// of WASM plugins, which are called for every redrawn line.
+///
+/// # Arguments
+/// * `instance_id` - The instance ID returned by `wasm_load_file`.
+/// * `hook` - The index returned by `wasm_find_hook`.
+/// * `args` - The arguments, as many as passed to `wasm_find_hook`.
+/// * `args_len` - The number of arguments.
+/// * `errmsg` - If errored, a string describing the error will be stored.
+///
+/// # Safety
+/// All the pointers argument should be non-null, and `args` should point to `args_len` integers.
+#[no_mangle]
+pub unsafe extern "C" fn wasm_call_hook(
+    instance_id: i32,
+    hook: i32,
+    args: *const i64,
+    args_len: usize,
+    errmsg: *mut *const c_char,
+) -> bool {
+    let args = std::slice::from_raw_parts(args, args_len);
+    let result = wasm_call_hook_impl(instance_id, hook, args);
+
+    unwrap_or_set_error_and_return(result, errmsg, false)
+}
+
+/// Calls the `on_lines` callback exported by a WASM instance, of type
+/// `func(event: buf-lines-event) -> bool`. See `nvim_wasm_buf_attach`.
+///
+/// Returns true if the callback detaches from the buffer.
+///
+/// # Safety
+/// All the pointers argument should be non-null.
+#[no_mangle]
+pub unsafe extern "C" fn wasm_call_buf_lines(
+    instance_id: i32,
+    func_name: *const c_char,
+    event: *const WasmBufLinesEvent,
+    errmsg: *mut *const c_char,
+) -> bool {
+    let func_name = CStr::from_ptr(func_name)
+        .to_str()
+        .expect("Function name is not a valid utf-8 string");
+    let event = nvim_types::BufLinesEvent::from(&*event);
+    let result = wasm_call_buf_event_impl(instance_id, func_name, event, "buf-lines-event");
+
+    unwrap_or_set_error_and_return(result, errmsg, false)
+}
+
+/// Calls the `on_bytes` callback exported by a WASM instance, of type
+/// `func(event: buf-bytes-event) -> bool`. See `nvim_wasm_buf_attach`.
+///
+/// Returns true if the callback detaches from the buffer.
+///
+/// # Safety
+/// All the pointers argument should be non-null.
+#[no_mangle]
+pub unsafe extern "C" fn wasm_call_buf_bytes(
+    instance_id: i32,
+    func_name: *const c_char,
+    event: *const WasmBufBytesEvent,
+    errmsg: *mut *const c_char,
+) -> bool {
+    let func_name = CStr::from_ptr(func_name)
+        .to_str()
+        .expect("Function name is not a valid utf-8 string");
+    let event = nvim_types::BufBytesEvent::from(&*event);
+    let result = wasm_call_buf_event_impl(instance_id, func_name, event, "buf-bytes-event");
+
+    unwrap_or_set_error_and_return(result, errmsg, false)
+}
+
+/// Calls the hook exported by a WASM instance receiving the output of its process, of type
+/// `func(process: s64, data: list<u8>)`. See `wasm_process_spawn`.
+///
+/// # Safety
+/// `func_name` must be a valid UTF-8 CString, `data` should point to `len` bytes, and `errmsg`
+/// should be non-null.
+#[no_mangle]
+pub unsafe extern "C" fn wasm_call_process_output(
+    instance_id: i32,
+    func_name: *const c_char,
+    process: i64,
+    data: *const c_char,
+    len: usize,
+    errmsg: *mut *const c_char,
+) {
+    let func_name = CStr::from_ptr(func_name)
+        .to_str()
+        .expect("Function name is not a valid utf-8 string");
+    let data = if len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(data as *const u8, len)
+    };
+    let result = wasm_call_process_output_impl(instance_id, func_name, process, data);
+
+    unwrap_or_set_error_and_return(result, errmsg, ())
+}
+
+/// Calls the hook exported by a WASM instance receiving the exit status of its process, of type
+/// `func(process: s64, status: s32)`. See `wasm_process_spawn`.
+///
+/// # Safety
+/// `func_name` must be a valid UTF-8 CString, and `errmsg` should be non-null.
+#[no_mangle]
+pub unsafe extern "C" fn wasm_call_process_exit(
+    instance_id: i32,
+    func_name: *const c_char,
+    process: i64,
+    status: i32,
+    errmsg: *mut *const c_char,
+) {
+    let func_name = CStr::from_ptr(func_name)
+        .to_str()
+        .expect("Function name is not a valid utf-8 string");
+    let result = wasm_call_process_exit_impl(instance_id, func_name, process, status);
+
+    unwrap_or_set_error_and_return(result, errmsg, ())
+}
+
+/// Unloads the instance with ID `instance_id`, dropping its store.
+///
+/// Returns false on error, e.g. when the instance is running or exports interfaces to other
+/// instances.
+///
+/// # Safety
+/// `errmsg` should be non-null.
+#[no_mangle]
+pub unsafe extern "C" fn wasm_unload_instance(
+    instance_id: i32,
+    errmsg: *mut *const c_char,
+) -> bool {
+    let result = wasm_unload_impl(instance_id).map(|()| true);
+
+    unwrap_or_set_error_and_return(result, errmsg, false)
+}
+
+/// Returns information about the WASM runtime and the loaded instances, see
+/// `nvim_wasm_get_runtime_info`.
+#[no_mangle]
+pub extern "C" fn wasm_get_runtime_info() -> nvim_sys::Dictionary {
+    runtime_info::runtime_info().into_ffi()
+}
+
+/// Starts profiling the calls to and from the instances, clearing the previous profiles.
+#[no_mangle]
+pub extern "C" fn wasm_profile_start() {
+    profile::start();
+}
+
+/// Stops profiling the calls to and from the instances.
+#[no_mangle]
+pub extern "C" fn wasm_profile_stop() {
+    profile::stop();
+}
+
+/// Returns the profiles of the instances, see `nvim_wasm_profile`.
+#[no_mangle]
+pub extern "C" fn wasm_get_profiles() -> nvim_sys::Array {
+    profile::profiles().into_ffi()
+}
+
+/// Sets the level below which the entries logged by plugins are dropped, as `vim.log.levels`.
+#[no_mangle]
+pub extern "C" fn wasm_set_log_level(level: i64) {
+    log::set_level(level);
+}
+
+/// Sets whether the warnings and errors logged by plugins are also shown with `vim.notify()`.
+#[no_mangle]
+pub extern "C" fn wasm_set_log_notify(notify: bool) {
+    log::set_notify(notify);
+}
+
 unsafe fn unwrap_or_set_error_and_return<T>(
     result: Result<T>,
     errmsg: *mut *const c_char,
@@ -99,13 +429,85 @@ unsafe fn unwrap_or_set_error_and_return<T>(
 /// The global state of the Nvim WASM module.
 struct WasmState {
     engine: Engine,
-    mutate_state: Mutex<WasmMutateState>,
+    linker: Mutex<PluginLinker>,
+    instances: Mutex<Instances>,
+}
+
+/// The table of the loaded instances, by instance ID.
+///
+/// IDs are not reused when instances are unloaded, so that what an unloaded instance registered,
+/// e.g. an autocmd, fails instead of calling another instance.
+#[derive(Default)]
+struct Instances {
+    loaded: BTreeMap<i32, LoadedInstance>,
+    next_id: i32,
+}
+
+impl Instances {
+    fn get(&self, instance_id: i32) -> Option<&LoadedInstance> {
+        self.loaded.get(&instance_id)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (i32, &LoadedInstance)> {
+        self.loaded.iter().map(|(id, loaded)| (*id, loaded))
+    }
+
+    /// Reserves the ID of an instance before instantiating it, see `Instances::insert`.
+    ///
+    /// Fails if no more instances can be loaded, i.e. if all the IDs are used.
+    fn reserve(&mut self) -> Result<i32> {
+        // This should rarely happen. No one loads 2^31 WASM files...
+        if self.next_id == i32::MAX {
+            bail!(
+                "Cannot load new WASM file because the number of instances has reached the limit."
+            );
+        }
+        let instance_id = self.next_id;
+        self.next_id += 1;
+        Ok(instance_id)
+    }
+
+    /// Adds the instance with the ID `instance_id` returned by `Instances::reserve`.
+    fn insert(&mut self, instance_id: i32, loaded: LoadedInstance) {
+        self.loaded.insert(instance_id, loaded);
+    }
+
+    fn remove(&mut self, instance_id: i32) -> Option<LoadedInstance> {
+        self.loaded.remove(&instance_id)
+    }
 }
 
-struct WasmMutateState {
+/// An entry of the table of instances.
+struct LoadedInstance {
+    instance: Arc<Mutex<WasmInstance>>,
+    info: InstanceInfo,
+}
+
+/// What is known about an instance, even while it is running.
+struct InstanceInfo {
+    /// Where the component comes from, e.g. `file plugin.wasm`.
+    source: String,
+    /// The name of the plugin, see `wasm_load_impl`.
+    name: Option<String>,
+    /// Where the plugin comes from, which owns its name, see `wasm_load_impl`.
+    owner: String,
+    /// The version of the `nvim:api` package imported by the component, if any.
+    api_version: Option<(u32, u32, u32)>,
+    /// The size of the linear memories of the instance, in bytes.
+    memory_size: Arc<AtomicUsize>,
+    /// The calls to and from the instance recorded while profiling.
+    profile: SharedProfile,
+    /// The hooks exported by the instance.
+    hooks: Hooks,
+}
+
+/// A loaded WASM instance.
+///
+/// Every instance has its own store, so that a WASM call can run another instance, e.g. when a
+/// plugin calls `nvim_wasm_call_func` or fires an autocmd handled by another plugin.
+struct WasmInstance {
     store: Store<NvimHost>,
-    linker: Linker<NvimHost>,
-    instances: Slab<Instance>,
+    instance: Instance,
 }
 
 /// The global instance of the Nvim WASM module state.
@@ -116,27 +518,17 @@ fn state() -> &'static WasmState {
     WASM_STATE.get().expect("Wasm state is not initialized")
 }
 
-/// Returns the config for creating the WASM engine.
-fn wasm_config() -> wasmtime::Config {
-    let mut config = wasmtime::Config::new();
-    config.wasm_component_model(true);
-    config
-}
-
 fn init_wasm_state(config: &wasmtime::Config) {
     let engine = Engine::new(config).expect("Failed to create wasm engine");
-    let store = Store::new(&engine, NvimHost);
     let mut linker = Linker::new(&engine);
     Plugin::add_to_linker(&mut linker, |state| state)
         .expect("Failed to add the host bindings to WASM linker");
+    wasmtime_wasi::add_to_linker_sync(&mut linker).expect("Failed to add WASI to WASM linker");
     WASM_STATE
         .set(WasmState {
             engine,
-            mutate_state: Mutex::new(WasmMutateState {
-                store,
-                linker,
-                instances: Slab::new(),
-            }),
+            linker: Mutex::new(PluginLinker::new(linker)),
+            instances: Mutex::new(Instances::default()),
         })
         .map_err(|_| ())
         .expect("Failed to initialize wasm state");
@@ -144,22 +536,385 @@ fn init_wasm_state(config: &wasmtime::Config) {
 
 const MUTEX_POISONED_ERR: &str = "Mutex is poisoned";
 
-fn wasm_load_file_impl(file_path: &str) -> Result<i32> {
+/// How plugins loaded from memory without a name are shown, e.g. in error messages. It does not
+/// name a log file or a store, which would be shared by all these plugins.
+const UNNAMED_PLUGIN: &str = "[bytes]";
+
+/// The owner of the plugins loaded from memory by the user, e.g. embedded in a Lua plugin, see
+/// `wasm_load_impl`.
+const MEMORY_OWNER: &str = "memory";
+
+/// Locks the linker to `action`, e.g. "load a WASM file", which is named in the error.
+///
+/// Fails instead of deadlocking when a plugin is loaded or unloaded while instantiating another
+/// one.
+fn lock_linker(action: &str) -> Result<MutexGuard<'static, PluginLinker>> {
+    match state().linker.try_lock() {
+        Ok(linker) => Ok(linker),
+        Err(TryLockError::WouldBlock) => {
+            bail!("Cannot {action} while another one is being instantiated")
+        }
+        Err(TryLockError::Poisoned(_)) => panic!("{MUTEX_POISONED_ERR}"),
+    }
+}
+
+/// Locks the table of instances.
+///
+/// The table is only locked to add, look up or remove an instance, never while WASM code runs.
+fn lock_instances() -> MutexGuard<'static, Instances> {
+    state().instances.lock().expect(MUTEX_POISONED_ERR)
+}
+
+fn wasm_load_file_impl(file_path: &str, user_initiated: bool) -> Result<i32> {
+    let source = format!("file {file_path}");
+    let bytes =
+        std::fs::read(file_path).with_context(|| format!("Failed to load the WASM {source}"))?;
+    let trust_source = trust::file_source(file_path);
+    let grant = trust::check_trust(&bytes, &trust_source, user_initiated)
+        .with_context(|| format!("Failed to load the WASM {source}"))?;
+    let name = Path::new(file_path)
+        .file_stem()
+        .map_or(file_path.into(), |stem| stem.to_string_lossy());
+    // Precompiled components are native code, which only the user can choose to run.
+    let precompiled = if unsafe { nvim_sys::wasm_current_instance() } >= 0 {
+        Err("WASM plugins cannot load precompiled components")
+    } else if !grant.allowed {
+        Err("Precompiled components must be allowed with :WasmTrust before they are loaded")
+    } else {
+        Ok(())
+    };
+    wasm_load_impl(
+        &bytes,
+        Some(&name),
+        Some(&trust_source),
+        &source,
+        &grant.capabilities,
+        precompiled,
+    )
+}
+
+fn wasm_trust_file_impl(file_path: &str, action: &str, capabilities: &str) -> Result<()> {
+    // Only the user decides what to trust, not a plugin calling `:WasmTrust` or
+    // `nvim_wasm_trust()` through `nvim_command()` and the like.
+    if unsafe { nvim_sys::wasm_current_instance() } >= 0 {
+        bail!("WASM plugins cannot change the trust database");
+    }
+    let source = trust::file_source(file_path);
+    let mut db = trust::TrustDb::read()?;
+    if action == "remove" {
+        // The file may have been deleted, its entries are still removed.
+        let hash = std::fs::read(file_path)
+            .ok()
+            .map(|bytes| trust::sha256(&bytes));
+        return db.remove(hash.as_deref(), &source);
+    }
+
+    let trust = match action {
+        "allow" => trust::Trust::Allow,
+        "deny" => trust::Trust::Deny,
+        _ => bail!("Invalid action: {action}"),
+    };
+    let bytes = std::fs::read(file_path)
+        .with_context(|| format!("Failed to read the WASM file {file_path}"))?;
+    let capabilities = capabilities
+        .split(',')
+        .filter(|capability| !capability.is_empty())
+        .map(str::to_string)
+        .collect();
+    db.set(trust::sha256(&bytes), trust, capabilities, source)
+}
+
+/// Loads the component or core module `bytes`, in binary or text format, and returns the instance
+/// ID.
+///
+/// `name` is the name of the plugin, e.g. the stem of its file, which names its log file and its
+/// store unless the plugin declares another name. Plugins without a name have neither. `owner`
+/// tells where the plugin comes from, e.g. the canonical path of its file, so that plugins from
+/// elsewhere cannot take its name while it is loaded, nor its store, see `PluginStorage`. It is
+/// `None` for plugins loaded from memory by WASM plugins, which cannot have a name. `source`
+/// tells where `bytes` come from in error messages, e.g. `file plugin.wasm`. `capabilities` are
+/// the capabilities granted to the plugin in the trust database. `precompiled` is `Ok` if `bytes`
+/// may be a precompiled component, see `compile_component`.
+fn wasm_load_impl(
+    bytes: &[u8],
+    name: Option<&str>,
+    owner: Option<&str>,
+    source: &str,
+    capabilities: &[String],
+    precompiled: Result<(), &str>,
+) -> Result<i32> {
     // TODO: It will be helpful to cache the compiled component here.
-    let component = Component::from_file(&state().engine, file_path)
-        .with_context(|| format!("Failed to load the WASM file {}", file_path))?;
+    let (component, declared_name) = compile_component(bytes, precompiled)
+        .with_context(|| format!("Failed to load the WASM {source}"))?;
+    let api_version = check_api_version(&component)
+        .with_context(|| format!("Failed to load the WASM {source}"))?;
+
+    let mut linker = lock_linker("load a WASM file")?;
+    linker
+        .check_imports(&component, capabilities)
+        .with_context(|| format!("Failed to load the WASM {source}"))?;
+    let interfaces = linker
+        .exported_interfaces(&component)
+        .with_context(|| format!("Failed to load the WASM {source}"))?;
+    let memory_size = Arc::new(AtomicUsize::new(0));
+    let profile = SharedProfile::default();
+    let name = declared_name.as_deref().or(name);
+    if let Some(name) = name {
+        check_plugin_name(name, owner)
+            .with_context(|| format!("Failed to load the WASM {source}"))?;
+    }
+    let owner = owner.unwrap_or_default();
+    let host = NvimHost::new(memory_size.clone(), profile.clone(), name, owner);
+    let mut store = Store::new(&state().engine, host);
+    store.limiter(|host| host);
+    if profile::meters_fuel() {
+        store.set_fuel(u64::MAX)?;
+    }
+
+    // The instance may call Nvim while it is instantiated, e.g. from the start function of a core
+    // module, so it runs with its own ID like in `wasm_call`. What it starts or registers before
+    // failing to instantiate is stopped or removed, as for unloaded instances.
+    let instance_id = lock_instances().reserve()?;
+    let call_state = unsafe { nvim_sys::wasm_linked_call_enter(instance_id) };
+    let instantiated = Plugin::instantiate(&mut store, &component, &linker.linker)
+        .with_context(|| format!("Failed to instantiate the WASM {source}"));
+    unsafe { nvim_sys::wasm_linked_call_leave(call_state) };
+    let instantiated = instantiated.and_then(|(_, instance)| {
+        Hooks::new(&state().engine, &component, &instance, &mut store)
+            .with_context(|| format!("Failed to load the WASM {source}"))
+            .map(|hooks| (instance, hooks))
+    });
+    let (instance, hooks) = match instantiated {
+        Ok(instantiated) => instantiated,
+        Err(err) => {
+            unsafe { nvim_sys::wasm_stop_instance(instance_id) };
+            return Err(err);
+        }
+    };
+
+    let instance = LoadedInstance {
+        instance: Arc::new(Mutex::new(WasmInstance { store, instance })),
+        info: InstanceInfo {
+            source: source.to_string(),
+            name: name.map(str::to_string),
+            owner: owner.to_string(),
+            api_version,
+            memory_size,
+            profile,
+            hooks,
+        },
+    };
+    lock_instances().insert(instance_id, instance);
+    if let Err(err) = linker.link_exports(instance_id, interfaces) {
+        // The instance is dropped after unlocking the table, as in `wasm_unload_impl`.
+        let unloaded = lock_instances().remove(instance_id);
+        drop(unloaded);
+        unsafe { nvim_sys::wasm_stop_instance(instance_id) };
+        return Err(err).with_context(|| format!("Failed to load the WASM {source}"));
+    }
+    Ok(instance_id)
+}
+
+/// Checks that the plugin from `owner` can be named `name`: the plugins loaded by WASM plugins
+/// cannot, and no plugin loaded from elsewhere may have the same log file and store.
+fn check_plugin_name(name: &str, owner: Option<&str>) -> Result<()> {
+    let Some(owner) = owner else {
+        bail!(
+            "WASM plugins cannot load plugins with a name: it could be the name of another plugin"
+        );
+    };
+    let file_name = paths::plugin_file_name(name);
+    for (id, loaded) in lock_instances().iter() {
+        let info = &loaded.info;
+        if info.owner != owner
+            && file_name.is_some()
+            && info.name.as_deref().and_then(paths::plugin_file_name) == file_name
+        {
+            bail!(
+                "The plugin name `{name}` is used by WASM instance {id}, loaded from {}",
+                info.source
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Unloads the instance with ID `instance_id`. The C side stops its timers, see `wasm_unload`.
+///
+/// Fails if the instance is running, or if it exports interfaces: they stay defined in the linker,
+/// and plugins may import them.
+fn wasm_unload_impl(instance_id: i32) -> Result<()> {
+    let linker = lock_linker("unload a WASM plugin")?;
+    if let Some(interface) = linker.exported_interface(instance_id) {
+        bail!("Cannot unload WASM instance {instance_id}, which exports `{interface}`");
+    }
+    let mut instances = lock_instances();
+    let loaded = instances
+        .get(instance_id)
+        .with_context(|| format!("Cannot find instance with ID = {instance_id}"))?;
+    if loaded.instance.try_lock().is_err() {
+        bail!("Cannot unload WASM instance {instance_id} while it is running");
+    }
+    let unloaded = instances.remove(instance_id);
+    // The store is dropped after unlocking the table.
+    drop(instances);
+    drop(unloaded);
+    Ok(())
+}
+
+/// Compiles the component `bytes`, in binary or text format. Core modules are wrapped in a
+/// component first. Components precompiled by `nvim-wasm-precompile` are loaded without compiling,
+/// if `precompiled` is `Ok`. Otherwise, it tells why they are refused.
+///
+/// Also returns the name declared by the plugin, see `nvim_wasm_compile::plugin_name`, which
+/// `nvim-wasm-precompile` keeps after precompiled components.
+fn compile_component(
+    bytes: &[u8],
+    precompiled: Result<(), &str>,
+) -> Result<(Component, Option<String>)> {
+    let engine = &state().engine;
+    let (precompiled_bytes, precompiled_name) = nvim_wasm_compile::split_precompiled_name(bytes)?;
+    match engine.detect_precompiled(precompiled_bytes) {
+        Some(Precompiled::Component) => {
+            if let Err(reason) = precompiled {
+                bail!("{reason}");
+            }
+            // SAFETY: Precompiled components are native code, which Wasmtime runs without
+            // validating it. They are only deserialized from files the user allowed in the trust
+            // database, see `wasm_load_file_impl`. Wasmtime refuses the ones compiled by another
+            // version of Wasmtime, for another target or with another config than `wasm_config()`.
+            unsafe { Component::deserialize(engine, precompiled_bytes) }
+                .context(
+                    "The precompiled component is incompatible with this Nvim, \
+                     precompile it again with nvim-wasm-precompile",
+                )
+                .map(|component| (component, precompiled_name))
+        }
+        Some(Precompiled::Module) => {
+            bail!("Precompiled core modules are not supported, precompile a component instead")
+        }
+        None => {
+            let binary = nvim_wasm_compile::component_binary(bytes)?;
+            let name = nvim_wasm_compile::plugin_name(&binary)?;
+            Ok((Component::from_binary(engine, &binary)?, name))
+        }
+    }
+}
+
+/// Checks that the component is built against an `nvim:api` package the host provides, and returns
+/// the version of the package it imports, if any.
+///
+/// The package is versioned by API level under a stable major version, e.g. `1.12.0`, and Wasmtime
+/// links the imports of an older API level to the host package. Components built against a newer
+/// API level, or against the packages versioned by Nvim release (`0.x`), are refused here rather
+/// than failing to link.
+fn check_api_version(component: &Component) -> Result<Option<(u32, u32, u32)>> {
+    let (major, minor, patch) = NVIM_API_PACKAGE_VERSION;
+    let mut api_version = None;
+    for (name, _) in component.component_type().imports(&state().engine) {
+        let Some((_, version)) = name
+            .strip_prefix("nvim:api/")
+            .and_then(|name| name.split_once('@'))
+        else {
+            continue;
+        };
+        let version = parse_package_version(version)?;
+        if version.0 != major {
+            bail!(
+                "The plugin imports `{name}`, from a `nvim:api` package this Nvim does not \
+                 support anymore. Rebuild it against nvim:api@{major}.{minor}.{patch} \
+                 (API level {NVIM_API_LEVEL})"
+            );
+        }
+        if version > NVIM_API_PACKAGE_VERSION {
+            bail!(
+                "The plugin imports `{name}`, but this Nvim only provides \
+                 nvim:api@{major}.{minor}.{patch} (API level {NVIM_API_LEVEL})"
+            );
+        }
+        api_version = api_version.max(Some(version));
+    }
+
+    Ok(api_version)
+}
+
+/// Parses a WIT package version like `1.12.0`. Prerelease and build metadata are ignored.
+fn parse_package_version(version: &str) -> Result<(u32, u32, u32)> {
+    let core = version.split(['-', '+']).next().unwrap_or_default();
+    let numbers = core
+        .split('.')
+        .map(str::parse)
+        .collect::<Result<Vec<u32>, _>>()
+        .with_context(|| format!("Invalid package version {version}"))?;
+    let [major, minor, patch] = numbers[..] else {
+        bail!("Invalid package version {version}");
+    };
+
+    Ok((major, minor, patch))
+}
+
+/// Returns the instance with ID `instance_id`.
+fn get_instance(instance_id: i32) -> Result<Arc<Mutex<WasmInstance>>> {
+    if instance_id < 0 {
+        bail!("Instance ID should be non-negative, got {instance_id}")
+    }
+    lock_instances()
+        .get(instance_id)
+        .map(|loaded| loaded.instance.clone())
+        .with_context(|| format!("Cannot find instance with ID = {instance_id}"))
+}
 
-    let mut mutate_state = state().mutate_state.lock().expect(MUTEX_POISONED_ERR);
-    let mutate_state = &mut *mutate_state;
-    // This should rarely happen. No one loads 2^31 WASM files...
-    if mutate_state.instances.len() >= i32::MAX as usize {
-        bail!("Cannot load new WASM file because the number of instances has reached the limit.");
+/// Locks the instance `instance`.
+///
+/// Fails instead of deadlocking when the instance is already running, i.e. when Neovim runs code
+/// of an instance again from inside a call to the same instance (e.g. a plugin calling its own
+/// function through `nvim_wasm_call_func`). The component model forbids re-entering an instance,
+/// while calling other instances is fine.
+fn lock_instance(
+    instance: &Mutex<WasmInstance>,
+    instance_id: i32,
+) -> Result<MutexGuard<'_, WasmInstance>> {
+    match instance.try_lock() {
+        Ok(instance) => Ok(instance),
+        Err(TryLockError::WouldBlock) => {
+            bail!("Cannot call WASM instance {instance_id} while it is running (re-entrant call)")
+        }
+        Err(TryLockError::Poisoned(_)) => panic!("{MUTEX_POISONED_ERR}"),
     }
-    let (_, instance) =
-        Plugin::instantiate(&mut mutate_state.store, &component, &mutate_state.linker)
-            .with_context(|| format!("Failed to instantiate the WASM file {}", file_path))?;
+}
 
-    Ok(mutate_state.instances.insert(instance) as i32)
+/// Returns the function `func_name` exported by the instance with ID `instance_id`.
+fn get_func(instance: &mut WasmInstance, instance_id: i32, func_name: &str) -> Result<Func> {
+    instance
+        .instance
+        .get_func(&mut instance.store, func_name)
+        .with_context(|| format!("Cannot find function {func_name} in instance {instance_id}"))
+}
+
+/// Calls `func` with the parameters `params` and returns the results of type `results`.
+///
+/// `type_` is the WIT type of the function, shown when `func` has another type.
+macro_rules! call_typed_func {
+    ($store:expr, $func:expr, $func_name:expr, $type_:expr, $params:expr => $results:ty) => {{
+        let store: &mut Store<NvimHost> = $store;
+        let func = $func.typed::<_, $results>(&mut *store).with_context(|| {
+            format!(
+                "The function {} is not a function of type {}",
+                $func_name, $type_
+            )
+        })?;
+        let results = profile::profile_export_call(&mut *store, $func_name, |store| {
+            func.call(store, $params)
+        })
+        .with_context(|| {
+            format!(
+                "The function call to {} trapped (an runtime exception is raised) or failed",
+                $func_name
+            )
+        })?;
+        func.post_return(&mut *store)?;
+        results
+    }};
 }
 
 fn wasm_call_func_impl(
@@ -167,20 +922,13 @@ fn wasm_call_func_impl(
     func_name: &str,
     args: &[NvimObject],
 ) -> Result<NvimObject> {
-    if instance_id < 0 {
-        bail!("Instance ID should be non-negative, got {instance_id}")
-    }
-    let mut mutate_state = state().mutate_state.lock().expect(MUTEX_POISONED_ERR);
-    let instance = *mutate_state
-        .instances
-        .get(instance_id as usize)
-        .with_context(|| format!("Cannot find instance with ID = {instance_id}"))?;
+    let instance = get_instance(instance_id)?;
+    let mut instance = lock_instance(&instance, instance_id)?;
+    let instance = &mut *instance;
 
-    let func: TypedFunc<(Vec<nvim_api::Object>,), (nvim_api::Object,)> = instance
-        .get_func(&mut mutate_state.store, func_name)
-        .with_context(|| format!("Cannot find function {func_name} in instance {instance_id}"))?
-        .typed(&mut mutate_state.store)
-        .with_context(|| {
+    let func = get_func(instance, instance_id, func_name)?;
+    let func: TypedFunc<(Vec<nvim_api::Object>,), (nvim_api::Object,)> =
+        func.typed(&mut instance.store).with_context(|| {
             format!("The function {func_name} is not a function of type list<Object> -> Object")
         })?;
     let args = args
@@ -188,19 +936,161 @@ fn wasm_call_func_impl(
         .map(|obj| Ok(obj.clone().try_into_wasm_type()?))
         .collect::<Result<Vec<_>>>()?;
 
-    let (result,) = func.call(&mut mutate_state.store, (args,)).with_context(|| {
+    let (result,) = profile::profile_export_call(&mut instance.store, func_name, |store| {
+        func.call(store, (args,))
+    })
+    .with_context(|| {
       format!("The function call to {func_name} trapped (an runtime exception is raised) or failed")
     })?;
+    func.post_return(&mut instance.store)?;
     Ok(NvimObject::from_wasm_type(result))
 }
 
+fn wasm_call_hook_impl(instance_id: i32, hook: i32, args: &[i64]) -> Result<bool> {
+    let (instance, hook) = {
+        let instances = lock_instances();
+        let loaded = instances
+            .get(instance_id)
+            .with_context(|| format!("Cannot find instance with ID = {instance_id}"))?;
+        (loaded.instance.clone(), loaded.info.hooks.get(hook)?)
+    };
+    let mut instance = lock_instance(&instance, instance_id)?;
+    hook.call(&mut instance.store, args)
+}
+
+/// Calls a buffer update callback taking the event record `event`, of WIT type `event_type`.
+fn wasm_call_buf_event_impl<E>(
+    instance_id: i32,
+    func_name: &str,
+    event: E,
+    event_type: &str,
+) -> Result<bool>
+where
+    (E,): wasmtime::component::ComponentNamedList + wasmtime::component::Lower,
+{
+    let instance = get_instance(instance_id)?;
+    let mut instance = lock_instance(&instance, instance_id)?;
+    let func = get_func(&mut instance, instance_id, func_name)?;
+    let type_ = || format!("func(event: {event_type}) -> bool");
+    let (detach,) =
+        call_typed_func!(&mut instance.store, func, func_name, type_(), (event,) => (bool,));
+    Ok(detach)
+}
+
+/// Calls a process hook receiving the output `data` of the process `process`.
+fn wasm_call_process_output_impl(
+    instance_id: i32,
+    func_name: &str,
+    process: i64,
+    data: &[u8],
+) -> Result<()> {
+    let instance = get_instance(instance_id)?;
+    let mut instance = lock_instance(&instance, instance_id)?;
+    let func = get_func(&mut instance, instance_id, func_name)?;
+    let type_ = "func(process: s64, data: list<u8>)";
+    call_typed_func!(&mut instance.store, func, func_name, type_, (process, data) => ());
+    Ok(())
+}
+
+/// Calls a process hook receiving the exit status `status` of the process `process`.
+fn wasm_call_process_exit_impl(
+    instance_id: i32,
+    func_name: &str,
+    process: i64,
+    status: i32,
+) -> Result<()> {
+    let instance = get_instance(instance_id)?;
+    let mut instance = lock_instance(&instance, instance_id)?;
+    let func = get_func(&mut instance, instance_id, func_name)?;
+    let type_ = "func(process: s64, status: s32)";
+    call_typed_func!(&mut instance.store, func, func_name, type_, (process, status) => ());
+    Ok(())
+}
+
 // This generates all the types and interface defined in the wit file.
 wasmtime::component::bindgen!("plugin");
 
 /// Implements the host bindings.
 ///
 /// See `wit/nvim.wit` for the definition of the host bindings.
-struct NvimHost;
+struct NvimHost {
+    /// The WASI context of plugins built for WASI, without access to stdio, environment variables
+    /// or the file system.
+    wasi: WasiCtx,
+    table: ResourceTable,
+    /// The size of the linear memories of the instance, see `InstanceInfo`.
+    memory_size: Arc<AtomicUsize>,
+    /// The last growth of a linear memory, taken back if it fails.
+    memory_growth: usize,
+    /// The profile of the instance, see `InstanceInfo`.
+    profile: SharedProfile,
+    /// The log file of the plugin, see `log.rs`.
+    log: PluginLog,
+    /// The store of the plugin, see `storage.rs`.
+    storage: PluginStorage,
+}
+
+impl NvimHost {
+    /// Creates the host of the plugin `name` from `owner`, which names its log file and its store.
+    fn new(
+        memory_size: Arc<AtomicUsize>,
+        profile: SharedProfile,
+        name: Option<&str>,
+        owner: &str,
+    ) -> Self {
+        Self {
+            wasi: WasiCtxBuilder::new().build(),
+            table: ResourceTable::new(),
+            memory_size,
+            memory_growth: 0,
+            profile,
+            log: PluginLog::new(name, name.unwrap_or(UNNAMED_PLUGIN)),
+            storage: PluginStorage::new(name, owner),
+        }
+    }
+}
+
+/// Tracks the memory used by the instance. Nothing is limited.
+impl ResourceLimiter for NvimHost {
+    fn memory_growing(
+        &mut self,
+        current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        self.memory_growth = desired - current;
+        self.memory_size
+            .fetch_add(self.memory_growth, Ordering::Relaxed);
+        Ok(true)
+    }
+
+    fn memory_grow_failed(&mut self, _error: anyhow::Error) -> wasmtime::Result<()> {
+        self.memory_size
+            .fetch_sub(self.memory_growth, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: usize,
+        _desired: usize,
+        _maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        Ok(true)
+    }
+}
+
+impl IoView for NvimHost {
+    fn table(&mut self) -> &mut ResourceTable {
+        &mut self.table
+    }
+}
+
+impl WasiView for NvimHost {
+    fn ctx(&mut self) -> &mut WasiCtx {
+        &mut self.wasi
+    }
+}
 
 include!(concat!(env!("OUT_DIR"), "/api_impl.rs"));
 
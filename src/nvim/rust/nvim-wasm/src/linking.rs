@@ -0,0 +1,228 @@
+use std::{collections::HashMap, fmt::Display};
+
+use anyhow::{bail, Context, Result};
+use wasmtime::component::{types::ComponentItem, Component, Linker, Val};
+
+use crate::{get_instance, lock_instance, profile, state, NvimHost};
+
+/// The linker shared by all the instances.
+///
+/// Besides the host bindings, the linker defines the interfaces exported by the loaded plugins, so
+/// that plugins loaded later can import them. A call to such an import is forwarded to the
+/// instance exporting the interface, which runs in its own store.
+pub(crate) struct PluginLinker {
+    pub(crate) linker: Linker<NvimHost>,
+    /// The ID of the instance exporting each interface.
+    providers: HashMap<String, i32>,
+}
+
+/// An interface exported by a component, with the names of its functions.
+pub(crate) struct ExportedInterface {
+    name: String,
+    funcs: Vec<String>,
+}
+
+/// The error of `check_imports` when a plugin imports an interface that no loaded plugin exports.
+/// Loading the plugin again may succeed once the plugin exporting the interface is loaded.
+#[derive(Debug)]
+pub(crate) struct MissingProvider(String);
+
+impl std::error::Error for MissingProvider {}
+
+impl Display for MissingProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The plugin imports `{}`, which is not exported by any loaded plugin. \
+             Load the plugin exporting it first",
+            self.0
+        )
+    }
+}
+
+impl PluginLinker {
+    pub(crate) fn new(linker: Linker<NvimHost>) -> Self {
+        Self {
+            linker,
+            providers: HashMap::new(),
+        }
+    }
+
+    /// Checks that every interface imported by `component` is provided either by Nvim or by a
+    /// loaded plugin, and that the plugin was granted the capabilities required by the gated
+    /// interfaces it imports.
+    pub(crate) fn check_imports(
+        &self,
+        component: &Component,
+        capabilities: &[String],
+    ) -> Result<()> {
+        for (name, _) in component.component_type().imports(&state().engine) {
+            let interface = name
+                .split_once('@')
+                .map_or(name, |(interface, _)| interface);
+            if let Some((_, capability)) = GATED_INTERFACES
+                .iter()
+                .find(|(gated, _)| *gated == interface)
+            {
+                if !capabilities.iter().any(|granted| granted == capability) {
+                    bail!(
+                        "The plugin imports `{name}`, which requires the `{capability}` \
+                         capability. Trust it with `:WasmTrust ++allow={capability}` first"
+                    );
+                }
+            }
+            if is_host_interface(name) || self.providers.contains_key(name) {
+                continue;
+            }
+            return Err(MissingProvider(name.to_string()).into());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the interfaces exported by `component`.
+    ///
+    /// Fails if an interface is already exported by a loaded plugin, or if it exports resources,
+    /// which cannot be passed between stores.
+    pub(crate) fn exported_interfaces(
+        &self,
+        component: &Component,
+    ) -> Result<Vec<ExportedInterface>> {
+        let engine = &state().engine;
+        let mut interfaces = Vec::new();
+        for (name, item) in component.component_type().exports(engine) {
+            let ComponentItem::ComponentInstance(instance) = item else {
+                continue;
+            };
+            if is_host_interface(name) {
+                bail!("The plugin exports `{name}`, which is provided by Nvim");
+            }
+            if let Some(provider) = self.providers.get(name) {
+                bail!(
+                    "The plugin exports `{name}`, which is already exported by instance {provider}"
+                );
+            }
+            let mut funcs = Vec::new();
+            for (item_name, item) in instance.exports(engine) {
+                match item {
+                    ComponentItem::ComponentFunc(_) => funcs.push(item_name.to_string()),
+                    ComponentItem::Resource(_) => bail!(
+                        "The plugin exports the resource `{item_name}` in `{name}`, \
+                         but resources cannot be shared between plugins"
+                    ),
+                    _ => {}
+                }
+            }
+            interfaces.push(ExportedInterface {
+                name: name.to_string(),
+                funcs,
+            });
+        }
+
+        Ok(interfaces)
+    }
+
+    /// Returns an interface exported by the instance `instance_id`, if any.
+    pub(crate) fn exported_interface(&self, instance_id: i32) -> Option<&str> {
+        self.providers
+            .iter()
+            .find(|(_, provider)| **provider == instance_id)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Defines the interfaces `interfaces` exported by the instance `instance_id`.
+    ///
+    /// On failure, the instance provides none of them.
+    pub(crate) fn link_exports(
+        &mut self,
+        instance_id: i32,
+        interfaces: Vec<ExportedInterface>,
+    ) -> Result<()> {
+        let result = self.define_exports(instance_id, interfaces);
+        if result.is_err() {
+            self.providers
+                .retain(|_, provider| *provider != instance_id);
+        }
+        result
+    }
+
+    fn define_exports(
+        &mut self,
+        instance_id: i32,
+        interfaces: Vec<ExportedInterface>,
+    ) -> Result<()> {
+        for interface in interfaces {
+            let mut linker_instance = self.linker.instance(&interface.name)?;
+            for func_name in interface.funcs {
+                let (interface_name, export_name) = (interface.name.clone(), func_name.clone());
+                linker_instance.func_new(&func_name, move |_, params, results| {
+                    call_export(instance_id, &interface_name, &export_name, params, results)
+                })?;
+            }
+            self.providers.insert(interface.name, instance_id);
+        }
+
+        Ok(())
+    }
+}
+
+/// The interfaces provided by Nvim which a plugin may only import when it was granted a capability
+/// in the trust database, see `trust.rs`.
+const GATED_INTERFACES: &[(&str, &str)] = &[("nvim:api/process", "process")];
+
+/// Returns true if the interface `name` is provided by Nvim, i.e. is an `nvim:api` or WASI
+/// interface.
+fn is_host_interface(name: &str) -> bool {
+    name.starts_with("nvim:api/") || name.starts_with("wasi:")
+}
+
+/// Calls the function `func_name` of the interface `interface` exported by the instance
+/// `instance_id`.
+fn call_export(
+    instance_id: i32,
+    interface: &str,
+    func_name: &str,
+    params: &[Val],
+    results: &mut [Val],
+) -> Result<()> {
+    let call_state = unsafe { nvim_sys::wasm_linked_call_enter(instance_id) };
+    let result = call_export_impl(instance_id, interface, func_name, params, results);
+    unsafe { nvim_sys::wasm_linked_call_leave(call_state) };
+    result
+}
+
+fn call_export_impl(
+    instance_id: i32,
+    interface: &str,
+    func_name: &str,
+    params: &[Val],
+    results: &mut [Val],
+) -> Result<()> {
+    let instance = get_instance(instance_id)?;
+    let mut instance = lock_instance(&instance, instance_id)?;
+    let instance = &mut *instance;
+    let store = &mut instance.store;
+
+    let func = instance
+        .instance
+        .get_export(&mut *store, None, interface)
+        .and_then(|index| {
+            instance
+                .instance
+                .get_export(&mut *store, Some(&index), func_name)
+        })
+        .and_then(|index| instance.instance.get_func(&mut *store, index))
+        .with_context(|| {
+            format!("Cannot find function {func_name} of `{interface}` in instance {instance_id}")
+        })?;
+    profile::profile_export_call(
+        &mut *store,
+        format_args!("{interface}#{func_name}"),
+        |store| func.call(store, params, results),
+    )
+    .with_context(|| {
+        format!("The function call to {func_name} of `{interface}` trapped or failed")
+    })?;
+    func.post_return(&mut *store)?;
+    Ok(())
+}
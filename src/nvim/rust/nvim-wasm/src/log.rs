@@ -0,0 +1,152 @@
+use std::{
+    ffi::{c_char, CStr},
+    fmt::Write as _,
+    fs::{self, File, OpenOptions},
+    io::Write as _,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, AtomicI64, Ordering},
+};
+
+use nvim_rs::{NvimDictionary, NvimObject, NvimResult, NvimString};
+
+use crate::{
+    nvim::api::log::{self, Level},
+    nvim_types::{Dictionary, Primitive},
+    paths, NvimHost,
+};
+
+/// The `WARN` level of `vim.log.levels`, the default log level.
+const WARN: i64 = 3;
+
+/// The level below which entries are dropped, as `vim.log.levels`, see `nvim_wasm_set_log`.
+static LOG_LEVEL: AtomicI64 = AtomicI64::new(WARN);
+
+/// Whether warnings and errors are also shown with `vim.notify()`.
+static NOTIFY: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn set_level(level: i64) {
+    LOG_LEVEL.store(level, Ordering::Relaxed);
+}
+
+pub(crate) fn set_notify(notify: bool) {
+    NOTIFY.store(notify, Ordering::Relaxed);
+}
+
+fn level_value(level: Level) -> i64 {
+    match level {
+        Level::Trace => 0,
+        Level::Debug => 1,
+        Level::Info => 2,
+        Level::Warn => WARN,
+        Level::Error => 4,
+    }
+}
+
+fn level_name(level: Level) -> &'static str {
+    match level {
+        Level::Trace => "TRC",
+        Level::Debug => "DBG",
+        Level::Info => "INF",
+        Level::Warn => "WRN",
+        Level::Error => "ERR",
+    }
+}
+
+fn is_enabled(level: Level) -> bool {
+    level_value(level) >= LOG_LEVEL.load(Ordering::Relaxed)
+}
+
+/// The log of a plugin, written to `stdpath('log')/wasm/<plugin>.log`.
+///
+/// Plugins without a name usable as a file name, e.g. those loaded from memory without a name, have
+/// no log file.
+pub(crate) struct PluginLog {
+    name: String,
+    path: Option<PathBuf>,
+    /// The log file, opened by the first entry.
+    file: Option<File>,
+}
+
+impl PluginLog {
+    /// Creates the log of the plugin `name`, which is shown as `display_name` by `vim.notify()`.
+    pub(crate) fn new(name: Option<&str>, display_name: &str) -> Self {
+        Self {
+            name: display_name.to_string(),
+            path: name
+                .and_then(paths::plugin_file_name)
+                .map(|file_name| paths::state_path(c"wasm").join(format!("{file_name}.log"))),
+            file: None,
+        }
+    }
+
+    fn write(&mut self, entry: &str) -> std::io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if self.file.is_none() {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            self.file = Some(OpenOptions::new().create(true).append(true).open(path)?);
+        }
+        match &mut self.file {
+            Some(file) => file.write_all(entry.as_bytes()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl log::Host for NvimHost {
+    fn log(&mut self, level: Level, message: String, fields: Dictionary) -> wasmtime::Result<()> {
+        if !is_enabled(level) {
+            return Ok(());
+        }
+
+        let mut time = [0 as c_char; 32];
+        unsafe { nvim_sys::wasm_log_time(time.as_mut_ptr(), time.len()) };
+        let time = unsafe { CStr::from_ptr(time.as_ptr()) }.to_string_lossy();
+        let mut entry = format!("{} {time} {message}", level_name(level));
+        for (key, value) in &fields {
+            let _ = write!(entry, " {key}={}", format_primitive(value));
+        }
+        entry.push('\n');
+        // A plugin is not stopped because its log cannot be written.
+        let _ = self.log.write(&entry);
+
+        if NOTIFY.load(Ordering::Relaxed) && level_value(level) >= WARN {
+            let message = NvimString::new(format!("{}: {message}", self.log.name));
+            let opts = NvimDictionary::new();
+            let mut error = NvimResult::new_ok();
+            let result = unsafe {
+                nvim_sys::nvim_notify(
+                    message.as_borrowed_ffi(),
+                    level_value(level),
+                    opts.as_borrowed_ffi(),
+                    error.as_ffi_mut(),
+                )
+            };
+            drop(unsafe { NvimObject::from_ffi(result) });
+            // Like the log file, a failing `vim.notify()` does not stop the plugin.
+            let _ = error.into_result();
+        }
+        Ok(())
+    }
+
+    fn enabled(&mut self, level: Level) -> wasmtime::Result<bool> {
+        Ok(is_enabled(level))
+    }
+}
+
+/// Formats a field value of a log entry, e.g. `"text"` or `42`.
+fn format_primitive(value: &Primitive) -> String {
+    match value {
+        Primitive::Nil => "nil".to_string(),
+        Primitive::Boolean(b) => b.to_string(),
+        Primitive::Integer(i) => i.to_string(),
+        Primitive::Float(f) => f.to_string(),
+        Primitive::String(s) => format!("{s:?}"),
+        Primitive::Buffer(handle) => format!("buffer:{handle}"),
+        Primitive::Window(handle) => format!("window:{handle}"),
+        Primitive::Tabpage(handle) => format!("tabpage:{handle}"),
+    }
+}
@@ -0,0 +1,43 @@
+use std::{ffi::CStr, path::PathBuf};
+
+/// Returns `$XDG_STATE_HOME/nvim/{subpath}`, i.e. a path in `stdpath('state')`, which is also
+/// `stdpath('log')`.
+pub(crate) fn state_path(subpath: &CStr) -> PathBuf {
+    let path = unsafe { nvim_sys::stdpaths_user_state_subpath(subpath.as_ptr(), 0, false) };
+    let path_buf = PathBuf::from(
+        unsafe { CStr::from_ptr(path) }
+            .to_str()
+            .expect("State directory is not a valid utf-8 string"),
+    );
+    unsafe { nvim_sys::xfree(path as *mut _) };
+    path_buf
+}
+
+/// Returns `$XDG_DATA_HOME/nvim/{subpath}`, i.e. a path in `stdpath('data')`.
+pub(crate) fn data_path(subpath: &CStr) -> PathBuf {
+    let path = unsafe { nvim_sys::stdpaths_user_data_subpath(subpath.as_ptr()) };
+    let path_buf = PathBuf::from(
+        unsafe { CStr::from_ptr(path) }
+            .to_str()
+            .expect("Data directory is not a valid utf-8 string"),
+    );
+    unsafe { nvim_sys::xfree(path as *mut _) };
+    path_buf
+}
+
+/// Returns the plugin name `name` as a file name, e.g. of the log file of the plugin, or `None`
+/// if nothing is left of it, e.g. for `..`.
+///
+/// The name may come from the plugin, it must not escape the directory of the file.
+pub(crate) fn plugin_file_name(name: &str) -> Option<String> {
+    let file_name = name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => c,
+            _ => '_',
+        })
+        .collect::<String>()
+        .trim_start_matches('.')
+        .to_string();
+    (!file_name.is_empty()).then_some(file_name)
+}
@@ -0,0 +1,67 @@
+use nvim_rs::{IntoObject, NvimArray, NvimDictionary, NvimResult, NvimString};
+
+use crate::{nvim::api::process, NvimHost};
+
+/// The processes are jobs of Nvim whose events call the hooks of the instance, see
+/// `src/nvim/wasm/process.c`. The C side knows the calling instance, which owns the process.
+///
+/// Only instances granted the `process` capability import this interface, see `linking.rs`.
+impl process::Host for NvimHost {
+    fn spawn(&mut self, options: process::SpawnOptions) -> wasmtime::Result<Result<i64, String>> {
+        let argv: NvimArray = options
+            .argv
+            .into_iter()
+            .map(|arg| NvimString::from_string(arg).into_object())
+            .collect();
+        let cwd = NvimString::from_string(options.cwd.unwrap_or_default());
+        let env = NvimDictionary::from_vec(
+            options
+                .env
+                .into_iter()
+                .map(|(name, value)| {
+                    (
+                        NvimString::from_string(name),
+                        NvimString::from_string(value).into_object(),
+                    )
+                })
+                .collect(),
+        );
+        let mut error = NvimResult::new_ok();
+        let process = unsafe {
+            nvim_sys::wasm_process_spawn(
+                argv.as_borrowed_ffi(),
+                cwd.as_borrowed_ffi(),
+                env.as_borrowed_ffi(),
+                options.stdin,
+                options.stdout,
+                options.stderr,
+                error.as_ffi_mut(),
+            )
+        };
+        Ok(error
+            .into_result()
+            .map(|()| process)
+            .map_err(|err| err.to_string()))
+    }
+
+    fn write(&mut self, process: i64, data: Vec<u8>) -> wasmtime::Result<Result<(), String>> {
+        let data = NvimString::new(data);
+        let mut error = NvimResult::new_ok();
+        unsafe {
+            nvim_sys::wasm_process_write(process, data.as_borrowed_ffi(), error.as_ffi_mut())
+        };
+        Ok(error.into_result().map_err(|err| err.to_string()))
+    }
+
+    fn close_stdin(&mut self, process: i64) -> wasmtime::Result<Result<(), String>> {
+        let mut error = NvimResult::new_ok();
+        unsafe { nvim_sys::wasm_process_close_stdin(process, error.as_ffi_mut()) };
+        Ok(error.into_result().map_err(|err| err.to_string()))
+    }
+
+    fn kill(&mut self, process: i64) -> wasmtime::Result<Result<(), String>> {
+        let mut error = NvimResult::new_ok();
+        unsafe { nvim_sys::wasm_process_kill(process, error.as_ffi_mut()) };
+        Ok(error.into_result().map_err(|err| err.to_string()))
+    }
+}
@@ -0,0 +1,199 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use nvim_rs::types::{IntoObject, NvimArray, NvimObject, NvimString};
+use wasmtime::{Config, ProfilingStrategy, Store};
+
+use crate::{lock_instances, runtime_info::dictionary, NvimHost, MUTEX_POISONED_ERR};
+
+/// Whether calls to and from instances are recorded, see `nvim_wasm_profile`.
+static PROFILING: AtomicBool = AtomicBool::new(false);
+
+/// Whether instances consume fuel, set once when the engine is created, see `configure`.
+static METERS_FUEL: AtomicBool = AtomicBool::new(false);
+
+/// The environment variable configuring the profiling of plugins, see `configure`.
+const PROFILER_ENV: &str = "NVIM_WASM_PROFILER";
+
+/// The calls to a function.
+#[derive(Default)]
+struct CallStats {
+    count: u64,
+    time: Duration,
+}
+
+impl CallStats {
+    fn record(&mut self, time: Duration) {
+        self.count += 1;
+        self.time += time;
+    }
+
+    fn to_object(&self) -> NvimObject {
+        dictionary(vec![
+            ("count", (self.count as i64).into_object()),
+            ("time", (self.time.as_nanos() as i64).into_object()),
+        ])
+        .into_object()
+    }
+}
+
+/// What is recorded about an instance while profiling.
+#[derive(Default)]
+pub(crate) struct InstanceProfile {
+    /// The calls to the functions exported by the instance, by name. The time of a call includes
+    /// the API calls it makes.
+    exports: HashMap<String, CallStats>,
+    /// The calls of the instance to API functions, by name.
+    api_calls: HashMap<&'static str, CallStats>,
+    /// The fuel consumed by the calls to the instance, i.e. roughly the number of WASM
+    /// instructions they ran, when fuel is metered.
+    fuel: u64,
+}
+
+/// The profile of an instance, shared by its store and the table of instances so that it can be
+/// read while the instance runs.
+pub(crate) type SharedProfile = Arc<Mutex<InstanceProfile>>;
+
+/// Configures the profiling of the engine from `$NVIM_WASM_PROFILER`, a comma-separated list of:
+/// - `perfmap` or `jitdump`: the profiling strategy of Wasmtime, so that profilers like `perf` can
+///   symbolize the JIT-compiled code of plugins.
+/// - `fuel`: instances consume fuel, which is recorded while profiling. Metering fuel slows down
+///   every plugin, so it is off by default.
+pub(crate) fn configure(config: &mut Config) {
+    let value = std::env::var(PROFILER_ENV).unwrap_or_default();
+    for option in value.split(',') {
+        match option {
+            "perfmap" => {
+                config.profiler(ProfilingStrategy::PerfMap);
+            }
+            "jitdump" => {
+                config.profiler(ProfilingStrategy::JitDump);
+            }
+            "fuel" => {
+                config.consume_fuel(true);
+                METERS_FUEL.store(true, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Whether instances consume fuel, see `configure`. Their store is then given all the fuel it can
+/// hold, so that they never run out of it.
+pub(crate) fn meters_fuel() -> bool {
+    METERS_FUEL.load(Ordering::Relaxed)
+}
+
+/// Clears the profiles of the instances and starts recording.
+pub(crate) fn start() {
+    for (_, loaded) in lock_instances().iter() {
+        *loaded.info.profile.lock().expect(MUTEX_POISONED_ERR) = InstanceProfile::default();
+    }
+    PROFILING.store(true, Ordering::Relaxed);
+}
+
+/// Stops recording. The profiles are kept until profiling starts again.
+pub(crate) fn stop() {
+    PROFILING.store(false, Ordering::Relaxed);
+}
+
+fn is_profiling() -> bool {
+    PROFILING.load(Ordering::Relaxed)
+}
+
+/// Runs `call`, a call to the function `func_name` exported by the instance of `store`, and
+/// records its time and the fuel it consumed when profiling.
+pub(crate) fn profile_export_call<T>(
+    store: &mut Store<NvimHost>,
+    func_name: impl Display,
+    call: impl FnOnce(&mut Store<NvimHost>) -> T,
+) -> T {
+    if !is_profiling() {
+        return call(store);
+    }
+
+    // Getting the fuel fails when it is not metered.
+    let fuel = store.get_fuel().ok();
+    let start = Instant::now();
+    let result = call(store);
+    let time = start.elapsed();
+    let fuel = fuel
+        .zip(store.get_fuel().ok())
+        .map_or(0, |(before, after)| before.saturating_sub(after));
+
+    let mut profile = store.data().profile.lock().expect(MUTEX_POISONED_ERR);
+    profile
+        .exports
+        .entry(func_name.to_string())
+        .or_default()
+        .record(time);
+    profile.fuel += fuel;
+    result
+}
+
+/// Records a call of an instance to an API function when dropped. The generated implementations
+/// of the API functions start one, see `build.rs`.
+pub(crate) struct ApiCallTimer {
+    call: Option<(SharedProfile, &'static str, Instant)>,
+}
+
+impl ApiCallTimer {
+    pub(crate) fn start(profile: &SharedProfile, func_name: &'static str) -> Self {
+        Self {
+            call: is_profiling().then(|| (profile.clone(), func_name, Instant::now())),
+        }
+    }
+}
+
+impl Drop for ApiCallTimer {
+    fn drop(&mut self) {
+        if let Some((profile, func_name, start)) = self.call.take() {
+            let time = start.elapsed();
+            profile
+                .lock()
+                .expect(MUTEX_POISONED_ERR)
+                .api_calls
+                .entry(func_name)
+                .or_default()
+                .record(time);
+        }
+    }
+}
+
+/// Returns the profiles of the loaded instances, see `nvim_wasm_profile`.
+pub(crate) fn profiles() -> NvimArray {
+    lock_instances()
+        .iter()
+        .map(|(id, loaded)| {
+            let profile = loaded.info.profile.lock().expect(MUTEX_POISONED_ERR);
+            let exports = profile
+                .exports
+                .iter()
+                .map(|(name, stats)| (name.as_str(), stats.to_object()));
+            let api_calls = profile
+                .api_calls
+                .iter()
+                .map(|(name, stats)| (*name, stats.to_object()));
+            let mut entries = vec![
+                ("id", (id as i64).into_object()),
+                (
+                    "source",
+                    NvimString::new(loaded.info.source.as_str()).into_object(),
+                ),
+                ("exports", dictionary(exports.collect()).into_object()),
+                ("api_calls", dictionary(api_calls.collect()).into_object()),
+            ];
+            if meters_fuel() {
+                entries.push(("fuel", (profile.fuel as i64).into_object()));
+            }
+            dictionary(entries).into_object()
+        })
+        .collect()
+}
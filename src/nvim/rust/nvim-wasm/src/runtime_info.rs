@@ -0,0 +1,61 @@
+use std::sync::atomic::Ordering;
+
+use nvim_rs::types::{IntoObject, NvimArray, NvimDictionary, NvimObject, NvimString};
+
+use crate::{lock_instances, NVIM_API_LEVEL, NVIM_API_PACKAGE_VERSION};
+
+/// Returns information about the WASM runtime and the loaded instances, see
+/// `nvim_wasm_get_runtime_info`.
+pub(crate) fn runtime_info() -> NvimDictionary {
+    let proposals = nvim_wasm_compile::WASM_PROPOSALS
+        .iter()
+        .map(|(name, _)| NvimString::new(*name).into_object())
+        .collect::<NvimArray>();
+    let instances = lock_instances()
+        .iter()
+        .map(|(id, loaded)| {
+            let info = &loaded.info;
+            let memory_size = info.memory_size.load(Ordering::Relaxed) as i64;
+            let mut instance = vec![
+                ("id", (id as i64).into_object()),
+                (
+                    "source",
+                    NvimString::new(info.source.as_str()).into_object(),
+                ),
+                ("memory", memory_size.into_object()),
+            ];
+            if let Some(api_version) = info.api_version {
+                instance.push(("api_version", version_object(api_version)));
+            }
+            dictionary(instance).into_object()
+        })
+        .collect::<NvimArray>();
+    // Compiled components are not cached yet, see `wasm_load_impl`.
+    let cache = dictionary(vec![("enabled", false.into_object())]);
+
+    dictionary(vec![
+        (
+            "wasmtime_version",
+            NvimString::new(wasmtime_environ::VERSION).into_object(),
+        ),
+        ("proposals", proposals.into_object()),
+        ("api_version", version_object(NVIM_API_PACKAGE_VERSION)),
+        ("api_level", i64::from(NVIM_API_LEVEL).into_object()),
+        ("cache", cache.into_object()),
+        ("instances", instances.into_object()),
+    ])
+}
+
+/// Returns the package version `version` as a string, e.g. "1.12.0".
+fn version_object((major, minor, patch): (u32, u32, u32)) -> NvimObject {
+    NvimString::new(format!("{major}.{minor}.{patch}")).into_object()
+}
+
+pub(crate) fn dictionary(entries: Vec<(&str, NvimObject)>) -> NvimDictionary {
+    NvimDictionary::from_vec(
+        entries
+            .into_iter()
+            .map(|(key, value)| (NvimString::new(key), value))
+            .collect(),
+    )
+}
@@ -0,0 +1,400 @@
+use std::{
+    fs::{self, File},
+    io::{self, ErrorKind, Write as _},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use rmp::{decode, encode, Marker};
+
+use crate::{
+    nvim::api::storage::{self, Value},
+    nvim_types::{Object, Primitive},
+    paths, NvimHost,
+};
+
+/// The maximum size of the values of a plugin, in bytes.
+const QUOTA: u64 = 16 * 1024 * 1024;
+
+/// The maximum length of a key, in bytes. Keys are hex-encoded in file names, which most file
+/// systems limit to 255 bytes.
+const MAX_KEY_LEN: usize = 100;
+
+/// The first byte of a value file, telling the variant of the value.
+const BYTES_TAG: u8 = b'b';
+const OBJECT_TAG: u8 = b'o';
+
+/// The file of a store holding its owner, which is not a key: keys are hex-encoded.
+const OWNER_FILE: &str = ".owner";
+
+/// The MessagePack extension types of handles, as in the RPC API, see `:help rpc-types`.
+const BUFFER_EXT: i8 = 0;
+const WINDOW_EXT: i8 = 1;
+const TABPAGE_EXT: i8 = 2;
+
+/// The store of a plugin, in `stdpath('data')/wasm/<plugin>/`.
+///
+/// Plugins are told apart by name, and a store is bound to the plugin which first writes to it:
+/// its owner, e.g. the path of the plugin file, is recorded in the store and other plugins with
+/// the same name cannot use the store. Plugins without a name usable as a directory name, e.g.
+/// those loaded from memory without a name, have no store.
+///
+/// Each value is a file named after the hex-encoded key, so that any key is a valid file name and
+/// keys differing only by case do not collide on case-insensitive file systems. Bytes are stored
+/// as is and objects as MessagePack, after a tag byte.
+pub(crate) struct PluginStorage {
+    dir: Option<PathBuf>,
+    /// Where the plugin comes from, see `wasm_load_impl`.
+    owner: String,
+}
+
+impl PluginStorage {
+    pub(crate) fn new(name: Option<&str>, owner: &str) -> Self {
+        Self {
+            dir: name
+                .and_then(paths::plugin_file_name)
+                .map(|file_name| paths::data_path(c"wasm").join(file_name)),
+            owner: owner.to_string(),
+        }
+    }
+
+    /// Returns the directory of the store, unless the store belongs to another plugin.
+    fn dir(&self) -> Result<&Path> {
+        let Some(dir) = &self.dir else {
+            bail!(
+                "The plugin has no store: it has no name, or its name is not a valid directory \
+                 name, see `nvim-plugin-name`"
+            );
+        };
+        match fs::read_to_string(dir.join(OWNER_FILE)) {
+            Ok(owner) if owner != self.owner => bail!(
+                "The store {} belongs to the plugin loaded from {owner}",
+                dir.display()
+            ),
+            Ok(_) => Ok(dir),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(dir),
+            Err(err) => Err(err).context("Failed to read the owner of the store"),
+        }
+    }
+
+    /// Records the plugin as the owner of the store in `dir`, unless the store has an owner.
+    fn claim(&self, dir: &Path) -> io::Result<()> {
+        let owner_file = File::options()
+            .write(true)
+            .create_new(true)
+            .open(dir.join(OWNER_FILE));
+        match owner_file {
+            Ok(mut file) => file.write_all(self.owner.as_bytes()),
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn key_path(&self, key: &str) -> Result<PathBuf> {
+        let dir = self.dir()?;
+        if key.is_empty() {
+            bail!("Key is empty");
+        }
+        if key.len() > MAX_KEY_LEN {
+            bail!("Key is longer than {MAX_KEY_LEN} bytes");
+        }
+        let file_name: String = key.bytes().map(|b| format!("{b:02x}")).collect();
+        Ok(dir.join(file_name))
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        let path = self.key_path(key)?;
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).with_context(|| format!("Failed to read the key `{key}`")),
+        };
+        decode_value(&bytes).map(Some).with_context(|| {
+            format!(
+                "The value of the key `{key}` is corrupted: {}",
+                path.display()
+            )
+        })
+    }
+
+    fn set(&self, key: &str, value: &Value) -> Result<()> {
+        let path = self.key_path(key)?;
+        let bytes = encode_value(value)?;
+        let usage = self.usage()?.saturating_sub(file_size(&path));
+        if usage + bytes.len() as u64 > QUOTA {
+            bail!(
+                "Cannot set the key `{key}`: the store would exceed its quota of {} MiB",
+                QUOTA >> 20
+            );
+        }
+
+        // The value is written to a temporary file renamed over the old value, so that a value is
+        // never partially written, even if Nvim crashes. The PID tells apart the temporary files
+        // of several Nvim instances.
+        let tmp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+        let dir = self.dir()?;
+        let result = fs::create_dir_all(dir)
+            .and_then(|()| self.claim(dir))
+            .and_then(|()| write_file(&tmp_path, &bytes))
+            .and_then(|()| fs::rename(&tmp_path, &path));
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        result.with_context(|| format!("Failed to write the key `{key}`"))
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        let path = self.key_path(key)?;
+        match fs::remove_file(path) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err).with_context(|| format!("Failed to delete the key `{key}`")),
+        }
+    }
+
+    fn list(&self) -> Result<Vec<String>> {
+        let mut keys: Vec<String> = self.entries()?.into_iter().map(|(key, _)| key).collect();
+        keys.sort();
+        Ok(keys)
+    }
+
+    /// Returns the total size of the values, checked against `QUOTA`.
+    fn usage(&self) -> Result<u64> {
+        Ok(self.entries()?.into_iter().map(|(_, size)| size).sum())
+    }
+
+    /// Returns the keys of the store and the sizes of their values. Other files, e.g. temporary
+    /// files left by a crash, are ignored.
+    fn entries(&self) -> Result<Vec<(String, u64)>> {
+        let read_dir = match fs::read_dir(self.dir()?) {
+            Ok(read_dir) => read_dir,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err).context("Failed to read the store"),
+        };
+        let mut entries = Vec::new();
+        for entry in read_dir {
+            let entry = entry.context("Failed to read the store")?;
+            let Some(key) = entry.file_name().to_str().and_then(decode_key) else {
+                continue;
+            };
+            let metadata = entry.metadata().context("Failed to read the store")?;
+            if metadata.is_file() {
+                entries.push((key, metadata.len()));
+            }
+        }
+        Ok(entries)
+    }
+}
+
+impl storage::Host for NvimHost {
+    fn get(&mut self, key: String) -> wasmtime::Result<Result<Option<Value>, String>> {
+        Ok(self.storage.get(&key).map_err(|err| format!("{err:#}")))
+    }
+
+    fn set(&mut self, key: String, value: Value) -> wasmtime::Result<Result<(), String>> {
+        Ok(self
+            .storage
+            .set(&key, &value)
+            .map_err(|err| format!("{err:#}")))
+    }
+
+    fn delete(&mut self, key: String) -> wasmtime::Result<Result<bool, String>> {
+        Ok(self.storage.delete(&key).map_err(|err| format!("{err:#}")))
+    }
+
+    fn list(&mut self) -> wasmtime::Result<Result<Vec<String>, String>> {
+        Ok(self.storage.list().map_err(|err| format!("{err:#}")))
+    }
+}
+
+/// Decodes a file name of the store into its key, e.g. `6b6579` into `key`.
+fn decode_key(file_name: &str) -> Option<String> {
+    if file_name.is_empty() {
+        return None;
+    }
+    let bytes = file_name
+        .as_bytes()
+        .chunks(2)
+        .map(|digits| {
+            // Uppercase digits are not produced by `key_path`, they would be another key.
+            if digits.len() != 2 || digits.iter().any(u8::is_ascii_uppercase) {
+                return None;
+            }
+            u8::from_str_radix(std::str::from_utf8(digits).ok()?, 16).ok()
+        })
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map_or(0, |metadata| metadata.len())
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+fn encode_value(value: &Value) -> io::Result<Vec<u8>> {
+    match value {
+        Value::Bytes(bytes) => {
+            let mut buf = Vec::with_capacity(bytes.len() + 1);
+            buf.push(BYTES_TAG);
+            buf.extend_from_slice(bytes);
+            Ok(buf)
+        }
+        Value::Object(object) => {
+            let mut buf = vec![OBJECT_TAG];
+            encode_object(&mut buf, object)?;
+            Ok(buf)
+        }
+    }
+}
+
+fn decode_value(bytes: &[u8]) -> Option<Value> {
+    match bytes.split_first()? {
+        (&BYTES_TAG, bytes) => Some(Value::Bytes(bytes.to_vec())),
+        (&OBJECT_TAG, mut bytes) => {
+            let object = decode_object(&mut bytes)?;
+            bytes.is_empty().then_some(Value::Object(object))
+        }
+        _ => None,
+    }
+}
+
+fn encode_object(buf: &mut Vec<u8>, object: &Object) -> io::Result<()> {
+    match object {
+        Object::Nil => encode::write_nil(buf)?,
+        Object::Boolean(b) => encode::write_bool(buf, *b)?,
+        Object::Integer(i) => {
+            encode::write_sint(buf, *i)?;
+        }
+        Object::Float(f) => encode::write_f64(buf, *f)?,
+        Object::String(s) => encode::write_str(buf, s)?,
+        Object::Array(array) => {
+            encode::write_array_len(buf, len_u32(array.len())?)?;
+            for item in array {
+                encode_primitive(buf, item)?;
+            }
+        }
+        Object::Dictionary(dictionary) => {
+            encode::write_map_len(buf, len_u32(dictionary.len())?)?;
+            for (key, value) in dictionary {
+                encode::write_str(buf, key)?;
+                encode_primitive(buf, value)?;
+            }
+        }
+        Object::Buffer(handle) => encode_handle(buf, BUFFER_EXT, *handle)?,
+        Object::Window(handle) => encode_handle(buf, WINDOW_EXT, *handle)?,
+        Object::Tabpage(handle) => encode_handle(buf, TABPAGE_EXT, *handle)?,
+    }
+    Ok(())
+}
+
+fn encode_primitive(buf: &mut Vec<u8>, primitive: &Primitive) -> io::Result<()> {
+    match primitive {
+        Primitive::Nil => encode::write_nil(buf)?,
+        Primitive::Boolean(b) => encode::write_bool(buf, *b)?,
+        Primitive::Integer(i) => {
+            encode::write_sint(buf, *i)?;
+        }
+        Primitive::Float(f) => encode::write_f64(buf, *f)?,
+        Primitive::String(s) => encode::write_str(buf, s)?,
+        Primitive::Buffer(handle) => encode_handle(buf, BUFFER_EXT, *handle)?,
+        Primitive::Window(handle) => encode_handle(buf, WINDOW_EXT, *handle)?,
+        Primitive::Tabpage(handle) => encode_handle(buf, TABPAGE_EXT, *handle)?,
+    }
+    Ok(())
+}
+
+/// Encodes a handle as an extension type whose data is the handle, like the RPC API.
+fn encode_handle(buf: &mut Vec<u8>, ext: i8, handle: i64) -> io::Result<()> {
+    let mut data = Vec::new();
+    encode::write_sint(&mut data, handle)?;
+    encode::write_ext_meta(buf, len_u32(data.len())?, ext)?;
+    buf.extend_from_slice(&data);
+    Ok(())
+}
+
+fn len_u32(len: usize) -> io::Result<u32> {
+    u32::try_from(len).map_err(|_| io::Error::new(ErrorKind::InvalidInput, "Object is too large"))
+}
+
+fn decode_object(rd: &mut &[u8]) -> Option<Object> {
+    match Marker::from_u8(*rd.first()?) {
+        Marker::FixArray(_) | Marker::Array16 | Marker::Array32 => {
+            let len = decode::read_array_len(rd).ok()?;
+            let array = (0..len)
+                .map(|_| decode_primitive(rd))
+                .collect::<Option<_>>()?;
+            Some(Object::Array(array))
+        }
+        Marker::FixMap(_) | Marker::Map16 | Marker::Map32 => {
+            let len = decode::read_map_len(rd).ok()?;
+            let dictionary = (0..len)
+                .map(|_| Some((decode_string(rd)?, decode_primitive(rd)?)))
+                .collect::<Option<_>>()?;
+            Some(Object::Dictionary(dictionary))
+        }
+        _ => Some(match decode_primitive(rd)? {
+            Primitive::Nil => Object::Nil,
+            Primitive::Boolean(b) => Object::Boolean(b),
+            Primitive::Integer(i) => Object::Integer(i),
+            Primitive::Float(f) => Object::Float(f),
+            Primitive::String(s) => Object::String(s),
+            Primitive::Buffer(handle) => Object::Buffer(handle),
+            Primitive::Window(handle) => Object::Window(handle),
+            Primitive::Tabpage(handle) => Object::Tabpage(handle),
+        }),
+    }
+}
+
+fn decode_primitive(rd: &mut &[u8]) -> Option<Primitive> {
+    Some(match Marker::from_u8(*rd.first()?) {
+        Marker::Null => {
+            decode::read_nil(rd).ok()?;
+            Primitive::Nil
+        }
+        Marker::True | Marker::False => Primitive::Boolean(decode::read_bool(rd).ok()?),
+        Marker::FixPos(_)
+        | Marker::FixNeg(_)
+        | Marker::U8
+        | Marker::U16
+        | Marker::U32
+        | Marker::U64
+        | Marker::I8
+        | Marker::I16
+        | Marker::I32
+        | Marker::I64 => Primitive::Integer(decode::read_int(rd).ok()?),
+        Marker::F64 => Primitive::Float(decode::read_f64(rd).ok()?),
+        Marker::FixStr(_) | Marker::Str8 | Marker::Str16 | Marker::Str32 => {
+            Primitive::String(decode_string(rd)?)
+        }
+        Marker::FixExt1
+        | Marker::FixExt2
+        | Marker::FixExt4
+        | Marker::FixExt8
+        | Marker::FixExt16
+        | Marker::Ext8 => {
+            let meta = decode::read_ext_meta(rd).ok()?;
+            let mut data = rd.get(..meta.size as usize)?;
+            *rd = &rd[meta.size as usize..];
+            let handle = decode::read_int(&mut data).ok()?;
+            match meta.typeid {
+                BUFFER_EXT => Primitive::Buffer(handle),
+                WINDOW_EXT => Primitive::Window(handle),
+                TABPAGE_EXT => Primitive::Tabpage(handle),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    })
+}
+
+fn decode_string(rd: &mut &[u8]) -> Option<String> {
+    let len = decode::read_str_len(rd).ok()? as usize;
+    let bytes = rd.get(..len)?;
+    *rd = &rd[len..];
+    String::from_utf8(bytes.to_vec()).ok()
+}
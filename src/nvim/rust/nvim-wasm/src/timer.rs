@@ -0,0 +1,14 @@
+use crate::{nvim::api::timer, NvimHost};
+
+/// The timers run on the main loop of Nvim and call the `on-timer` hook of the instance, see
+/// `src/nvim/wasm/timer.c`. The C side knows the calling instance, which owns the timer.
+impl timer::Host for NvimHost {
+    fn start(&mut self, ms: u64, repeat: i32, callback_id: i64) -> wasmtime::Result<i64> {
+        Ok(unsafe { nvim_sys::wasm_timer_start(ms, repeat, callback_id) })
+    }
+
+    fn stop(&mut self, id: i64) -> wasmtime::Result<()> {
+        unsafe { nvim_sys::wasm_timer_stop(id) };
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    ffi::{CStr, CString},
+    fs,
+    io::ErrorKind,
+    path::PathBuf,
+};
+
+use anyhow::{bail, Context, Result};
+
+use crate::paths;
+
+/// The trust given to a component by the user.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Trust {
+    Allow,
+    Deny,
+}
+
+impl Trust {
+    fn as_str(self) -> &'static str {
+        match self {
+            Trust::Allow => "allow",
+            Trust::Deny => "deny",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "allow" => Some(Trust::Allow),
+            "deny" => Some(Trust::Deny),
+            _ => None,
+        }
+    }
+}
+
+/// The capabilities which can be granted to trusted components, see `linking.rs`.
+pub(crate) const CAPABILITIES: &[&str] = &["process"];
+
+struct TrustEntry {
+    hash: String,
+    trust: Trust,
+    /// The capabilities granted to the component, only if it is allowed.
+    capabilities: Vec<String>,
+    source: String,
+}
+
+/// The trust database of WASM plugins, stored in `$XDG_STATE_HOME/nvim/wasm_trust`.
+///
+/// Components are identified by the SHA-256 of their bytes, so that a plugin is not trusted
+/// anymore when it changes. Each line is an entry `<sha256> <allow|deny> <source>`, where the
+/// source, i.e. the file or the name of the plugin, tells which plugin the entry is about. The
+/// capabilities granted to an allowed component follow `allow`, e.g. `allow,process`.
+pub(crate) struct TrustDb {
+    path: PathBuf,
+    entries: Vec<TrustEntry>,
+}
+
+impl TrustDb {
+    pub(crate) fn read() -> Result<Self> {
+        let path = paths::state_path(c"wasm_trust");
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => String::new(),
+            Err(err) => {
+                return Err(err).with_context(|| {
+                    format!("Failed to read the WASM trust database {}", path.display())
+                })
+            }
+        };
+        let entries = contents
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, ' ');
+                let hash = fields.next()?.to_string();
+                let mut trust = fields.next()?.split(',');
+                Some(TrustEntry {
+                    hash,
+                    trust: Trust::parse(trust.next()?)?,
+                    capabilities: trust.map(str::to_string).collect(),
+                    source: fields.next()?.to_string(),
+                })
+            })
+            .collect();
+
+        Ok(Self { path, entries })
+    }
+
+    fn write(&self) -> Result<()> {
+        let contents = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let trust = std::iter::once(entry.trust.as_str())
+                    .chain(entry.capabilities.iter().map(String::as_str))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                format!("{} {trust} {}\n", entry.hash, entry.source)
+            })
+            .collect::<String>();
+        self.path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&self.path, contents))
+            .with_context(|| {
+                format!(
+                    "Failed to write the WASM trust database {}",
+                    self.path.display()
+                )
+            })
+    }
+
+    /// Returns the entry of the component whose SHA-256 is `hash`.
+    fn get(&self, hash: &str) -> Option<&TrustEntry> {
+        self.entries.iter().find(|entry| entry.hash == hash)
+    }
+
+    /// Returns true if another version of the component `hash` loaded from `source` is trusted.
+    fn is_changed(&self, hash: &str, source: &str) -> bool {
+        self.entries.iter().any(|entry| {
+            entry.source == source && entry.hash != hash && entry.trust == Trust::Allow
+        })
+    }
+
+    /// Sets the trust of the component `hash` loaded from `source`, and the capabilities granted
+    /// to it if it is allowed, replacing the entries of the other versions of `source`.
+    pub(crate) fn set(
+        &mut self,
+        hash: String,
+        trust: Trust,
+        capabilities: Vec<String>,
+        source: String,
+    ) -> Result<()> {
+        if let Some(capability) = capabilities
+            .iter()
+            .find(|capability| !CAPABILITIES.contains(&capability.as_str()))
+        {
+            bail!("Invalid capability: {capability}");
+        }
+        if trust == Trust::Deny && !capabilities.is_empty() {
+            bail!("Capabilities can only be granted to allowed plugins");
+        }
+        self.entries
+            .retain(|entry| entry.hash != hash && entry.source != source);
+        self.entries.push(TrustEntry {
+            hash,
+            trust,
+            capabilities,
+            source,
+        });
+        self.write()
+    }
+
+    /// Removes the entries of the component `hash`, if any, and of all the versions of `source`.
+    pub(crate) fn remove(&mut self, hash: Option<&str>, source: &str) -> Result<()> {
+        self.entries
+            .retain(|entry| Some(entry.hash.as_str()) != hash && entry.source != source);
+        self.write()
+    }
+}
+
+/// Returns the SHA-256 of `bytes`, as `sha256()` in Vimscript.
+pub(crate) fn sha256(bytes: &[u8]) -> String {
+    let hash = unsafe { nvim_sys::sha256_bytes(bytes.as_ptr(), bytes.len(), std::ptr::null(), 0) };
+    unsafe { CStr::from_ptr(hash) }
+        .to_str()
+        .expect("SHA-256 is not a valid utf-8 string")
+        .to_string()
+}
+
+/// Returns the source of the file `file_path` in the trust database, i.e. its full path.
+pub(crate) fn file_source(file_path: &str) -> String {
+    fs::canonicalize(file_path)
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| file_path.to_string())
+}
+
+/// What a component which may run is granted, see `check_trust`.
+pub(crate) struct Grant {
+    /// True if the component is allowed in the trust database, and not only loaded by the user.
+    pub(crate) allowed: bool,
+    /// The capabilities granted to the component.
+    pub(crate) capabilities: Vec<String>,
+}
+
+/// Checks that the component `bytes` loaded from `source` may run, and returns what is granted
+/// to it.
+///
+/// Components denied in the trust database never run. The other ones run when the user loads them,
+/// e.g. with `:wasm`. When `user_initiated` is false, e.g. for a plugin found in 'runtimepath',
+/// the user is asked whether to trust the component unless it is already trusted. Only components
+/// allowed in the trust database are granted capabilities.
+pub(crate) fn check_trust(bytes: &[u8], source: &str, user_initiated: bool) -> Result<Grant> {
+    let hash = sha256(bytes);
+    let mut db = TrustDb::read()?;
+    match db.get(&hash) {
+        Some(entry) if entry.trust == Trust::Allow => {
+            return Ok(Grant {
+                allowed: true,
+                capabilities: entry.capabilities.clone(),
+            })
+        }
+        Some(_) => bail!("The plugin is denied in the WASM trust database"),
+        None if user_initiated => {
+            return Ok(Grant {
+                allowed: false,
+                capabilities: Vec::new(),
+            })
+        }
+        None => {}
+    }
+
+    let message = if db.is_changed(&hash, source) {
+        format!("{source} has changed since it was trusted.")
+    } else {
+        format!("{source} is not trusted.")
+    };
+    let message = CString::new(message)?;
+    match unsafe { nvim_sys::wasm_trust_prompt(message.as_ptr()) } {
+        2 => {
+            db.set(hash, Trust::Deny, Vec::new(), source.to_string())?;
+            bail!("The plugin is denied in the WASM trust database")
+        }
+        3 => db
+            .set(hash, Trust::Allow, Vec::new(), source.to_string())
+            .map(|()| Grant {
+                allowed: true,
+                capabilities: Vec::new(),
+            }),
+        _ => bail!("The plugin is not trusted, use :WasmTrust to trust it"),
+    }
+}
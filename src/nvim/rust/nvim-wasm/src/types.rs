@@ -32,6 +32,9 @@ pub(crate) enum TypeConversionError {
     NonPrimitiveType(NvimApiType),
     /// Indicates that the conversion failed because the object is converted to an incorrect type.
     ObjectConversionError(ObjectConversionError),
+    /// Indicates that the conversion failed because an array is converted to a tuple of a
+    /// different length.
+    LengthMismatch { expected: usize, actual: usize },
 }
 
 impl std::error::Error for TypeConversionError {}
@@ -55,6 +58,12 @@ impl Display for TypeConversionError {
             Self::ObjectConversionError(err) => {
                 write!(f, "{}", err)
             }
+            Self::LengthMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Expect an array of length {expected}, got an array of length {actual}"
+                )
+            }
         }
     }
 }
@@ -199,6 +208,15 @@ where
     }
 }
 
+impl<T> FromWasmType<Vec<T>> for NvimObject
+where
+    NvimObject: FromWasmType<T>,
+{
+    fn from_wasm_type(value: Vec<T>) -> Self {
+        NvimArray::from_wasm_type(value).into_object()
+    }
+}
+
 impl<T> TryIntoWasmType<Vec<(String, T)>> for NvimObject
 where
     NvimDictionary: TryIntoWasmType<Vec<(String, T)>>,
@@ -251,6 +269,65 @@ where
     }
 }
 
+macro_rules! impl_wasm_conversion_for_tuple {
+    ($len:literal; $(($var:ident, $ty:ident)),+) => {
+        impl<T> TryIntoWasmType<($($ty,)+)> for NvimArray
+        where
+            NvimObject: TryIntoWasmType<T>,
+        {
+            fn try_into_wasm_type(self) -> Result<($($ty,)+)> {
+                let vec = self.into_vec();
+                let actual = vec.len();
+                let Ok([$($var,)+]) = <[NvimObject; $len]>::try_from(vec) else {
+                    return Err(TypeConversionError::LengthMismatch { expected: $len, actual });
+                };
+                Ok(($($var.try_into_wasm_type()?,)+))
+            }
+        }
+
+        impl<T> TryIntoWasmType<($($ty,)+)> for &NvimArray
+        where
+            for<'a> &'a NvimObject: TryIntoWasmType<T>,
+        {
+            fn try_into_wasm_type(self) -> Result<($($ty,)+)> {
+                let [$($var,)+] = &self[..] else {
+                    return Err(TypeConversionError::LengthMismatch {
+                        expected: $len,
+                        actual: self.len(),
+                    });
+                };
+                Ok(($($var.try_into_wasm_type()?,)+))
+            }
+        }
+
+        impl<T> FromWasmType<($($ty,)+)> for NvimArray
+        where
+            NvimObject: FromWasmType<T>,
+        {
+            fn from_wasm_type(($($var,)+): ($($ty,)+)) -> Self {
+                NvimArray::from_vec(vec![$(NvimObject::from_wasm_type($var),)+])
+            }
+        }
+
+        // Only the conversion from WASM is implemented for `NvimObject`, which is needed by keyset
+        // fields. Implementing `TryIntoWasmType` for tuples would conflict with the dictionary
+        // conversions, since a dictionary entry is also a tuple.
+        impl<T> FromWasmType<($($ty,)+)> for NvimObject
+        where
+            NvimObject: FromWasmType<T>,
+        {
+            fn from_wasm_type(value: ($($ty,)+)) -> Self {
+                NvimArray::from_wasm_type(value).into_object()
+            }
+        }
+    };
+}
+
+// The lengths are `TUPLE_LENGTHS` of gen-api-func-lib, which skips the functions using others.
+impl_wasm_conversion_for_tuple!(2; (v0, T), (v1, T));
+impl_wasm_conversion_for_tuple!(3; (v0, T), (v1, T), (v2, T));
+impl_wasm_conversion_for_tuple!(4; (v0, T), (v1, T), (v2, T), (v3, T));
+
 impl<T> TryIntoWasmType<Vec<(String, T)>> for NvimDictionary
 where
     NvimObject: TryIntoWasmType<T>,
@@ -414,3 +491,67 @@ impl FromWasmType<nvim_types::Primitive> for NvimObject {
         })
     }
 }
+
+/// The `on_lines` event of a buffer, see `:help api-buffer-updates-lua`.
+#[repr(C)]
+pub struct WasmBufLinesEvent {
+    buffer: i64,
+    changedtick: i64,
+    has_changedtick: bool,
+    first_line: i64,
+    last_line: i64,
+    last_line_updated: i64,
+    byte_count: i64,
+    deleted_codepoints: i64,
+    deleted_codeunits: i64,
+    has_utf_sizes: bool,
+}
+
+impl From<&WasmBufLinesEvent> for nvim_types::BufLinesEvent {
+    fn from(event: &WasmBufLinesEvent) -> Self {
+        Self {
+            buffer: event.buffer,
+            changedtick: event.has_changedtick.then_some(event.changedtick),
+            first_line: event.first_line,
+            last_line: event.last_line,
+            last_line_updated: event.last_line_updated,
+            byte_count: event.byte_count,
+            deleted_codepoints: event.has_utf_sizes.then_some(event.deleted_codepoints),
+            deleted_codeunits: event.has_utf_sizes.then_some(event.deleted_codeunits),
+        }
+    }
+}
+
+/// The `on_bytes` event of a buffer, see `:help api-buffer-updates-lua`.
+#[repr(C)]
+pub struct WasmBufBytesEvent {
+    buffer: i64,
+    changedtick: i64,
+    start_row: i64,
+    start_col: i64,
+    start_byte: i64,
+    old_end_row: i64,
+    old_end_col: i64,
+    old_end_byte: i64,
+    new_end_row: i64,
+    new_end_col: i64,
+    new_end_byte: i64,
+}
+
+impl From<&WasmBufBytesEvent> for nvim_types::BufBytesEvent {
+    fn from(event: &WasmBufBytesEvent) -> Self {
+        Self {
+            buffer: event.buffer,
+            changedtick: event.changedtick,
+            start_row: event.start_row,
+            start_col: event.start_col,
+            start_byte: event.start_byte,
+            old_end_row: event.old_end_row,
+            old_end_col: event.old_end_col,
+            old_end_byte: event.old_end_byte,
+            new_end_row: event.new_end_row,
+            new_end_col: event.new_end_col,
+            new_end_byte: event.new_end_byte,
+        }
+    }
+}
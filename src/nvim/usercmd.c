@@ -1053,6 +1053,31 @@ void uc_clear(garray_T *gap)
   GA_DEEP_CLEAR(gap, ucmd_T, free_ucmd);
 }
 
+/// Delete the user commands in "gap" defined by channel "channel_id".
+static void uc_clear_ga_chan(garray_T *gap, uint64_t channel_id)
+{
+  int j = 0;
+  for (int i = 0; i < gap->ga_len; i++) {
+    ucmd_T *cmd = USER_CMD_GA(gap, i);
+    if (cmd->uc_script_ctx.sc_chan == channel_id) {
+      free_ucmd(cmd);
+    } else {
+      *USER_CMD_GA(gap, j++) = *cmd;
+    }
+  }
+  gap->ga_len = j;
+}
+
+/// Delete the global and buffer-local user commands defined by a channel,
+/// e.g. those of a WASM plugin which is unloaded.
+void uc_clear_chan(uint64_t channel_id)
+{
+  uc_clear_ga_chan(&ucmds, channel_id);
+  FOR_ALL_BUFFERS(buf) {
+    uc_clear_ga_chan(&buf->b_ucmds, channel_id);
+  }
+}
+
 void ex_delcommand(exarg_T *eap)
 {
   int i = 0;
@@ -0,0 +1,98 @@
+#include "nvim/wasm/buffer.h"
+
+#include <stddef.h>
+#include <string.h>
+
+#include "nvim/api/private/defs.h"
+#include "nvim/api/private/helpers.h"
+#include "nvim/api/private/validate.h"
+#include "nvim/ascii.h"
+#include "nvim/buffer_defs.h"
+#include "nvim/func_attr.h"
+#include "nvim/macros.h"
+#include "nvim/memline.h"
+#include "nvim/memory.h"
+#include "nvim/pos.h"
+
+/// The capacity of chunks when the sizes of the lines are not known.
+#define WASM_CHUNK_CAPACITY (1024 * 1024)
+
+#ifdef INCLUDE_GENERATED_DECLARATIONS
+# include "wasm/buffer.c.generated.h"
+#endif
+
+/// Returns the handle of `buffer`, or of the current buffer if `buffer` is 0.
+///
+/// @return The buffer handle, 0 on error.
+Buffer wasm_buf_handle(Buffer buffer, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  buf_T *buf = find_buffer_by_handle(buffer, err);
+  return buf ? buf->handle : 0;
+}
+
+/// Returns the capacity to allocate for the chunk of `buffer` starting at `start_line`, see
+/// wasm_buf_read_chunk(): `max_bytes`, or less when the rest of the buffer is smaller.
+///
+/// The chunk is at most `max_bytes` long, unless its first line alone is longer: it is returned
+/// whole so that readers always make progress. The chunk is empty after the last line, and for
+/// unloaded buffers.
+///
+/// @param buffer      Buffer handle
+/// @param start_line  First line of the chunk, 0-based
+/// @param max_bytes   Maximum size of the chunk
+/// @param[out] err    Error details, if any
+/// @return The capacity, 0 if the chunk is empty.
+size_t wasm_buf_chunk_capacity(Buffer buffer, Integer start_line, size_t max_bytes, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  buf_T *buf = find_buffer_by_handle(buffer, err);
+  if (!buf || buf->b_ml.ml_mfp == NULL) {
+    return 0;
+  }
+  VALIDATE_RANGE((start_line >= 0 && start_line <= buf->b_ml.ml_line_count), "start_line", {
+    return 0;
+  });
+  if (start_line == buf->b_ml.ml_line_count) {
+    return 0;
+  }
+
+  // The offsets come from the sizes of the memline chunks, without reading the lines.
+  long start = ml_find_line_or_offset(buf, (linenr_T)start_line + 1, NULL, true);
+  long end = ml_find_line_or_offset(buf, buf->b_ml.ml_line_count + 1, NULL, true);
+  // The last line break is not counted with 'noeol'.
+  size_t capacity = start < 0 || end < start ? WASM_CHUNK_CAPACITY : (size_t)(end - start) + 1;
+  // At least 1, so that the first line is read when longer than `max_bytes`.
+  return MAX(MIN(max_bytes, capacity), 1);
+}
+
+/// Copies the chunk of `buffer` starting at `start_line` to `dest`, for the `buffer-reader` of
+/// WASM plugins: the lines which fit in `capacity` bytes, each followed by a NL.
+///
+/// The lines are read once, straight from the memline. NUL bytes, stored as NL in the memline, are
+/// restored.
+///
+/// @param buffer      Buffer handle, validated by wasm_buf_chunk_capacity()
+/// @param start_line  First line of the chunk, 0-based
+/// @param dest        Destination of `capacity` bytes
+/// @param capacity    Capacity returned by wasm_buf_chunk_capacity()
+/// @return The size of the chunk. If the first line does not fit, nothing is copied and the size
+///         of the line is returned: call again with that capacity.
+size_t wasm_buf_read_chunk(Buffer buffer, Integer start_line, char *dest, size_t capacity)
+  FUNC_ATTR_NONNULL_ALL
+{
+  buf_T *buf = handle_get_buffer(buffer);
+  size_t size = 0;
+  for (linenr_T lnum = (linenr_T)start_line + 1; lnum <= buf->b_ml.ml_line_count; lnum++) {
+    const char *line = ml_get_buf(buf, lnum, false);
+    size_t len = strlen(line);
+    if (size + len + 1 > capacity) {
+      return size > 0 ? size : len + 1;
+    }
+    memcpy(dest + size, line, len);
+    memchrsub(dest + size, NL, NUL, len);
+    dest[size + len] = NL;
+    size += len + 1;
+  }
+  return size;
+}
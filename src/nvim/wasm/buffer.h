@@ -0,0 +1,12 @@
+#ifndef NVIM_WASM_BUFFER_H
+#define NVIM_WASM_BUFFER_H
+
+#include <stddef.h>
+
+#include "nvim/api/private/defs.h"
+
+#ifdef INCLUDE_GENERATED_DECLARATIONS
+# include "wasm/buffer.h.generated.h"
+#endif
+
+#endif  // NVIM_WASM_BUFFER_H
@@ -1,10 +1,79 @@
 #include "nvim/wasm/executor.h"
 
+#include <assert.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <string.h>
+#include <time.h>
+#include <uv.h>
+
+#include "klib/kvec.h"
 #include "nvim/api/private/defs.h"
+#include "nvim/api/private/helpers.h"
+#include "nvim/autocmd.h"
+#include "nvim/buffer.h"
+#include "nvim/buffer_defs.h"
+#include "nvim/buffer_updates.h"
+#include "nvim/cursor.h"
+#include "nvim/decoration_provider.h"
+#include "nvim/eval/window.h"
 #include "nvim/ex_cmds_defs.h"
+#include "nvim/ex_getln.h"
 #include "nvim/func_attr.h"
+#include "nvim/garray.h"
+#include "nvim/gettext.h"
+#include "nvim/globals.h"
+#include "nvim/mapping.h"
+#include "nvim/memory.h"
 #include "nvim/message.h"
+#include "nvim/option_defs.h"
+#include "nvim/os/os.h"
+#include "nvim/pos.h"
 #include "nvim/rust/nvim-wasm/include/wasm-rs.h"
+#include "nvim/runtime.h"
+#include "nvim/usercmd.h"
+#include "nvim/vim.h"
+#include "nvim/wasm/process.h"
+#include "nvim/wasm/timer.h"
+#include "nvim/window.h"
+
+/// Returned by wasm_load_file() when the plugin imports an interface that no loaded plugin exports.
+#define WASM_MISSING_PROVIDER (-2)
+
+/// A buffer or window context entered by a WASM instance.
+///
+/// WASM instances cannot be re-entered while they are running, so nvim_buf_call() and
+/// nvim_win_call() cannot call back into the plugin. Instead, the plugin enters the context, runs
+/// its code and restores the context, see nvim_wasm_buf_enter().
+typedef struct {
+  win_T *win;  ///< The entered window, or NULL if a buffer is entered.
+  aco_save_T aco;  ///< Saved state of a buffer context.
+  switchwin_T switchwin;  ///< Saved state of a window context.
+  pos_T curpos;  ///< Cursor of the entered window.
+  int cwd_status;  ///< Whether `cwd` was saved.
+  bool apply_acd;  ///< Apply 'autochdir' instead of restoring `cwd`.
+  char cwd[MAXPATHL];
+} WasmContext;
+
+#ifdef INCLUDE_GENERATED_DECLARATIONS
+# include "wasm/executor.c.generated.h"
+#endif
+
+/// Contexts entered by WASM instances, innermost last.
+static kvec_t(WasmContext *) wasm_contexts = KV_INITIAL_VALUE;
+
+/// The number of contexts entered before the running call, which belong to the callers and cannot
+/// be restored by the running instance.
+static size_t call_depth = 0;
+
+/// The instance whose export is running, or -1.
+static int32_t current_instance = -1;
+
+/// The files loaded by wasm_load() and the names of the plugins loaded by wasm_load_blob(),
+/// indexed by instance ID.
+static kvec_t(char *) instance_files = KV_INITIAL_VALUE;
 
 void wasm_init(void)
 {
@@ -13,17 +82,528 @@ void wasm_init(void)
 
 void ex_wasm(exarg_T *const eap) FUNC_ATTR_NONNULL_ALL
 {
-  char *file_path = eap->arg;
+  wasm_run_file(eap->arg, true);
+}
+
+/// Adds the files found by do_in_runtimepath() to the garray_T `cookie`.
+static bool wasm_collect_plugins(int num_fnames, char **fnames, bool all, void *cookie)
+{
+  garray_T *files = cookie;
+  for (int i = 0; i < num_fnames; i++) {
+    GA_APPEND(char *, files, xstrdup(fnames[i]));
+  }
+  return num_fnames > 0;
+}
+
+/// Loads the WASM plugins found in 'runtimepath' at startup, i.e. the "plugin/**/*.wasm" files,
+/// after the other plugin files, see load_plugins().
+///
+/// A plugin importing an interface exported by another plugin fails to load before it, whatever
+/// the order of their files: such plugins are loaded again after the other ones, until no more
+/// plugin loads.
+void wasm_load_plugins(void)
+{
+  garray_T files;
+  ga_init(&files, (int)sizeof(char *), 8);
+  do_in_runtimepath("plugin/**/*.wasm", DIP_ALL, wasm_collect_plugins, &files);
+
+  // The errors of the plugins left in "files", reported when no more plugin loads.
+  garray_T errors;
+  ga_init(&errors, (int)sizeof(char *), 8);
+  bool loaded_one = true;
+  while (files.ga_len > 0 && loaded_one) {
+    loaded_one = false;
+    errors.ga_len = 0;
+    int pending = 0;
+    for (int i = 0; i < files.ga_len; i++) {
+      char *file = ((char **)files.ga_data)[i];
+      const char *errmsg = NULL;
+      int32_t instance_id = wasm_load_file(file, false, &errmsg);
+      if (instance_id == WASM_MISSING_PROVIDER) {
+        ((char **)files.ga_data)[pending++] = file;
+        GA_APPEND(const char *, &errors, errmsg);
+        continue;
+      }
+      if (instance_id >= 0) {
+        loaded_one = true;
+      }
+      wasm_run_instance(instance_id, file, errmsg);
+      xfree(file);
+    }
+    files.ga_len = pending;
+  }
+
+  for (int i = 0; i < errors.ga_len; i++) {
+    emsg_multiline(((const char **)errors.ga_data)[i], /*multiline=*/true);
+  }
+  ga_clear(&errors);
+  ga_clear_strings(&files);
+}
+
+/// Loads a WASM plugin and calls its `run` function, like |:wasm|. Errors are reported with
+/// emsg().
+///
+/// @param file_path       The path of the plugin.
+/// @param user_initiated  False for the plugins found in 'runtimepath', by |:runtime|, see
+///                        wasm_load().
+void wasm_run_file(const char *file_path, bool user_initiated)
+  FUNC_ATTR_NONNULL_ALL
+{
   const char *errmsg = NULL;
-  int32_t instance_id = wasm_load_file(file_path, &errmsg);
+  int32_t instance_id = wasm_load_file(file_path, user_initiated, &errmsg);
+  wasm_run_instance(instance_id, file_path, errmsg);
+}
+
+/// Calls the `run` function of an instance returned by wasm_load_file(), or reports the error of
+/// wasm_load_file().
+static void wasm_run_instance(int32_t instance_id, const char *file_path, const char *errmsg)
+  FUNC_ATTR_NONNULL_ARG(2)
+{
+  Error err = ERROR_INIT;
+  instance_id = wasm_register_instance(instance_id, file_path, errmsg, &err);
   if (instance_id < 0) {
-    emsg_multiline(errmsg, /*multiline=*/true);
+    emsg_multiline(err.msg, /*multiline=*/true);
+    api_clear_error(&err);
     return;
   }
   Array empty_arg = ARRAY_DICT_INIT;
-  Object result = wasm_call_func(instance_id, "run", empty_arg, &errmsg);
-  if (errmsg != NULL) {
-    emsg_multiline(errmsg, /*multiline=*/true);
+  Object result = wasm_call(instance_id, "run", empty_arg, &err);
+  if (ERROR_SET(&err)) {
+    emsg_multiline(err.msg, /*multiline=*/true);
+    api_clear_error(&err);
   }
   (void)result;
 }
+
+/// Loads and instantiates a WASM component.
+///
+/// Components denied in the WASM trust database are never loaded, see |:WasmTrust|.
+///
+/// @param file_path       The path of the component.
+/// @param user_initiated  False if the user did not ask for loading the component, e.g. when it
+///                        is found in 'runtimepath'. The user is then asked whether to trust it.
+/// @param[out] err        Error details, if any
+/// @return The instance ID, -1 on error.
+int32_t wasm_load(const char *file_path, bool user_initiated, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  const char *errmsg = NULL;
+  int32_t instance_id = wasm_load_file(file_path, user_initiated, &errmsg);
+  return wasm_register_instance(instance_id, file_path, errmsg, err);
+}
+
+/// Loads and instantiates a WASM component from memory, see nvim_wasm_load_bytes().
+///
+/// @param bytes           The component, in binary or text format.
+/// @param name            The name of the plugin, shown instead of its file, or NULL.
+/// @param user_initiated  False if the user did not ask for loading the plugin, see wasm_load().
+/// @param[out] err        Error details, if any
+/// @return The instance ID, -1 on error.
+int32_t wasm_load_blob(String bytes, const char *name, bool user_initiated, Error *err)
+  FUNC_ATTR_NONNULL_ARG(4)
+{
+  const char *errmsg = NULL;
+  int32_t instance_id = wasm_load_bytes(bytes.data, bytes.size, name, user_initiated, &errmsg);
+  return wasm_register_instance(instance_id, name != NULL ? name : "[bytes]", errmsg, err);
+}
+
+/// Records the file of an instance loaded by wasm_load_file() or wasm_load_bytes().
+static int32_t wasm_register_instance(int32_t instance_id, const char *file, const char *errmsg,
+                                      Error *err)
+{
+  if (instance_id < 0) {
+    api_set_error(err, kErrorTypeException, "%s", errmsg);
+    return -1;
+  }
+  while (kv_size(instance_files) <= (size_t)instance_id) {
+    kv_push(instance_files, NULL);
+  }
+  kv_A(instance_files, instance_id) = xstrdup(file);
+  return instance_id;
+}
+
+/// Unloads a WASM instance and removes what it registered, see nvim_wasm_unload().
+///
+/// Not allowed under textlock: buffer callbacks and decoration providers are called under
+/// textlock, and must not be removed while they are iterated.
+///
+/// @param instance_id The instance ID returned by wasm_load().
+/// @param[out] err    Error details, if any
+void wasm_unload(int32_t instance_id, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  if (text_locked()) {
+    api_set_error(err, kErrorTypeException, "%s", get_text_locked_msg());
+    return;
+  }
+  const char *errmsg = NULL;
+  if (!wasm_unload_instance(instance_id, &errmsg)) {
+    api_set_error(err, kErrorTypeException, "%s", errmsg);
+    return;
+  }
+  wasm_stop_instance(instance_id);
+}
+
+/// Stops the timers and processes of a WASM instance which is unloaded, or which failed to
+/// instantiate after starting them, and deletes its autocmds, mappings, user commands, decoration
+/// providers and buffer callbacks.
+///
+/// The buffer callbacks of an instance which failed to instantiate were appended after those which
+/// may be iterated, so they can be removed under textlock.
+void wasm_stop_instance(int32_t instance_id)
+{
+  wasm_timer_stop_instance(instance_id);
+  wasm_process_stop_instance(instance_id);
+  uint64_t channel_id = wasm_channel_id(instance_id);
+  autocmd_delete_chan(channel_id);
+  map_clear_chan(channel_id);
+  uc_clear_chan(channel_id);
+  decor_provider_clear_wasm(instance_id);
+  buf_updates_unregister_wasm(instance_id);
+}
+
+/// Sets the trust of a WASM file in the trust database, see nvim_wasm_trust().
+///
+/// @param file_path  The WASM file.
+/// @param action        "allow", "deny" or "remove".
+/// @param capabilities  Comma-separated capabilities granted to the file, e.g. "process".
+/// @param[out] err      Error details, if any
+void wasm_trust(const char *file_path, const char *action, const char *capabilities, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  const char *errmsg = NULL;
+  wasm_trust_file(file_path, action, capabilities, &errmsg);
+  if (errmsg != NULL) {
+    api_set_error(err, kErrorTypeException, "%s", errmsg);
+  }
+}
+
+/// Asks the user whether to trust a WASM plugin loaded automatically, see wasm_load().
+///
+/// @param message  The question, e.g. "plugin.wasm is not trusted."
+/// @return 1 to ignore the plugin, 2 to deny it, 3 to allow it, or 0 if cancelled.
+int wasm_trust_prompt(const char *message)
+  FUNC_ATTR_NONNULL_ALL
+{
+  return do_dialog(VIM_QUESTION, NULL, message, _("&ignore\n&deny\n&allow"), 1, NULL, false);
+}
+
+/// Writes the local time to `buf`, e.g. "2024-01-31T12:34:56.789", for the log files of WASM
+/// plugins. Uses the format of the Nvim log file, see log.c.
+///
+/// @param[out] buf  Buffer for the time, left empty on failure.
+/// @param size      Size of `buf`.
+void wasm_log_time(char *buf, size_t size)
+  FUNC_ATTR_NONNULL_ALL
+{
+  buf[0] = NUL;
+  struct tm local_time;
+  char date_time[20];
+  if (os_localtime(&local_time) == NULL
+      || strftime(date_time, sizeof(date_time), "%Y-%m-%dT%H:%M:%S", &local_time) == 0) {
+    return;
+  }
+  int millis = 0;
+  uv_timeval64_t curtime;
+  if (uv_gettimeofday(&curtime) == 0) {
+    millis = (int)curtime.tv_usec / 1000;
+  }
+  snprintf(buf, size, "%s.%03d", date_time, millis);
+}
+
+/// Returns the pseudo-channel ID of a WASM instance.
+///
+/// API functions called by the instance receive this channel ID, so that what the instance
+/// registers (autocmds, mappings, user commands, etc.) can be traced back to it, e.g. by
+/// `:verbose map`.
+uint64_t wasm_channel_id(int32_t instance_id)
+{
+  assert(instance_id >= 0);
+  return WASM_CHANNEL_ID_BASE + (uint64_t)instance_id;
+}
+
+/// Returns the channel ID of the running WASM instance, see wasm_channel_id().
+uint64_t wasm_current_channel_id(void)
+{
+  return wasm_channel_id(current_instance);
+}
+
+/// Returns the file of the WASM instance with the channel ID `channel_id`, or NULL if it is not a
+/// WASM channel.
+const char *wasm_channel_file(uint64_t channel_id)
+{
+  if (channel_id < WASM_CHANNEL_ID_BASE
+      || channel_id - WASM_CHANNEL_ID_BASE >= kv_size(instance_files)) {
+    return NULL;
+  }
+  return kv_A(instance_files, channel_id - WASM_CHANNEL_ID_BASE);
+}
+
+/// Calls a function exported by a WASM instance.
+///
+/// Contexts entered by the instance and not restored are restored when the call returns.
+///
+/// @param instance_id The instance ID returned by wasm_load().
+/// @param func_name   The name of the export, of type `func(args: list<object>) -> object`.
+/// @param args        The arguments.
+/// @param[out] err    Error details, if any
+Object wasm_call(int32_t instance_id, const char *func_name, Array args, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  WasmCallState state = wasm_call_enter(instance_id);
+  const char *errmsg = NULL;
+  Object result = wasm_call_func(instance_id, func_name, args, &errmsg);
+  wasm_call_leave(state, errmsg, err);
+  return result;
+}
+
+/// Finds a hook exported by a WASM instance, of type `func(s64, ...) -> bool`, to be called with
+/// wasm_call_hook_func().
+///
+/// @param instance_id The instance ID returned by wasm_load().
+/// @param func_name   The name of the export.
+/// @param args_len    The number of arguments of the hook, at most 4.
+/// @param[out] err    Error details, if any
+/// @return The hook, or -1 on error.
+int wasm_find_hook_func(int32_t instance_id, const char *func_name, size_t args_len, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  const char *errmsg = NULL;
+  int hook = wasm_find_hook(instance_id, func_name, args_len, &errmsg);
+  if (errmsg != NULL) {
+    api_set_error(err, kErrorTypeException, "%s", errmsg);
+  }
+  return hook;
+}
+
+/// Calls a hook exported by a WASM instance, found by wasm_find_hook_func().
+///
+/// Unlike wasm_call(), the hook is not looked up by name, and the arguments are passed as is
+/// instead of as API objects.
+///
+/// @param instance_id The instance ID returned by wasm_load().
+/// @param hook        The hook returned by wasm_find_hook_func().
+/// @param args        The arguments, as many as the hook takes.
+/// @param args_len    The number of arguments.
+/// @param[out] err    Error details, if any
+/// @return The result of the hook, false on error.
+bool wasm_call_hook_func(int32_t instance_id, int hook, const int64_t *args, size_t args_len,
+                         Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  WasmCallState state = wasm_call_enter(instance_id);
+  const char *errmsg = NULL;
+  bool result = wasm_call_hook(instance_id, hook, args, args_len, &errmsg);
+  wasm_call_leave(state, errmsg, err);
+  return result;
+}
+
+/// Calls the hook `func_name` exported by a WASM instance, of type `func(s64, ...) -> bool`.
+///
+/// Like wasm_call_hook_func(), for hooks called now and then, e.g. timers.
+///
+/// @return The result of the hook, false on error.
+bool wasm_call_named_hook_func(int32_t instance_id, const char *func_name, const int64_t *args,
+                               size_t args_len, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  int hook = wasm_find_hook_func(instance_id, func_name, args_len, err);
+  if (ERROR_SET(err)) {
+    return false;
+  }
+  return wasm_call_hook_func(instance_id, hook, args, args_len, err);
+}
+
+/// Calls the `on_lines` callback exported by a WASM instance, see nvim_wasm_buf_attach().
+///
+/// @return true to detach from the buffer, false on error.
+bool wasm_call_buf_lines_func(int32_t instance_id, const char *func_name,
+                              const WasmBufLinesEvent *event, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  WasmCallState state = wasm_call_enter(instance_id);
+  const char *errmsg = NULL;
+  bool result = wasm_call_buf_lines(instance_id, func_name, event, &errmsg);
+  wasm_call_leave(state, errmsg, err);
+  return result;
+}
+
+/// Calls the `on_bytes` callback exported by a WASM instance, see nvim_wasm_buf_attach().
+///
+/// @return true to detach from the buffer, false on error.
+bool wasm_call_buf_bytes_func(int32_t instance_id, const char *func_name,
+                              const WasmBufBytesEvent *event, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  WasmCallState state = wasm_call_enter(instance_id);
+  const char *errmsg = NULL;
+  bool result = wasm_call_buf_bytes(instance_id, func_name, event, &errmsg);
+  wasm_call_leave(state, errmsg, err);
+  return result;
+}
+
+/// Calls the export of a WASM instance receiving the output of its process, see
+/// wasm_process_spawn().
+///
+/// @param process_id  The ID of the process.
+/// @param data        The output, empty at EOF.
+void wasm_call_process_output_func(int32_t instance_id, const char *func_name, int64_t process_id,
+                                   String data, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  WasmCallState state = wasm_call_enter(instance_id);
+  const char *errmsg = NULL;
+  wasm_call_process_output(instance_id, func_name, process_id, data.data, data.size, &errmsg);
+  wasm_call_leave(state, errmsg, err);
+}
+
+/// Calls the export of a WASM instance receiving the exit status of its process, see
+/// wasm_process_spawn().
+void wasm_call_process_exit_func(int32_t instance_id, const char *func_name, int64_t process_id,
+                                 int status, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  WasmCallState state = wasm_call_enter(instance_id);
+  const char *errmsg = NULL;
+  wasm_call_process_exit(instance_id, func_name, process_id, status, &errmsg);
+  wasm_call_leave(state, errmsg, err);
+}
+
+/// Makes `instance_id` the running instance while another instance calls one of its exports,
+/// through an interface the other instance imports from it, or while it is instantiated.
+///
+/// @return The state to pass to wasm_linked_call_leave().
+WasmCallState wasm_linked_call_enter(int32_t instance_id)
+{
+  return wasm_call_enter(instance_id);
+}
+
+/// Restores the state saved by wasm_linked_call_enter().
+void wasm_linked_call_leave(WasmCallState state)
+{
+  wasm_call_leave(state, NULL, NULL);
+}
+
+/// Makes `instance_id` the running instance, see wasm_current_instance(). What the instance
+/// defines, e.g. with nvim_command(), is set from its pseudo-channel, see wasm_channel_id().
+static WasmCallState wasm_call_enter(int32_t instance_id)
+{
+  WasmCallState state = { current_instance, kv_size(wasm_contexts), call_depth, current_sctx };
+  current_instance = instance_id;
+  call_depth = kv_size(wasm_contexts);
+  current_sctx.sc_sid = SID_API_CLIENT;
+  current_sctx.sc_lnum = 0;
+  current_sctx.sc_chan = wasm_channel_id(instance_id);
+  return state;
+}
+
+/// Restores the state saved by wasm_call_enter(), including the contexts entered by the instance
+/// and not restored.
+static void wasm_call_leave(WasmCallState state, const char *errmsg, Error *err)
+{
+  wasm_restore_contexts(state.depth);
+  current_instance = state.instance;
+  call_depth = state.call_depth;
+  current_sctx = state.sctx;
+  if (errmsg != NULL) {
+    api_set_error(err, kErrorTypeException, "%s", errmsg);
+  }
+}
+
+/// Returns the WASM instance whose export is running, or -1 when no WASM code runs.
+int32_t wasm_current_instance(void)
+{
+  return current_instance;
+}
+
+/// Makes `buf` the current buffer until the context is restored, like nvim_buf_call().
+///
+/// @return The context handle, see wasm_restore_context().
+Integer wasm_enter_buf(buf_T *buf)
+  FUNC_ATTR_NONNULL_ALL
+{
+  WasmContext *ctx = xcalloc(1, sizeof(*ctx));
+  aucmd_prepbuf(&ctx->aco, buf);
+  kv_push(wasm_contexts, ctx);
+  return (Integer)kv_size(wasm_contexts);
+}
+
+/// Makes `wp` the current window until the context is restored, like WIN_EXECUTE().
+///
+/// @return The context handle, see wasm_restore_context(). 0 on error.
+Integer wasm_enter_win(win_T *wp, tabpage_T *tp, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  WasmContext *ctx = xcalloc(1, sizeof(*ctx));
+  ctx->win = wp;
+  ctx->curpos = wp->w_cursor;
+  ctx->cwd_status = FAIL;
+  // Getting and setting directory can be slow on some systems, only do
+  // this when the current or target window/tab have a local directory or
+  // 'acd' is set.
+  if (curwin != wp
+      && (curwin->w_localdir != NULL || wp->w_localdir != NULL
+          || (curtab != tp && (curtab->tp_localdir != NULL || tp->tp_localdir != NULL))
+          || p_acd)) {
+    ctx->cwd_status = os_dirname(ctx->cwd, MAXPATHL);
+  }
+  // If 'acd' is set, check we are using that directory.  If yes, then
+  // apply 'acd' afterwards, otherwise restore the current directory.
+  if (ctx->cwd_status == OK && p_acd) {
+    char autocwd[MAXPATHL];
+    do_autochdir();
+    ctx->apply_acd = os_dirname(autocwd, MAXPATHL) == OK && strcmp(ctx->cwd, autocwd) == 0;
+  }
+  kv_push(wasm_contexts, ctx);
+
+  if (switch_win_noblock(&ctx->switchwin, wp, tp, true) == FAIL) {
+    wasm_restore_contexts(kv_size(wasm_contexts) - 1);
+    api_set_error(err, kErrorTypeException, "Failed to switch to window %d", wp->handle);
+    return 0;
+  }
+  check_cursor();
+  return (Integer)kv_size(wasm_contexts);
+}
+
+/// Restores the innermost context, which must be `handle` and entered during the running call.
+///
+/// @return false if `handle` is not the innermost context, or was entered by a caller of the
+///         running instance.
+bool wasm_restore_context(Integer handle)
+{
+  if (handle <= 0 || (size_t)handle != kv_size(wasm_contexts) || (size_t)handle <= call_depth) {
+    return false;
+  }
+  wasm_restore_contexts((size_t)handle - 1);
+  return true;
+}
+
+/// Restores the contexts until only `depth` of them are left.
+static void wasm_restore_contexts(size_t depth)
+{
+  while (kv_size(wasm_contexts) > depth) {
+    WasmContext *ctx = kv_pop(wasm_contexts);
+    win_T *wp = ctx->win;
+    if (wp == NULL) {
+      aucmd_restbuf(&ctx->aco);
+    } else {
+      restore_win_noblock(&ctx->switchwin, true);
+      if (ctx->apply_acd) {
+        do_autochdir();
+      } else if (ctx->cwd_status == OK) {
+        os_chdir(ctx->cwd);
+      }
+      // Update the status line if the cursor moved.
+      if (win_valid(wp) && !equalpos(ctx->curpos, wp->w_cursor)) {
+        wp->w_redr_status = true;
+      }
+      // In case the plugin moved the cursor or changed the Visual area,
+      // check it is valid.
+      check_cursor();
+      if (VIsual_active) {
+        check_pos(curbuf, &VIsual);
+      }
+    }
+    xfree(ctx);
+  }
+}
@@ -1,7 +1,23 @@
 #ifndef NVIM_WASM_EXECUTOR_H
 #define NVIM_WASM_EXECUTOR_H
 
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include "nvim/api/private/defs.h"
+#include "nvim/buffer_defs.h"
+#include "nvim/eval/typval_defs.h"
 #include "nvim/ex_cmds_defs.h"
+#include "nvim/rust/nvim-wasm/include/wasm-rs.h"
+
+/// The state saved when calling into a WASM instance.
+typedef struct {
+  int32_t instance;  ///< The previously running instance.
+  size_t depth;  ///< The number of contexts entered before the call.
+  size_t call_depth;  ///< The number of contexts entered before the previous call.
+  sctx_T sctx;  ///< The script context before the call.
+} WasmCallState;
 
 #ifdef INCLUDE_GENERATED_DECLARATIONS
 # include "wasm/executor.h.generated.h"
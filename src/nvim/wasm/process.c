@@ -0,0 +1,205 @@
+#include "nvim/wasm/process.h"
+
+#include <assert.h>
+#include <inttypes.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#include "nvim/api/private/defs.h"
+#include "nvim/api/private/helpers.h"
+#include "nvim/api/private/validate.h"
+#include "nvim/channel.h"
+#include "nvim/eval/funcs.h"
+#include "nvim/eval/typval.h"
+#include "nvim/eval/typval_defs.h"
+#include "nvim/event/process.h"
+#include "nvim/garray.h"
+#include "nvim/map.h"
+#include "nvim/memory.h"
+#include "nvim/message.h"
+#include "nvim/os/fs.h"
+#include "nvim/wasm/executor.h"
+
+#ifdef INCLUDE_GENERATED_DECLARATIONS
+# include "wasm/process.c.generated.h"
+#endif
+
+/// The names of the exports called on the events of the processes spawned by WASM instances, of
+/// types `func(process: s64, data: list<u8>)` and `func(process: s64, status: s32)`.
+#define WASM_PROCESS_STDOUT_HOOK "on-process-stdout"
+#define WASM_PROCESS_STDERR_HOOK "on-process-stderr"
+#define WASM_PROCESS_EXIT_HOOK "on-process-exit"
+
+/// Spawns a process for the running WASM instance with the `nvim:api/process` interface, like
+/// jobstart().
+///
+/// The process is owned by the instance, which receives its output and exit status in calls to
+/// its `on-process-stdout`, `on-process-stderr` and `on-process-exit` exports.
+///
+/// @param argv        The command and its arguments, as strings.
+/// @param cwd         Working directory of the process, Nvim's working directory if empty.
+/// @param env         Environment variables added to Nvim's environment.
+/// @param has_stdin   Open a pipe for the stdin of the process, see wasm_process_write().
+/// @param has_stdout  Send the stdout of the process to the instance.
+/// @param has_stderr  Send the stderr of the process to the instance.
+/// @param[out] err    Error details, if any
+/// @return The process ID, i.e. the ID of its channel, or 0 on error.
+int64_t wasm_process_spawn(Array argv, String cwd, Dictionary env, bool has_stdin,
+                           bool has_stdout, bool has_stderr, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  int32_t instance_id = wasm_current_instance();
+  assert(instance_id >= 0);
+  VALIDATE(argv.size > 0, "%s", "'argv' is empty", {
+    return 0;
+  });
+  VALIDATE(cwd.size == 0 || os_isdir(cwd.data), "Invalid 'cwd': '%s' is not a directory",
+           cwd.data, {
+    return 0;
+  });
+  // Resolve the executable like tv_to_argv(), so that $PATHEXT works on Windows.
+  char *exe_resolved = NULL;
+  const char *arg0 = argv.items[0].data.string.data;
+  VALIDATE(os_can_exe(arg0, &exe_resolved, true), "Invalid 'argv': '%s' is not executable",
+           arg0, {
+    return 0;
+  });
+
+  char **args = xcalloc(argv.size + 1, sizeof(char *));
+  args[0] = exe_resolved;
+  for (size_t i = 1; i < argv.size; i++) {
+    args[i] = xstrdup(argv.items[i].data.string.data);
+  }
+
+  dictitem_T *job_env = tv_dict_item_alloc("env");
+  job_env->di_tv.v_type = VAR_DICT;
+  job_env->di_tv.vval.v_dict = tv_dict_alloc();
+  job_env->di_tv.vval.v_dict->dv_refcount++;
+  for (size_t i = 0; i < env.size; i++) {
+    tv_dict_add_str(job_env->di_tv.vval.v_dict, env.items[i].key.data, env.items[i].key.size,
+                    env.items[i].value.data.string.data);
+  }
+  dict_T *environment = create_environment(job_env, false, false, NULL);
+  tv_dict_item_free(job_env);
+
+  // The instance is called instead of the callbacks, which are only set so that the output is read.
+  CallbackReader on_stdout = CALLBACK_READER_INIT;
+  CallbackReader on_stderr = CALLBACK_READER_INIT;
+  on_stdout.wasm = has_stdout;
+  on_stderr.wasm = has_stderr;
+  varnumber_T status = 0;
+  Channel *chan = NULL;
+  TRY_WRAP(err, {
+    chan = channel_job_start(args, NULL, on_stdout, on_stderr, CALLBACK_NONE, false, false, false,
+                             false, has_stdin ? kChannelStdinPipe : kChannelStdinNull,
+                             cwd.size > 0 ? cwd.data : NULL, 0, 0, environment, &status);
+  });
+  if (chan == NULL) {
+    if (!ERROR_SET(err)) {
+      api_set_error(err, kErrorTypeException, "Failed to spawn process");
+    }
+    return 0;
+  }
+  chan->wasm_instance = instance_id;
+  channel_create_event(chan, "wasm");
+  return (int64_t)chan->id;
+}
+
+/// Writes `data` to the stdin of a process spawned by the running WASM instance.
+void wasm_process_write(int64_t process_id, String data, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  Channel *chan = find_wasm_process(process_id, err);
+  if (chan == NULL) {
+    return;
+  }
+  const char *error = NULL;
+  channel_send(chan->id, data.data, data.size, false, &error);
+  if (error != NULL) {
+    api_set_error(err, kErrorTypeException, "%s", error);
+  }
+}
+
+/// Closes the stdin of a process spawned by the running WASM instance.
+void wasm_process_close_stdin(int64_t process_id, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  Channel *chan = find_wasm_process(process_id, err);
+  if (chan == NULL) {
+    return;
+  }
+  const char *error = NULL;
+  if (!channel_close(chan->id, kChannelPartStdin, &error)) {
+    api_set_error(err, kErrorTypeException, "%s", error);
+  }
+}
+
+/// Stops a process spawned by the running WASM instance, like jobstop().
+void wasm_process_kill(int64_t process_id, Error *err)
+  FUNC_ATTR_NONNULL_ALL
+{
+  Channel *chan = find_wasm_process(process_id, err);
+  if (chan != NULL) {
+    process_stop(&chan->stream.proc);
+  }
+}
+
+/// Stops the processes of the WASM instance `instance_id`, when it is unloaded.
+///
+/// Their remaining output and exit status are dropped, as the instance cannot be called anymore.
+void wasm_process_stop_instance(int32_t instance_id)
+{
+  Channel *chan;
+  pmap_foreach_value(&channels, chan, {
+    if (chan->wasm_instance == instance_id) {
+      chan->wasm_instance = -1;
+      chan->on_data.wasm = false;
+      chan->on_stderr.wasm = false;
+      ga_clear(&chan->on_data.buffer);
+      ga_clear(&chan->on_stderr.buffer);
+      chan->on_data.eof = false;
+      chan->on_stderr.eof = false;
+      process_stop(&chan->stream.proc);
+    }
+  });
+}
+
+/// Calls the export of the WASM instance owning `chan` for an event of its process, instead of
+/// the callbacks of a job. See channel_callback_call().
+///
+/// @param reader  The stream with new output, sent to the instance. Empty at EOF. NULL when the
+///                process exited.
+void wasm_process_callback(Channel *chan, CallbackReader *reader)
+  FUNC_ATTR_NONNULL_ARG(1)
+{
+  Error err = ERROR_INIT;
+  if (reader != NULL) {
+    const char *hook = reader == &chan->on_data ? WASM_PROCESS_STDOUT_HOOK
+                                                : WASM_PROCESS_STDERR_HOOK;
+    String data = { .data = reader->buffer.ga_data, .size = (size_t)reader->buffer.ga_len };
+    wasm_call_process_output_func(chan->wasm_instance, hook, (int64_t)chan->id, data, &err);
+    ga_clear(&reader->buffer);
+  } else {
+    wasm_call_process_exit_func(chan->wasm_instance, WASM_PROCESS_EXIT_HOOK, (int64_t)chan->id,
+                                chan->exit_status, &err);
+  }
+  if (ERROR_SET(&err)) {
+    emsg_multiline(err.msg, /*multiline=*/true);
+    api_clear_error(&err);
+  }
+}
+
+/// Returns the channel of the process `process_id` if it is owned by the running WASM instance.
+///
+/// The processes of other instances are not found, so that an instance cannot use them.
+static Channel *find_wasm_process(int64_t process_id, Error *err)
+{
+  Channel *chan = process_id > 0 ? find_channel((uint64_t)process_id) : NULL;
+  if (chan == NULL || chan->wasm_instance < 0
+      || chan->wasm_instance != wasm_current_instance()) {
+    api_set_error(err, kErrorTypeValidation, "Invalid process id: %" PRId64, process_id);
+    return NULL;
+  }
+  return chan;
+}
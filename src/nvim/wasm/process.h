@@ -0,0 +1,14 @@
+#ifndef NVIM_WASM_PROCESS_H
+#define NVIM_WASM_PROCESS_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+#include "nvim/api/private/defs.h"
+#include "nvim/channel.h"
+
+#ifdef INCLUDE_GENERATED_DECLARATIONS
+# include "wasm/process.h.generated.h"
+#endif
+
+#endif  // NVIM_WASM_PROCESS_H
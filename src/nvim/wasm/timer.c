@@ -0,0 +1,162 @@
+#include "nvim/wasm/timer.h"
+
+#include <assert.h>
+#include <stdbool.h>
+#include <stdint.h>
+
+#include "nvim/api/private/defs.h"
+#include "nvim/api/private/helpers.h"
+#include "nvim/event/loop.h"
+#include "nvim/event/multiqueue.h"
+#include "nvim/event/time.h"
+#include "nvim/macros.h"
+#include "nvim/main.h"
+#include "nvim/map.h"
+#include "nvim/memory.h"
+#include "nvim/message.h"
+#include "nvim/wasm/executor.h"
+
+/// A timer started by a WASM instance with the `nvim:api/timer` interface.
+typedef struct {
+  TimeWatcher tw;
+  int64_t timer_id;
+  int32_t instance_id;  ///< The instance owning the timer, whose `on-timer` hook is called.
+  int64_t callback_id;  ///< Passed to the hook, so that the instance tells its timers apart.
+  uint64_t timeout;
+  int repeat_count;  ///< Calls left, negative to repeat forever.
+  int emsg_count;
+  int refcount;
+  bool stopped;
+} WasmTimer;
+
+#ifdef INCLUDE_GENERATED_DECLARATIONS
+# include "wasm/timer.c.generated.h"
+#endif
+
+/// The name of the hook exported by the instances using timers, of type
+/// `func(timer: s64, callback-id: s64) -> bool`.
+#define WASM_TIMER_HOOK "on-timer"
+
+static int64_t last_timer_id = 0;
+static PMap(uint64_t) wasm_timers = MAP_INIT;
+
+/// Starts a timer calling the `on-timer` hook of the running WASM instance, like timer_start().
+///
+/// @param timeout       Time between the calls, in milliseconds.
+/// @param repeat_count  Number of calls, negative to call the hook until the timer is stopped.
+/// @param callback_id   Passed to the hook along with the timer ID.
+/// @return The timer ID.
+int64_t wasm_timer_start(uint64_t timeout, int repeat_count, int64_t callback_id)
+{
+  int32_t instance_id = wasm_current_instance();
+  assert(instance_id >= 0);
+  WasmTimer *timer = xmalloc(sizeof *timer);
+  timer->timer_id = ++last_timer_id;
+  timer->instance_id = instance_id;
+  timer->callback_id = callback_id;
+  timer->timeout = timeout;
+  timer->repeat_count = repeat_count == 0 ? 1 : repeat_count;
+  timer->emsg_count = 0;
+  timer->refcount = 1;
+  timer->stopped = false;
+
+  time_watcher_init(&main_loop, &timer->tw, timer);
+  timer->tw.events = multiqueue_new_child(main_loop.events);
+  // if main loop is blocked, don't queue up multiple events
+  timer->tw.blockable = true;
+  time_watcher_start(&timer->tw, wasm_timer_due_cb, timeout, timeout);
+
+  pmap_put(uint64_t)(&wasm_timers, (uint64_t)timer->timer_id, timer);
+  return timer->timer_id;
+}
+
+/// Stops the timer `timer_id` if it is owned by the running WASM instance.
+///
+/// Timers of other instances are left running, so that an instance cannot stop them.
+void wasm_timer_stop(int64_t timer_id)
+{
+  WasmTimer *timer = pmap_get(uint64_t)(&wasm_timers, (uint64_t)timer_id);
+  if (timer != NULL && timer->instance_id == wasm_current_instance()) {
+    wasm_timer_stop_timer(timer);
+  }
+}
+
+/// Stops the timers of the WASM instance `instance_id`, when it is unloaded.
+void wasm_timer_stop_instance(int32_t instance_id)
+{
+  WasmTimer *timer;
+  pmap_foreach_value(&wasm_timers, timer, {
+    if (timer->instance_id == instance_id) {
+      wasm_timer_stop_timer(timer);
+    }
+  })
+}
+
+void wasm_timer_teardown(void)
+{
+  WasmTimer *timer;
+  pmap_foreach_value(&wasm_timers, timer, {
+    wasm_timer_stop_timer(timer);
+  })
+}
+
+/// invoked on the main loop
+static void wasm_timer_due_cb(TimeWatcher *tw, void *data)
+{
+  WasmTimer *timer = (WasmTimer *)data;
+  if (timer->stopped) {
+    return;
+  }
+
+  timer->refcount++;
+  // if repeat was negative repeat forever
+  if (timer->repeat_count >= 0 && --timer->repeat_count == 0) {
+    wasm_timer_stop_timer(timer);
+  }
+
+  Error err = ERROR_INIT;
+  int64_t args[] = { timer->timer_id, timer->callback_id };
+  bool stop = wasm_call_named_hook_func(timer->instance_id, WASM_TIMER_HOOK, args,
+                                        ARRAY_SIZE(args), &err);
+  if (ERROR_SET(&err)) {
+    emsg_multiline(err.msg, /*multiline=*/true);
+    api_clear_error(&err);
+    timer->emsg_count++;
+  }
+
+  if (stop || timer->emsg_count >= 3) {
+    wasm_timer_stop_timer(timer);
+  } else if (!timer->stopped && timer->timeout == 0) {
+    // Like timer_due_cb(): call the hook again on the next event loop tick.
+    time_watcher_start(&timer->tw, wasm_timer_due_cb, 0, 0);
+  }
+  wasm_timer_decref(timer);
+}
+
+static void wasm_timer_stop_timer(WasmTimer *timer)
+{
+  if (timer->stopped) {
+    // avoid double free
+    return;
+  }
+  timer->stopped = true;
+  time_watcher_stop(&timer->tw);
+  time_watcher_close(&timer->tw, wasm_timer_close_cb);
+}
+
+/// This will be run on the main loop after the last wasm_timer_due_cb(), so at this point it is
+/// safe to free the timer.
+static void wasm_timer_close_cb(TimeWatcher *tw, void *data)
+{
+  WasmTimer *timer = (WasmTimer *)data;
+  multiqueue_free(timer->tw.events);
+  pmap_del(uint64_t)(&wasm_timers, (uint64_t)timer->timer_id, NULL);
+  wasm_timer_decref(timer);
+}
+
+static void wasm_timer_decref(WasmTimer *timer)
+{
+  if (--timer->refcount == 0) {
+    xfree(timer);
+  }
+}
@@ -0,0 +1,10 @@
+#ifndef NVIM_WASM_TIMER_H
+#define NVIM_WASM_TIMER_H
+
+#include <stdint.h>
+
+#ifdef INCLUDE_GENERATED_DECLARATIONS
+# include "wasm/timer.h.generated.h"
+#endif
+
+#endif  // NVIM_WASM_TIMER_H
@@ -0,0 +1,208 @@
+;; A WASM plugin attaching to buffers with `nvim_wasm_buf_attach()`.
+;;
+;; `run` executes its first argument as an Ex command with `nvim_command()`, and returns the error
+;; message of the command, or nil: the plugin attaches with `run('call nvim_wasm_buf_attach(...)')`.
+;; The callbacks log their arguments, and `events()` returns the log as a flat list of integers,
+;; then clears it:
+;; - `on-lines`: 1, buffer, changedtick (-1 if none), first-line, last-line, last-line-updated,
+;;   byte-count
+;; - `on-bytes`: 2, followed by the 11 fields of the event
+;; - `on-changedtick`: 3, buffer, changedtick
+;; - `on-detach`: 4, buffer
+;; The callbacks return false to stay attached, until `detach()` is called.
+(component
+  (import "nvim:api/nvim-api@1.12.0" (instance $api
+    (export "nvim-command" (func (param "command" string) (result (result (error string)))))
+  ))
+
+  (type $primitive' (variant
+    (case "nil")
+    (case "boolean" bool)
+    (case "integer" s64)
+    (case "float" float64)
+    (case "string" string)
+    (case "buffer" s64)
+    (case "window" s64)
+    (case "tabpage" s64)
+  ))
+  (export $primitive "primitive" (type $primitive'))
+  (type $object' (variant
+    (case "nil")
+    (case "boolean" bool)
+    (case "integer" s64)
+    (case "float" float64)
+    (case "string" string)
+    (case "array" (list $primitive))
+    (case "dictionary" (list (tuple string $primitive)))
+    (case "buffer" s64)
+    (case "window" s64)
+    (case "tabpage" s64)
+  ))
+  (export $object "object" (type $object'))
+
+  (type $buf-lines-event' (record
+    (field "buffer" s64)
+    (field "changedtick" (option s64))
+    (field "first-line" s64)
+    (field "last-line" s64)
+    (field "last-line-updated" s64)
+    (field "byte-count" s64)
+    (field "deleted-codepoints" (option s64))
+    (field "deleted-codeunits" (option s64))
+  ))
+  (export $buf-lines-event "buf-lines-event" (type $buf-lines-event'))
+  (type $buf-bytes-event' (record
+    (field "buffer" s64)
+    (field "changedtick" s64)
+    (field "start-row" s64)
+    (field "start-col" s64)
+    (field "start-byte" s64)
+    (field "old-end-row" s64)
+    (field "old-end-col" s64)
+    (field "old-end-byte" s64)
+    (field "new-end-row" s64)
+    (field "new-end-col" s64)
+    (field "new-end-byte" s64)
+  ))
+  (export $buf-bytes-event "buf-bytes-event" (type $buf-bytes-event'))
+
+  ;; The memory and a bump allocator which never frees.
+  (core module $libc
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 32768))
+    (func (export "cabi_realloc") (param i32 i32) (param $align i32) (param $size i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get $align) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get $align))))
+      (global.set $heap (i32.add (local.get $ptr) (local.get $size)))
+      (if (i32.gt_u (global.get $heap) (i32.shl (memory.size) (i32.const 16)))
+        (then (drop (memory.grow (i32.const 1)))))
+      (local.get $ptr))
+  )
+  (core instance $libc (instantiate $libc))
+
+  (core func $command (canon lower (func $api "nvim-command")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+
+  ;; The object returned by the exports is stored at address 0, the result of `nvim_command()` at
+  ;; 16, the log at 4096 and the list returned by `events()` at 16384.
+  (core module $main
+    (import "libc" "memory" (memory 1))
+    (import "nvim" "command" (func $command (param i32 i32 i32)))
+    (global $len (mut i32) (i32.const 0))
+    (global $detach (mut i32) (i32.const 0))
+
+    ;; Appends `value` to the log.
+    (func $log (param $value i64)
+      (if (i32.ge_u (global.get $len) (i32.const 1024))
+        (then unreachable))
+      (i64.store offset=4096 (i32.shl (global.get $len) (i32.const 3)) (local.get $value))
+      (global.set $len (i32.add (global.get $len) (i32.const 1))))
+
+    (func (export "run") (param $args i32) (param $len i32) (result i32)
+      (i32.store8 (i32.const 0) (i32.const 0))
+      ;; The first argument must be a string.
+      (if (i32.or
+            (i32.eqz (local.get $len))
+            (i32.ne (i32.load8_u (local.get $args)) (i32.const 4)))
+        (then unreachable))
+      (call $command
+        (i32.load offset=8 (local.get $args))
+        (i32.load offset=12 (local.get $args))
+        (i32.const 16))
+      (if (i32.load8_u (i32.const 16))
+        (then
+          (i32.store8 (i32.const 0) (i32.const 4))
+          (i32.store offset=8 (i32.const 0) (i32.load (i32.const 20)))
+          (i32.store offset=12 (i32.const 0) (i32.load (i32.const 24)))))
+      (i32.const 0))
+
+    (func (export "events") (param $args i32) (param $len i32) (result i32)
+      (local $i i32)
+      (local $item i32)
+      (block $done
+        (loop $copy
+          (br_if $done (i32.ge_u (local.get $i) (global.get $len)))
+          (local.set $item (i32.add (i32.const 16384) (i32.shl (local.get $i) (i32.const 4))))
+          (i32.store8 (local.get $item) (i32.const 2))
+          (i64.store offset=8 (local.get $item)
+            (i64.load offset=4096 (i32.shl (local.get $i) (i32.const 3))))
+          (local.set $i (i32.add (local.get $i) (i32.const 1)))
+          (br $copy)))
+      (i32.store8 (i32.const 0) (i32.const 5))
+      (i32.store offset=8 (i32.const 0) (i32.const 16384))
+      (i32.store offset=12 (i32.const 0) (global.get $len))
+      (global.set $len (i32.const 0))
+      (i32.const 0))
+
+    (func (export "detach") (param $args i32) (param $len i32) (result i32)
+      (global.set $detach (i32.const 1))
+      (i32.store8 (i32.const 0) (i32.const 0))
+      (i32.const 0))
+
+    (func (export "on-lines")
+      (param $buffer i64) (param $has_changedtick i32) (param $changedtick i64)
+      (param $first_line i64) (param $last_line i64) (param $last_line_updated i64)
+      (param $byte_count i64) (param i32 i64 i32 i64) (result i32)
+      (call $log (i64.const 1))
+      (call $log (local.get $buffer))
+      (call $log
+        (select (local.get $changedtick) (i64.const -1) (local.get $has_changedtick)))
+      (call $log (local.get $first_line))
+      (call $log (local.get $last_line))
+      (call $log (local.get $last_line_updated))
+      (call $log (local.get $byte_count))
+      (global.get $detach))
+
+    (func (export "on-bytes")
+      (param i64 i64 i64 i64 i64 i64 i64 i64 i64 i64 i64) (result i32)
+      (call $log (i64.const 2))
+      (call $log (local.get 0))
+      (call $log (local.get 1))
+      (call $log (local.get 2))
+      (call $log (local.get 3))
+      (call $log (local.get 4))
+      (call $log (local.get 5))
+      (call $log (local.get 6))
+      (call $log (local.get 7))
+      (call $log (local.get 8))
+      (call $log (local.get 9))
+      (call $log (local.get 10))
+      (global.get $detach))
+
+    (func (export "on-changedtick") (param $buffer i64) (param $changedtick i64) (result i32)
+      (call $log (i64.const 3))
+      (call $log (local.get $buffer))
+      (call $log (local.get $changedtick))
+      (global.get $detach))
+
+    (func (export "on-detach") (param $buffer i64) (result i32)
+      (call $log (i64.const 4))
+      (call $log (local.get $buffer))
+      (i32.const 0))
+  )
+  (core instance $main (instantiate $main
+    (with "libc" (instance $libc))
+    (with "nvim" (instance (export "command" (func $command))))
+  ))
+
+  (func (export "run") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "run")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "events") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "events")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "detach") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "detach")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "on-lines") (param "event" $buf-lines-event) (result bool)
+    (canon lift (core func $main "on-lines")))
+  (func (export "on-bytes") (param "event" $buf-bytes-event) (result bool)
+    (canon lift (core func $main "on-bytes")))
+  (func (export "on-changedtick") (param "bufnr" s64) (param "changedtick" s64) (result bool)
+    (canon lift (core func $main "on-changedtick")))
+  (func (export "on-detach") (param "bufnr" s64) (result bool)
+    (canon lift (core func $main "on-detach")))
+)
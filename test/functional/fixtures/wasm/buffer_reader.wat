@@ -0,0 +1,150 @@
+;; A WASM plugin reading buffers with the `buffer-reader` resource.
+;;
+;; `chunks(buffer, max-bytes)` reads `buffer` in chunks of at most `max-bytes` and returns the list
+;; of chunks, or the error message of the reader.
+(component
+  (import "nvim:api/nvim-buffer@1.12.0" (instance $buffer
+    (export "buffer-reader" (type $reader (sub resource)))
+    (export "[static]buffer-reader.open" (func
+      (param "buffer" s64) (result (result (own $reader) (error string)))))
+    (export "[method]buffer-reader.read-chunk" (func
+      (param "self" (borrow $reader)) (param "start-line" s64) (param "max-bytes" u32)
+      (result (result (list u8) (error string)))))
+  ))
+  (alias export $buffer "buffer-reader" (type $reader))
+
+  (type $primitive' (variant
+    (case "nil")
+    (case "boolean" bool)
+    (case "integer" s64)
+    (case "float" float64)
+    (case "string" string)
+    (case "buffer" s64)
+    (case "window" s64)
+    (case "tabpage" s64)
+  ))
+  (export $primitive "primitive" (type $primitive'))
+  (type $object' (variant
+    (case "nil")
+    (case "boolean" bool)
+    (case "integer" s64)
+    (case "float" float64)
+    (case "string" string)
+    (case "array" (list $primitive))
+    (case "dictionary" (list (tuple string $primitive)))
+    (case "buffer" s64)
+    (case "window" s64)
+    (case "tabpage" s64)
+  ))
+  (export $object "object" (type $object'))
+
+  ;; The memory and a bump allocator which never frees.
+  (core module $libc
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 32768))
+    (func (export "cabi_realloc") (param i32 i32) (param $align i32) (param $size i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get $align) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get $align))))
+      (global.set $heap (i32.add (local.get $ptr) (local.get $size)))
+      (if (i32.gt_u (global.get $heap) (i32.shl (memory.size) (i32.const 16)))
+        (then (drop (memory.grow (i32.const 1)))))
+      (local.get $ptr))
+  )
+  (core instance $libc (instantiate $libc))
+
+  (core func $open (canon lower (func $buffer "[static]buffer-reader.open")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (core func $read_chunk (canon lower (func $buffer "[method]buffer-reader.read-chunk")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (core func $drop (canon resource.drop $reader))
+
+  ;; The object returned by `chunks` is stored at address 0, the results of the reader at 16 and
+  ;; the list of chunks at 16384.
+  (core module $main
+    (import "libc" "memory" (memory 1))
+    (import "nvim" "open" (func $open (param i64 i32)))
+    (import "nvim" "read-chunk" (func $read_chunk (param i32 i64 i32 i32)))
+    (import "nvim" "drop" (func $drop (param i32)))
+
+    ;; Returns the integer argument `n` of `args`.
+    (func $arg (param $args i32) (param $len i32) (param $n i32) (result i64)
+      (local $arg i32)
+      (if (i32.ge_u (local.get $n) (local.get $len))
+        (then unreachable))
+      (local.set $arg (i32.add (local.get $args) (i32.mul (local.get $n) (i32.const 16))))
+      (if (i32.ne (i32.load8_u (local.get $arg)) (i32.const 2))
+        (then unreachable))
+      (i64.load offset=8 (local.get $arg)))
+
+    ;; Returns the error message of the result at 16.
+    (func $error (result i32)
+      (i32.store8 (i32.const 0) (i32.const 4))
+      (i32.store offset=8 (i32.const 0) (i32.load (i32.const 20)))
+      (i32.store offset=12 (i32.const 0) (i32.load (i32.const 24)))
+      (i32.const 0))
+
+    (func (export "chunks") (param $args i32) (param $len i32) (result i32)
+      (local $reader i32)
+      (local $max_bytes i32)
+      (local $line i64)
+      (local $count i32)
+      (local $item i32)
+      (local $ptr i32)
+      (local $size i32)
+      (local $i i32)
+      (local.set $max_bytes
+        (i32.wrap_i64 (call $arg (local.get $args) (local.get $len) (i32.const 1))))
+      (call $open (call $arg (local.get $args) (local.get $len) (i32.const 0)) (i32.const 16))
+      (if (i32.load8_u (i32.const 16))
+        (then (return (call $error))))
+      (local.set $reader (i32.load (i32.const 20)))
+      (block $done
+        (loop $read
+          (call $read_chunk
+            (local.get $reader) (local.get $line) (local.get $max_bytes) (i32.const 16))
+          (if (i32.load8_u (i32.const 16))
+            (then
+              (call $drop (local.get $reader))
+              (return (call $error))))
+          (local.set $ptr (i32.load (i32.const 20)))
+          (local.set $size (i32.load (i32.const 24)))
+          (br_if $done (i32.eqz (local.get $size)))
+          (if (i32.ge_u (local.get $count) (i32.const 1024))
+            (then unreachable))
+          (local.set $item (i32.add (i32.const 16384) (i32.shl (local.get $count) (i32.const 4))))
+          (i32.store8 (local.get $item) (i32.const 4))
+          (i32.store offset=8 (local.get $item) (local.get $ptr))
+          (i32.store offset=12 (local.get $item) (local.get $size))
+          (local.set $count (i32.add (local.get $count) (i32.const 1)))
+          ;; The next chunk starts after the lines of this one.
+          (local.set $i (i32.const 0))
+          (block $counted
+            (loop $count_lines
+              (br_if $counted (i32.ge_u (local.get $i) (local.get $size)))
+              (if (i32.eq (i32.load8_u (i32.add (local.get $ptr) (local.get $i))) (i32.const 10))
+                (then (local.set $line (i64.add (local.get $line) (i64.const 1)))))
+              (local.set $i (i32.add (local.get $i) (i32.const 1)))
+              (br $count_lines)))
+          (br $read)))
+      (call $drop (local.get $reader))
+      (i32.store8 (i32.const 0) (i32.const 5))
+      (i32.store offset=8 (i32.const 0) (i32.const 16384))
+      (i32.store offset=12 (i32.const 0) (local.get $count))
+      (i32.const 0))
+  )
+  (core instance $main (instantiate $main
+    (with "libc" (instance $libc))
+    (with "nvim" (instance
+      (export "open" (func $open))
+      (export "read-chunk" (func $read_chunk))
+      (export "drop" (func $drop))
+    ))
+  ))
+
+  (func (export "chunks") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "chunks")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+)
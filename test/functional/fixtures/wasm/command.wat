@@ -0,0 +1,83 @@
+;; A WASM plugin whose `run` function executes its first argument as an Ex command with
+;; `nvim_command()`, and returns the error message of the command, or nil.
+(component
+  (import "nvim:api/nvim-api@1.12.0" (instance $api
+    (export "nvim-command" (func (param "command" string) (result (result (error string)))))
+  ))
+
+  (type $primitive' (variant
+    (case "nil")
+    (case "boolean" bool)
+    (case "integer" s64)
+    (case "float" float64)
+    (case "string" string)
+    (case "buffer" s64)
+    (case "window" s64)
+    (case "tabpage" s64)
+  ))
+  (export $primitive "primitive" (type $primitive'))
+  (type $object' (variant
+    (case "nil")
+    (case "boolean" bool)
+    (case "integer" s64)
+    (case "float" float64)
+    (case "string" string)
+    (case "array" (list $primitive))
+    (case "dictionary" (list (tuple string $primitive)))
+    (case "buffer" s64)
+    (case "window" s64)
+    (case "tabpage" s64)
+  ))
+  (export $object "object" (type $object'))
+
+  ;; The memory and a bump allocator which never frees.
+  (core module $libc
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 1024))
+    (func (export "cabi_realloc") (param i32 i32) (param $align i32) (param $size i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get $align) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get $align))))
+      (global.set $heap (i32.add (local.get $ptr) (local.get $size)))
+      (if (i32.gt_u (global.get $heap) (i32.shl (memory.size) (i32.const 16)))
+        (then (drop (memory.grow (i32.const 1)))))
+      (local.get $ptr))
+  )
+  (core instance $libc (instantiate $libc))
+
+  (core func $command (canon lower (func $api "nvim-command")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+
+  ;; The object returned by `run` is stored at address 0, the result of `nvim_command()` at 16.
+  (core module $main
+    (import "libc" "memory" (memory 1))
+    (import "nvim" "command" (func $command (param i32 i32 i32)))
+    (func (export "run") (param $args i32) (param $len i32) (result i32)
+      (i32.store8 (i32.const 0) (i32.const 0))
+      ;; The first argument must be a string.
+      (if (i32.or
+            (i32.eqz (local.get $len))
+            (i32.ne (i32.load8_u (local.get $args)) (i32.const 4)))
+        (then unreachable))
+      (call $command
+        (i32.load offset=8 (local.get $args))
+        (i32.load offset=12 (local.get $args))
+        (i32.const 16))
+      (if (i32.load8_u (i32.const 16))
+        (then
+          (i32.store8 (i32.const 0) (i32.const 4))
+          (i32.store offset=8 (i32.const 0) (i32.load (i32.const 20)))
+          (i32.store offset=12 (i32.const 0) (i32.load (i32.const 24)))))
+      (i32.const 0))
+  )
+  (core instance $main (instantiate $main
+    (with "libc" (instance $libc))
+    (with "nvim" (instance (export "command" (func $command))))
+  ))
+
+  (func (export "run") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "run")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+)
@@ -0,0 +1,160 @@
+;; A WASM plugin implementing a decoration provider.
+;;
+;; `run` executes its first argument as an Ex command with `nvim_command()`, and returns the error
+;; message of the command, or nil: the plugin registers its hooks with
+;; `run('call nvim_wasm_set_decoration_provider(...)')`. `setup(ns_id, hl_id)` sets the namespace
+;; and the highlight group of the `on-line` hook, which highlights the first character of each
+;; redrawn line with `nvim_wasm_add_ephemeral_hl()`. `lines()` returns how many times `on-line` was
+;; called. `bad-line` is a hook of the wrong type for `on_line`.
+(component
+  (import "nvim:api/nvim-api@1.12.0" (instance $api
+    (export "nvim-command" (func (param "command" string) (result (result (error string)))))
+    (export "nvim-wasm-add-ephemeral-hl" (func
+      (param "ns-id" s64) (param "line" s64) (param "col" s64) (param "end-row" s64)
+      (param "end-col" s64) (param "hl-id" s64) (param "priority" s64)
+      (result (result (error string)))))
+  ))
+
+  (type $primitive' (variant
+    (case "nil")
+    (case "boolean" bool)
+    (case "integer" s64)
+    (case "float" float64)
+    (case "string" string)
+    (case "buffer" s64)
+    (case "window" s64)
+    (case "tabpage" s64)
+  ))
+  (export $primitive "primitive" (type $primitive'))
+  (type $object' (variant
+    (case "nil")
+    (case "boolean" bool)
+    (case "integer" s64)
+    (case "float" float64)
+    (case "string" string)
+    (case "array" (list $primitive))
+    (case "dictionary" (list (tuple string $primitive)))
+    (case "buffer" s64)
+    (case "window" s64)
+    (case "tabpage" s64)
+  ))
+  (export $object "object" (type $object'))
+
+  ;; The memory and a bump allocator which never frees.
+  (core module $libc
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 1024))
+    (func (export "cabi_realloc") (param i32 i32) (param $align i32) (param $size i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get $align) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get $align))))
+      (global.set $heap (i32.add (local.get $ptr) (local.get $size)))
+      (if (i32.gt_u (global.get $heap) (i32.shl (memory.size) (i32.const 16)))
+        (then (drop (memory.grow (i32.const 1)))))
+      (local.get $ptr))
+  )
+  (core instance $libc (instantiate $libc))
+
+  (core func $command (canon lower (func $api "nvim-command")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (core func $add_ephemeral_hl (canon lower (func $api "nvim-wasm-add-ephemeral-hl")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+
+  ;; The object returned by the exports is stored at address 0, the result of `nvim_command()` at
+  ;; 16 and the result of `nvim_wasm_add_ephemeral_hl()` at 32.
+  (core module $main
+    (import "libc" "memory" (memory 1))
+    (import "nvim" "command" (func $command (param i32 i32 i32)))
+    (import "nvim" "add-ephemeral-hl" (func $add_ephemeral_hl
+      (param i64 i64 i64 i64 i64 i64 i64 i32)))
+    (global $ns (mut i64) (i64.const 0))
+    (global $hl (mut i64) (i64.const 0))
+    (global $lines (mut i64) (i64.const 0))
+
+    ;; Returns the integer argument `n` of `args`.
+    (func $arg (param $args i32) (param $len i32) (param $n i32) (result i64)
+      (local $arg i32)
+      (if (i32.ge_u (local.get $n) (local.get $len))
+        (then unreachable))
+      (local.set $arg (i32.add (local.get $args) (i32.mul (local.get $n) (i32.const 16))))
+      (if (i32.ne (i32.load8_u (local.get $arg)) (i32.const 2))
+        (then unreachable))
+      (i64.load offset=8 (local.get $arg)))
+
+    (func (export "run") (param $args i32) (param $len i32) (result i32)
+      (i32.store8 (i32.const 0) (i32.const 0))
+      ;; The first argument must be a string.
+      (if (i32.or
+            (i32.eqz (local.get $len))
+            (i32.ne (i32.load8_u (local.get $args)) (i32.const 4)))
+        (then unreachable))
+      (call $command
+        (i32.load offset=8 (local.get $args))
+        (i32.load offset=12 (local.get $args))
+        (i32.const 16))
+      (if (i32.load8_u (i32.const 16))
+        (then
+          (i32.store8 (i32.const 0) (i32.const 4))
+          (i32.store offset=8 (i32.const 0) (i32.load (i32.const 20)))
+          (i32.store offset=12 (i32.const 0) (i32.load (i32.const 24)))))
+      (i32.const 0))
+
+    (func (export "setup") (param $args i32) (param $len i32) (result i32)
+      (global.set $ns (call $arg (local.get $args) (local.get $len) (i32.const 0)))
+      (global.set $hl (call $arg (local.get $args) (local.get $len) (i32.const 1)))
+      (i32.store8 (i32.const 0) (i32.const 0))
+      (i32.const 0))
+
+    (func (export "lines") (param $args i32) (param $len i32) (result i32)
+      (i32.store8 (i32.const 0) (i32.const 2))
+      (i64.store (i32.const 8) (global.get $lines))
+      (i32.const 0))
+
+    (func (export "on-tick") (param $tick i64) (result i32)
+      (i32.const 1))
+
+    (func (export "on-win") (param i64 i64 i64 i64) (result i32)
+      (i32.const 1))
+
+    (func (export "on-line") (param $win i64) (param $buf i64) (param $row i64) (result i32)
+      (global.set $lines (i64.add (global.get $lines) (i64.const 1)))
+      (call $add_ephemeral_hl
+        (global.get $ns) (local.get $row) (i64.const 0) (local.get $row) (i64.const 1)
+        (global.get $hl) (i64.const 4096) (i32.const 32))
+      ;; Traps if the call failed.
+      (if (i32.load8_u (i32.const 32))
+        (then unreachable))
+      (i32.const 1))
+  )
+  (core instance $main (instantiate $main
+    (with "libc" (instance $libc))
+    (with "nvim" (instance
+      (export "command" (func $command))
+      (export "add-ephemeral-hl" (func $add_ephemeral_hl))
+    ))
+  ))
+
+  (func (export "run") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "run")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "setup") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "setup")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "lines") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "lines")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "on-start") (param "tick" s64) (result bool)
+    (canon lift (core func $main "on-tick")))
+  (func (export "on-win")
+    (param "winid" s64) (param "bufnr" s64) (param "toprow" s64) (param "botrow" s64)
+    (result bool)
+    (canon lift (core func $main "on-win")))
+  (func (export "on-line") (param "winid" s64) (param "bufnr" s64) (param "row" s64) (result bool)
+    (canon lift (core func $main "on-line")))
+  (func (export "on-end") (param "tick" s64) (result bool)
+    (canon lift (core func $main "on-tick")))
+  (func (export "bad-line") (param "row" s64) (result bool)
+    (canon lift (core func $main "on-tick")))
+)
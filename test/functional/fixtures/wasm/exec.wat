@@ -0,0 +1,50 @@
+;; A WASM plugin exporting the `test:wasm/exec` interface, whose `exec` function executes an Ex
+;; command with `nvim_command()` and returns the error message of the command, if any.
+(component
+  (import "nvim:api/nvim-api@1.12.0" (instance $api
+    (export "nvim-command" (func (param "command" string) (result (result (error string)))))
+  ))
+
+  ;; The memory and a bump allocator which never frees.
+  (core module $libc
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 1024))
+    (func (export "cabi_realloc") (param i32 i32) (param $align i32) (param $size i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get $align) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get $align))))
+      (global.set $heap (i32.add (local.get $ptr) (local.get $size)))
+      (if (i32.gt_u (global.get $heap) (i32.shl (memory.size) (i32.const 16)))
+        (then (drop (memory.grow (i32.const 1)))))
+      (local.get $ptr))
+  )
+  (core instance $libc (instantiate $libc))
+
+  (core func $command (canon lower (func $api "nvim-command")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+
+  ;; The result of `exec` is stored at address 0, the result of `nvim_command()` at 16. Both are
+  ;; a discriminant followed by a string.
+  (core module $main
+    (import "libc" "memory" (memory 1))
+    (import "nvim" "command" (func $command (param i32 i32 i32)))
+    (func (export "exec") (param $ptr i32) (param $len i32) (result i32)
+      (call $command (local.get $ptr) (local.get $len) (i32.const 16))
+      (i32.store8 (i32.const 0) (i32.load8_u (i32.const 16)))
+      (i32.store offset=4 (i32.const 0) (i32.load (i32.const 20)))
+      (i32.store offset=8 (i32.const 0) (i32.load (i32.const 24)))
+      (i32.const 0))
+  )
+  (core instance $main (instantiate $main
+    (with "libc" (instance $libc))
+    (with "nvim" (instance (export "command" (func $command))))
+  ))
+
+  (func $exec (param "command" string) (result (option string))
+    (canon lift (core func $main "exec")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (instance $exec (export "exec" (func $exec)))
+  (export "test:wasm/exec" (instance $exec))
+)
@@ -0,0 +1,101 @@
+;; A WASM plugin whose `run` function logs its first argument as a warning with the `log`
+;; interface, with a `length` field set to the length of the message.
+(component
+  (import "nvim:api/log@1.12.0" (instance $log
+    (type $level' (enum "trace" "debug" "info" "warn" "error"))
+    (export "level" (type $level (eq $level')))
+    (type $primitive' (variant
+      (case "nil")
+      (case "boolean" bool)
+      (case "integer" s64)
+      (case "float" float64)
+      (case "string" string)
+      (case "buffer" s64)
+      (case "window" s64)
+      (case "tabpage" s64)
+    ))
+    (export "primitive" (type $primitive (eq $primitive')))
+    (export "log" (func
+      (param "level" $level) (param "message" string)
+      (param "fields" (list (tuple string $primitive)))))
+  ))
+
+  (type $primitive' (variant
+    (case "nil")
+    (case "boolean" bool)
+    (case "integer" s64)
+    (case "float" float64)
+    (case "string" string)
+    (case "buffer" s64)
+    (case "window" s64)
+    (case "tabpage" s64)
+  ))
+  (export $primitive "primitive" (type $primitive'))
+  (type $object' (variant
+    (case "nil")
+    (case "boolean" bool)
+    (case "integer" s64)
+    (case "float" float64)
+    (case "string" string)
+    (case "array" (list $primitive))
+    (case "dictionary" (list (tuple string $primitive)))
+    (case "buffer" s64)
+    (case "window" s64)
+    (case "tabpage" s64)
+  ))
+  (export $object "object" (type $object'))
+
+  ;; The memory and a bump allocator which never frees.
+  (core module $libc
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 1024))
+    (func (export "cabi_realloc") (param i32 i32) (param $align i32) (param $size i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get $align) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get $align))))
+      (global.set $heap (i32.add (local.get $ptr) (local.get $size)))
+      (if (i32.gt_u (global.get $heap) (i32.shl (memory.size) (i32.const 16)))
+        (then (drop (memory.grow (i32.const 1)))))
+      (local.get $ptr))
+  )
+  (core instance $libc (instantiate $libc))
+
+  (core func $log (canon lower (func $log "log") (memory $libc "memory")))
+
+  ;; The object returned by `run` (nil) is stored at address 0, the fields of the entry at 64, and
+  ;; the name of the field at 128.
+  (core module $main
+    (import "libc" "memory" (memory 1))
+    (import "nvim" "log" (func $log (param i32 i32 i32 i32 i32)))
+    (data (i32.const 128) "length")
+    (func (export "run") (param $args i32) (param $len i32) (result i32)
+      (i32.store8 (i32.const 0) (i32.const 0))
+      ;; The first argument must be a string.
+      (if (i32.or
+            (i32.eqz (local.get $len))
+            (i32.ne (i32.load8_u (local.get $args)) (i32.const 4)))
+        (then unreachable))
+      ;; ("length", integer(len))
+      (i32.store (i32.const 64) (i32.const 128))
+      (i32.store (i32.const 68) (i32.const 6))
+      (i32.store8 (i32.const 72) (i32.const 2))
+      (i64.store (i32.const 80) (i64.extend_i32_u (i32.load offset=12 (local.get $args))))
+      (call $log
+        (i32.const 3)
+        (i32.load offset=8 (local.get $args))
+        (i32.load offset=12 (local.get $args))
+        (i32.const 64)
+        (i32.const 1))
+      (i32.const 0))
+  )
+  (core instance $main (instantiate $main
+    (with "libc" (instance $libc))
+    (with "nvim" (instance (export "log" (func $log))))
+  ))
+
+  (func (export "run") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "run")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+)
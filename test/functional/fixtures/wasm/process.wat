@@ -0,0 +1,323 @@
+;; A WASM plugin using the `process` interface.
+;;
+;; `spawn(argv, cwd, env, flags)` spawns a process and returns its ID or an error message. `cwd` is
+;; a string or nil, `env` a dictionary of strings or nil, and `flags` opens stdin (1), stdout (2)
+;; and stderr (4). `write(id, data)`, `close-stdin(id)` and `kill(id)` return nil or an error
+;; message. The hooks append `[process, 'stdout' or 'stderr', data]` and `[process, 'exit',
+;; status]` to `g:wasm_process_events`.
+(component
+  (import "nvim:api/process@1.12.0" (instance $process
+    (type $spawn-options' (record
+      (field "argv" (list string))
+      (field "cwd" (option string))
+      (field "env" (list (tuple string string)))
+      (field "stdin" bool)
+      (field "stdout" bool)
+      (field "stderr" bool)
+    ))
+    (export "spawn-options" (type $spawn-options (eq $spawn-options')))
+    (export "spawn" (func (param "options" $spawn-options) (result (result s64 (error string)))))
+    (export "write" (func
+      (param "process" s64) (param "data" (list u8)) (result (result (error string)))))
+    (export "close-stdin" (func (param "process" s64) (result (result (error string)))))
+    (export "kill" (func (param "process" s64) (result (result (error string)))))
+  ))
+  (import "nvim:api/nvim-api@1.12.0" (instance $api
+    (type $primitive' (variant
+      (case "nil")
+      (case "boolean" bool)
+      (case "integer" s64)
+      (case "float" float64)
+      (case "string" string)
+      (case "buffer" s64)
+      (case "window" s64)
+      (case "tabpage" s64)
+    ))
+    (export "primitive" (type $primitive (eq $primitive')))
+    (type $object' (variant
+      (case "nil")
+      (case "boolean" bool)
+      (case "integer" s64)
+      (case "float" float64)
+      (case "string" string)
+      (case "array" (list $primitive))
+      (case "dictionary" (list (tuple string $primitive)))
+      (case "buffer" s64)
+      (case "window" s64)
+      (case "tabpage" s64)
+    ))
+    (export "object" (type $object (eq $object')))
+    (export "nvim-set-var" (func
+      (param "name" string) (param "value" $object) (result (result (error string)))))
+    (export "nvim-command" (func (param "command" string) (result (result (error string)))))
+  ))
+
+  (type $primitive' (variant
+    (case "nil")
+    (case "boolean" bool)
+    (case "integer" s64)
+    (case "float" float64)
+    (case "string" string)
+    (case "buffer" s64)
+    (case "window" s64)
+    (case "tabpage" s64)
+  ))
+  (export $primitive "primitive" (type $primitive'))
+  (type $object' (variant
+    (case "nil")
+    (case "boolean" bool)
+    (case "integer" s64)
+    (case "float" float64)
+    (case "string" string)
+    (case "array" (list $primitive))
+    (case "dictionary" (list (tuple string $primitive)))
+    (case "buffer" s64)
+    (case "window" s64)
+    (case "tabpage" s64)
+  ))
+  (export $object "object" (type $object'))
+
+  ;; The memory and a bump allocator which never frees.
+  (core module $libc
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 1024))
+    (func (export "cabi_realloc") (param i32 i32) (param $align i32) (param $size i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get $align) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get $align))))
+      (global.set $heap (i32.add (local.get $ptr) (local.get $size)))
+      (if (i32.gt_u (global.get $heap) (i32.shl (memory.size) (i32.const 16)))
+        (then (drop (memory.grow (i32.const 1)))))
+      (local.get $ptr))
+  )
+  (core instance $libc (instantiate $libc))
+
+  (core func $spawn (canon lower (func $process "spawn")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (core func $write (canon lower (func $process "write")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (core func $close_stdin (canon lower (func $process "close-stdin")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (core func $kill (canon lower (func $process "kill")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (core func $set_var (canon lower (func $api "nvim-set-var")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (core func $command (canon lower (func $api "nvim-command")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+
+  ;; The object returned by the functions is stored at address 0, the results of the imports at 32,
+  ;; the names of the variables at 64 and 96, the commands at 128, 256 and 384, the argv of `spawn`
+  ;; at 512 (at most 32 arguments) and its env at 768 (at most 16 variables).
+  (core module $main
+    (import "libc" "memory" (memory 1))
+    (import "nvim" "spawn" (func $spawn
+      (param i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32)))
+    (import "nvim" "write" (func $write (param i64 i32 i32 i32)))
+    (import "nvim" "close-stdin" (func $close_stdin (param i64 i32)))
+    (import "nvim" "kill" (func $kill (param i64 i32)))
+    (import "nvim" "set-var" (func $set_var (param i32 i32 i32 i64 i32 i32)))
+    (import "nvim" "command" (func $command (param i32 i32 i32)))
+    (data (i32.const 64) "wasm_process_id")
+    (data (i32.const 96) "wasm_process_data")
+    (data (i32.const 128)
+      "let g:wasm_process_events += [[g:wasm_process_id, 'stdout', g:wasm_process_data]]")
+    (data (i32.const 256)
+      "let g:wasm_process_events += [[g:wasm_process_id, 'stderr', g:wasm_process_data]]")
+    (data (i32.const 384)
+      "let g:wasm_process_events += [[g:wasm_process_id, 'exit', g:wasm_process_data]]")
+
+    ;; Returns the address of the argument `n` of `args`, which must be of type `disc`.
+    (func $arg (param $args i32) (param $len i32) (param $n i32) (param $disc i32) (result i32)
+      (local $arg i32)
+      (if (i32.ge_u (local.get $n) (local.get $len))
+        (then unreachable))
+      (local.set $arg (i32.add (local.get $args) (i32.mul (local.get $n) (i32.const 16))))
+      (if (i32.ne (i32.load8_u (local.get $arg)) (local.get $disc))
+        (then unreachable))
+      (local.get $arg))
+
+    ;; Returns the process ID passed as the first argument.
+    (func $process_id (param $args i32) (param $len i32) (result i64)
+      (i64.load offset=8
+        (call $arg (local.get $args) (local.get $len) (i32.const 0) (i32.const 2))))
+
+    ;; Returns nil, or the error message if the `result<_, string>` at 32 is an error.
+    (func $return_unit (result i32)
+      (if (i32.load8_u (i32.const 32))
+        (then
+          (i32.store8 (i32.const 0) (i32.const 4))
+          (i32.store (i32.const 8) (i32.load (i32.const 36)))
+          (i32.store (i32.const 12) (i32.load (i32.const 40))))
+        (else
+          (i32.store8 (i32.const 0) (i32.const 0))))
+      (i32.const 0))
+
+    ;; Traps if the API call whose result is at 32 failed.
+    (func $check
+      (if (i32.load8_u (i32.const 32))
+        (then unreachable)))
+
+    ;; Sets `g:wasm_process_id` to `process`, `g:wasm_process_data` to the object `data`, and runs
+    ;; the command `cmd`.
+    (func $record (param $process i64) (param $disc i32) (param $data i64) (param $data_len i32)
+        (param $cmd i32) (param $cmd_len i32)
+      (call $set_var (i32.const 64) (i32.const 15) (i32.const 2) (local.get $process) (i32.const 0)
+        (i32.const 32))
+      (call $check)
+      (call $set_var (i32.const 96) (i32.const 17) (local.get $disc) (local.get $data)
+        (local.get $data_len) (i32.const 32))
+      (call $check)
+      (call $command (local.get $cmd) (local.get $cmd_len) (i32.const 32))
+      (call $check))
+
+    (func (export "spawn") (param $args i32) (param $len i32) (result i32)
+      (local $arg i32)
+      (local $list i32)
+      (local $n i32)
+      (local $argc i32)
+      (local $envc i32)
+      (local $i i32)
+      (local $cwd_disc i32)
+      (local $cwd_ptr i32)
+      (local $cwd_len i32)
+      (local $flags i32)
+      (local.set $flags (i32.wrap_i64 (i64.load offset=8
+        (call $arg (local.get $args) (local.get $len) (i32.const 3) (i32.const 2)))))
+      ;; argv: an array of strings, copied to 512 as a list<string>.
+      (local.set $arg (call $arg (local.get $args) (local.get $len) (i32.const 0) (i32.const 5)))
+      (local.set $list (i32.load offset=8 (local.get $arg)))
+      (local.set $argc (i32.load offset=12 (local.get $arg)))
+      (if (i32.gt_u (local.get $argc) (i32.const 32))
+        (then unreachable))
+      (local.set $i (i32.const 0))
+      (block $done
+        (loop $copy
+          (br_if $done (i32.ge_u (local.get $i) (local.get $argc)))
+          (local.set $n (i32.add (local.get $list) (i32.mul (local.get $i) (i32.const 16))))
+          (if (i32.ne (i32.load8_u (local.get $n)) (i32.const 4))
+            (then unreachable))
+          (i32.store offset=512 (i32.mul (local.get $i) (i32.const 8))
+            (i32.load offset=8 (local.get $n)))
+          (i32.store offset=516 (i32.mul (local.get $i) (i32.const 8))
+            (i32.load offset=12 (local.get $n)))
+          (local.set $i (i32.add (local.get $i) (i32.const 1)))
+          (br $copy)))
+      ;; cwd: a string or nil.
+      (local.set $arg (i32.add (local.get $args) (i32.const 16)))
+      (if (i32.eq (i32.load8_u (local.get $arg)) (i32.const 4))
+        (then
+          (local.set $cwd_disc (i32.const 1))
+          (local.set $cwd_ptr (i32.load offset=8 (local.get $arg)))
+          (local.set $cwd_len (i32.load offset=12 (local.get $arg)))))
+      ;; env: a dictionary of strings or nil, copied to 768 as a list<tuple<string, string>>.
+      (local.set $arg (i32.add (local.get $args) (i32.const 32)))
+      (if (i32.eq (i32.load8_u (local.get $arg)) (i32.const 6))
+        (then
+          (local.set $list (i32.load offset=8 (local.get $arg)))
+          (local.set $envc (i32.load offset=12 (local.get $arg)))))
+      (if (i32.gt_u (local.get $envc) (i32.const 16))
+        (then unreachable))
+      (local.set $i (i32.const 0))
+      (block $done
+        (loop $copy
+          (br_if $done (i32.ge_u (local.get $i) (local.get $envc)))
+          (local.set $n (i32.add (local.get $list) (i32.mul (local.get $i) (i32.const 24))))
+          (if (i32.ne (i32.load8_u offset=8 (local.get $n)) (i32.const 4))
+            (then unreachable))
+          (i32.store offset=768 (i32.mul (local.get $i) (i32.const 16))
+            (i32.load (local.get $n)))
+          (i32.store offset=772 (i32.mul (local.get $i) (i32.const 16))
+            (i32.load offset=4 (local.get $n)))
+          (i32.store offset=776 (i32.mul (local.get $i) (i32.const 16))
+            (i32.load offset=16 (local.get $n)))
+          (i32.store offset=780 (i32.mul (local.get $i) (i32.const 16))
+            (i32.load offset=20 (local.get $n)))
+          (local.set $i (i32.add (local.get $i) (i32.const 1)))
+          (br $copy)))
+
+      (call $spawn
+        (i32.const 512) (local.get $argc)
+        (local.get $cwd_disc) (local.get $cwd_ptr) (local.get $cwd_len)
+        (i32.const 768) (local.get $envc)
+        (i32.and (local.get $flags) (i32.const 1))
+        (i32.and (i32.shr_u (local.get $flags) (i32.const 1)) (i32.const 1))
+        (i32.and (i32.shr_u (local.get $flags) (i32.const 2)) (i32.const 1))
+        (i32.const 32))
+      (if (i32.load8_u (i32.const 32))
+        (then
+          (i32.store8 (i32.const 0) (i32.const 4))
+          (i32.store (i32.const 8) (i32.load (i32.const 40)))
+          (i32.store (i32.const 12) (i32.load (i32.const 44))))
+        (else
+          (i32.store8 (i32.const 0) (i32.const 2))
+          (i64.store (i32.const 8) (i64.load (i32.const 40)))))
+      (i32.const 0))
+
+    (func (export "write") (param $args i32) (param $len i32) (result i32)
+      (local $data i32)
+      (local.set $data (call $arg (local.get $args) (local.get $len) (i32.const 1) (i32.const 4)))
+      (call $write
+        (call $process_id (local.get $args) (local.get $len))
+        (i32.load offset=8 (local.get $data)) (i32.load offset=12 (local.get $data))
+        (i32.const 32))
+      (call $return_unit))
+
+    (func (export "close-stdin") (param $args i32) (param $len i32) (result i32)
+      (call $close_stdin
+        (call $process_id (local.get $args) (local.get $len))
+        (i32.const 32))
+      (call $return_unit))
+
+    (func (export "kill") (param $args i32) (param $len i32) (result i32)
+      (call $kill
+        (call $process_id (local.get $args) (local.get $len))
+        (i32.const 32))
+      (call $return_unit))
+
+    (func (export "on-process-stdout") (param $process i64) (param $data i32) (param $len i32)
+      (call $record (local.get $process) (i32.const 4) (i64.extend_i32_u (local.get $data))
+        (local.get $len) (i32.const 128) (i32.const 81)))
+
+    (func (export "on-process-stderr") (param $process i64) (param $data i32) (param $len i32)
+      (call $record (local.get $process) (i32.const 4) (i64.extend_i32_u (local.get $data))
+        (local.get $len) (i32.const 256) (i32.const 81)))
+
+    (func (export "on-process-exit") (param $process i64) (param $status i32)
+      (call $record (local.get $process) (i32.const 2) (i64.extend_i32_s (local.get $status))
+        (i32.const 0) (i32.const 384) (i32.const 79)))
+  )
+  (core instance $main (instantiate $main
+    (with "libc" (instance $libc))
+    (with "nvim" (instance
+      (export "spawn" (func $spawn))
+      (export "write" (func $write))
+      (export "close-stdin" (func $close_stdin))
+      (export "kill" (func $kill))
+      (export "set-var" (func $set_var))
+      (export "command" (func $command))
+    ))
+  ))
+
+  (func (export "spawn") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "spawn")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "write") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "write")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "close-stdin") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "close-stdin")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "kill") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "kill")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "on-process-stdout") (param "process" s64) (param "data" (list u8))
+    (canon lift (core func $main "on-process-stdout")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "on-process-stderr") (param "process" s64) (param "data" (list u8))
+    (canon lift (core func $main "on-process-stderr")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "on-process-exit") (param "process" s64) (param "status" s32)
+    (canon lift (core func $main "on-process-exit")))
+)
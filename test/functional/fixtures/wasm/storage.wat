@@ -0,0 +1,291 @@
+;; A WASM plugin using the `storage` interface, declaring the name `storage-test`.
+;;
+;; `set(key, value)` stores the object `value` and `set-bytes(key, data)` the bytes of the string
+;; `data`, and both return nil or an error message. `get(key)` returns nil if `key` is not set, the
+;; object, `['bytes', data]` for bytes, or `['error', message]`. `delete(key)` returns whether `key`
+;; was set and `list()` the keys, or an error message.
+(component
+  (import "nvim:api/storage@1.12.0" (instance $storage
+    (type $primitive' (variant
+      (case "nil")
+      (case "boolean" bool)
+      (case "integer" s64)
+      (case "float" float64)
+      (case "string" string)
+      (case "buffer" s64)
+      (case "window" s64)
+      (case "tabpage" s64)
+    ))
+    (export "primitive" (type $primitive (eq $primitive')))
+    (type $object' (variant
+      (case "nil")
+      (case "boolean" bool)
+      (case "integer" s64)
+      (case "float" float64)
+      (case "string" string)
+      (case "array" (list $primitive))
+      (case "dictionary" (list (tuple string $primitive)))
+      (case "buffer" s64)
+      (case "window" s64)
+      (case "tabpage" s64)
+    ))
+    (export "object" (type $object (eq $object')))
+    (type $value' (variant
+      (case "bytes" (list u8))
+      (case "object" $object)
+    ))
+    (export "value" (type $value (eq $value')))
+    (export "get" (func (param "key" string) (result (result (option $value) (error string)))))
+    (export "set" (func
+      (param "key" string) (param "value" $value) (result (result (error string)))))
+    (export "delete" (func (param "key" string) (result (result bool (error string)))))
+    (export "list" (func (result (result (list string) (error string)))))
+  ))
+
+  (type $primitive' (variant
+    (case "nil")
+    (case "boolean" bool)
+    (case "integer" s64)
+    (case "float" float64)
+    (case "string" string)
+    (case "buffer" s64)
+    (case "window" s64)
+    (case "tabpage" s64)
+  ))
+  (export $primitive "primitive" (type $primitive'))
+  (type $object' (variant
+    (case "nil")
+    (case "boolean" bool)
+    (case "integer" s64)
+    (case "float" float64)
+    (case "string" string)
+    (case "array" (list $primitive))
+    (case "dictionary" (list (tuple string $primitive)))
+    (case "buffer" s64)
+    (case "window" s64)
+    (case "tabpage" s64)
+  ))
+  (export $object "object" (type $object'))
+
+  ;; The memory and a bump allocator which never frees, growing the memory as needed.
+  (core module $libc
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 1024))
+    (func (export "cabi_realloc") (param i32 i32) (param $align i32) (param $size i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get $align) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get $align))))
+      (global.set $heap (i32.add (local.get $ptr) (local.get $size)))
+      (if (i32.gt_u (global.get $heap) (i32.shl (memory.size) (i32.const 16)))
+        (then
+          (if (i32.lt_s
+                (memory.grow (i32.sub
+                  (i32.shr_u (i32.add (global.get $heap) (i32.const 0xffff)) (i32.const 16))
+                  (memory.size)))
+                (i32.const 0))
+            (then unreachable))))
+      (local.get $ptr))
+  )
+  (core instance $libc (instantiate $libc))
+
+  (core func $get (canon lower (func $storage "get")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (core func $set (canon lower (func $storage "set")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (core func $delete (canon lower (func $storage "delete")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (core func $list (canon lower (func $storage "list")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+
+  ;; The object returned by the functions is stored at address 0, the results of the imports at 32,
+  ;; the strings "bytes" and "error" at 96 and 112, and the arrays returned by `get` at 128.
+  (core module $main
+    (@custom "nvim-plugin-name" "storage-test")
+    (import "libc" "memory" (memory 1))
+    (import "libc" "cabi_realloc" (func $realloc (param i32 i32 i32 i32) (result i32)))
+    (import "nvim" "get" (func $get (param i32 i32 i32)))
+    (import "nvim" "set" (func $set (param i32 i32 i32 i32 i64 i32 i32)))
+    (import "nvim" "delete" (func $delete (param i32 i32 i32)))
+    (import "nvim" "list" (func $list (param i32)))
+    (data (i32.const 96) "bytes")
+    (data (i32.const 112) "error")
+
+    ;; Returns the address of the argument `n` of `args`, which must be of type `disc`.
+    (func $arg (param $args i32) (param $len i32) (param $n i32) (param $disc i32) (result i32)
+      (local $arg i32)
+      (if (i32.ge_u (local.get $n) (local.get $len))
+        (then unreachable))
+      (local.set $arg (i32.add (local.get $args) (i32.mul (local.get $n) (i32.const 16))))
+      (if (i32.ne (i32.load8_u (local.get $arg)) (local.get $disc))
+        (then unreachable))
+      (local.get $arg))
+
+    ;; Returns the error message if the `result<_, string>` at 32 is an error, or else false.
+    (func $return_error (result i32)
+      (if (result i32) (i32.load8_u (i32.const 32))
+        (then
+          (i32.store8 (i32.const 0) (i32.const 4))
+          (i32.store (i32.const 8) (i32.load (i32.const 36)))
+          (i32.store (i32.const 12) (i32.load (i32.const 40)))
+          (i32.const 1))
+        (else
+          (i32.const 0))))
+
+    ;; Returns the array `[tag, string]` where `tag` is "bytes" or "error".
+    (func $return_tagged (param $tag i32) (param $ptr i32) (param $len i32)
+      (i32.store8 (i32.const 128) (i32.const 4))
+      (i32.store (i32.const 136) (local.get $tag))
+      (i32.store (i32.const 140) (i32.const 5))
+      (i32.store8 (i32.const 144) (i32.const 4))
+      (i32.store (i32.const 152) (local.get $ptr))
+      (i32.store (i32.const 156) (local.get $len))
+      (i32.store8 (i32.const 0) (i32.const 5))
+      (i32.store (i32.const 8) (i32.const 128))
+      (i32.store (i32.const 12) (i32.const 2)))
+
+    (func (export "get") (param $args i32) (param $len i32) (result i32)
+      (local $key i32)
+      (local.set $key (call $arg (local.get $args) (local.get $len) (i32.const 0) (i32.const 4)))
+      (call $get
+        (i32.load offset=8 (local.get $key)) (i32.load offset=12 (local.get $key))
+        (i32.const 32))
+      (if (i32.load8_u (i32.const 32))
+        (then
+          (call $return_tagged
+            (i32.const 112) (i32.load (i32.const 40)) (i32.load (i32.const 44))))
+        (else
+          (if (i32.eqz (i32.load8_u (i32.const 40)))
+            (then
+              (i32.store8 (i32.const 0) (i32.const 0)))
+            (else
+              (if (i32.eqz (i32.load8_u (i32.const 48)))
+                (then
+                  (call $return_tagged
+                    (i32.const 96) (i32.load (i32.const 56)) (i32.load (i32.const 60))))
+                (else
+                  (i64.store (i32.const 0) (i64.load (i32.const 56)))
+                  (i64.store (i32.const 8) (i64.load (i32.const 64)))))))))
+      (i32.const 0))
+
+    (func (export "set") (param $args i32) (param $len i32) (result i32)
+      (local $key i32)
+      (local $value i32)
+      (local $disc i32)
+      (local $payload i64)
+      (local $payload_len i32)
+      (local.set $key (call $arg (local.get $args) (local.get $len) (i32.const 0) (i32.const 4)))
+      (if (i32.lt_u (local.get $len) (i32.const 2))
+        (then unreachable))
+      (local.set $value (i32.add (local.get $args) (i32.const 16)))
+      (local.set $disc (i32.load8_u (local.get $value)))
+      ;; Flattens the object: booleans are bytes, strings and lists are pointers and lengths.
+      (if (i32.eq (local.get $disc) (i32.const 1))
+        (then
+          (local.set $payload (i64.load8_u offset=8 (local.get $value))))
+        (else
+          (if (i32.and
+                (i32.ge_u (local.get $disc) (i32.const 4))
+                (i32.le_u (local.get $disc) (i32.const 6)))
+            (then
+              (local.set $payload (i64.extend_i32_u (i32.load offset=8 (local.get $value))))
+              (local.set $payload_len (i32.load offset=12 (local.get $value))))
+            (else
+              (if (local.get $disc)
+                (then
+                  (local.set $payload (i64.load offset=8 (local.get $value)))))))))
+      (call $set
+        (i32.load offset=8 (local.get $key)) (i32.load offset=12 (local.get $key))
+        (i32.const 1) (local.get $disc) (local.get $payload) (local.get $payload_len)
+        (i32.const 32))
+      (if (i32.eqz (call $return_error))
+        (then
+          (i32.store8 (i32.const 0) (i32.const 0))))
+      (i32.const 0))
+
+    (func (export "set-bytes") (param $args i32) (param $len i32) (result i32)
+      (local $key i32)
+      (local $data i32)
+      (local.set $key (call $arg (local.get $args) (local.get $len) (i32.const 0) (i32.const 4)))
+      (local.set $data (call $arg (local.get $args) (local.get $len) (i32.const 1) (i32.const 4)))
+      (call $set
+        (i32.load offset=8 (local.get $key)) (i32.load offset=12 (local.get $key))
+        (i32.const 0) (i32.load offset=8 (local.get $data))
+        (i64.extend_i32_u (i32.load offset=12 (local.get $data))) (i32.const 0)
+        (i32.const 32))
+      (if (i32.eqz (call $return_error))
+        (then
+          (i32.store8 (i32.const 0) (i32.const 0))))
+      (i32.const 0))
+
+    (func (export "delete") (param $args i32) (param $len i32) (result i32)
+      (local $key i32)
+      (local.set $key (call $arg (local.get $args) (local.get $len) (i32.const 0) (i32.const 4)))
+      (call $delete
+        (i32.load offset=8 (local.get $key)) (i32.load offset=12 (local.get $key))
+        (i32.const 32))
+      (if (i32.eqz (call $return_error))
+        (then
+          (i32.store8 (i32.const 0) (i32.const 1))
+          (i32.store8 (i32.const 8) (i32.load8_u (i32.const 36)))))
+      (i32.const 0))
+
+    (func (export "list") (param $args i32) (param $len i32) (result i32)
+      (local $keys i32)
+      (local $count i32)
+      (local $array i32)
+      (local $i i32)
+      (call $list (i32.const 32))
+      (if (call $return_error)
+        (then (return (i32.const 0))))
+      ;; Converts the list<string> to an array of string objects.
+      (local.set $keys (i32.load (i32.const 36)))
+      (local.set $count (i32.load (i32.const 40)))
+      (local.set $array (call $realloc
+        (i32.const 0) (i32.const 0) (i32.const 8) (i32.mul (local.get $count) (i32.const 16))))
+      (local.set $i (i32.const 0))
+      (block $done
+        (loop $copy
+          (br_if $done (i32.ge_u (local.get $i) (local.get $count)))
+          (i32.store8
+            (i32.add (local.get $array) (i32.mul (local.get $i) (i32.const 16))) (i32.const 4))
+          (i32.store offset=8
+            (i32.add (local.get $array) (i32.mul (local.get $i) (i32.const 16)))
+            (i32.load (i32.add (local.get $keys) (i32.mul (local.get $i) (i32.const 8)))))
+          (i32.store offset=12
+            (i32.add (local.get $array) (i32.mul (local.get $i) (i32.const 16)))
+            (i32.load offset=4 (i32.add (local.get $keys) (i32.mul (local.get $i) (i32.const 8)))))
+          (local.set $i (i32.add (local.get $i) (i32.const 1)))
+          (br $copy)))
+      (i32.store8 (i32.const 0) (i32.const 5))
+      (i32.store (i32.const 8) (local.get $array))
+      (i32.store (i32.const 12) (local.get $count))
+      (i32.const 0))
+  )
+  (core instance $main (instantiate $main
+    (with "libc" (instance $libc))
+    (with "nvim" (instance
+      (export "get" (func $get))
+      (export "set" (func $set))
+      (export "delete" (func $delete))
+      (export "list" (func $list))
+    ))
+  ))
+
+  (func (export "get") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "get")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "set") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "set")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "set-bytes") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "set-bytes")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "delete") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "delete")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "list") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "list")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+)
@@ -0,0 +1,159 @@
+;; A WASM plugin using the `timer` interface.
+;;
+;; `start(ms, repeat, callback_id)` starts a timer and returns its ID, `stop(id)` stops a timer.
+;; The `on-timer` hook appends the callback ID to `g:wasm_timer_calls`, and stops the timer when the
+;; callback ID is 0.
+(component
+  (import "nvim:api/timer@1.12.0" (instance $timer
+    (export "start" (func
+      (param "ms" u64) (param "repeat" s32) (param "callback-id" s64) (result s64)))
+    (export "stop" (func (param "id" s64)))
+  ))
+  (import "nvim:api/nvim-api@1.12.0" (instance $api
+    (type $primitive' (variant
+      (case "nil")
+      (case "boolean" bool)
+      (case "integer" s64)
+      (case "float" float64)
+      (case "string" string)
+      (case "buffer" s64)
+      (case "window" s64)
+      (case "tabpage" s64)
+    ))
+    (export "primitive" (type $primitive (eq $primitive')))
+    (type $object' (variant
+      (case "nil")
+      (case "boolean" bool)
+      (case "integer" s64)
+      (case "float" float64)
+      (case "string" string)
+      (case "array" (list $primitive))
+      (case "dictionary" (list (tuple string $primitive)))
+      (case "buffer" s64)
+      (case "window" s64)
+      (case "tabpage" s64)
+    ))
+    (export "object" (type $object (eq $object')))
+    (export "nvim-set-var" (func
+      (param "name" string) (param "value" $object) (result (result (error string)))))
+    (export "nvim-command" (func (param "command" string) (result (result (error string)))))
+  ))
+
+  (type $primitive' (variant
+    (case "nil")
+    (case "boolean" bool)
+    (case "integer" s64)
+    (case "float" float64)
+    (case "string" string)
+    (case "buffer" s64)
+    (case "window" s64)
+    (case "tabpage" s64)
+  ))
+  (export $primitive "primitive" (type $primitive'))
+  (type $object' (variant
+    (case "nil")
+    (case "boolean" bool)
+    (case "integer" s64)
+    (case "float" float64)
+    (case "string" string)
+    (case "array" (list $primitive))
+    (case "dictionary" (list (tuple string $primitive)))
+    (case "buffer" s64)
+    (case "window" s64)
+    (case "tabpage" s64)
+  ))
+  (export $object "object" (type $object'))
+
+  ;; The memory and a bump allocator which never frees.
+  (core module $libc
+    (memory (export "memory") 1)
+    (global $heap (mut i32) (i32.const 1024))
+    (func (export "cabi_realloc") (param i32 i32) (param $align i32) (param $size i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr
+        (i32.and
+          (i32.add (global.get $heap) (i32.sub (local.get $align) (i32.const 1)))
+          (i32.sub (i32.const 0) (local.get $align))))
+      (global.set $heap (i32.add (local.get $ptr) (local.get $size)))
+      (if (i32.gt_u (global.get $heap) (i32.shl (memory.size) (i32.const 16)))
+        (then (drop (memory.grow (i32.const 1)))))
+      (local.get $ptr))
+  )
+  (core instance $libc (instantiate $libc))
+
+  (core func $start (canon lower (func $timer "start")))
+  (core func $stop (canon lower (func $timer "stop")))
+  (core func $set_var (canon lower (func $api "nvim-set-var")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (core func $command (canon lower (func $api "nvim-command")
+    (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+
+  ;; The object returned by `start` and `stop` is stored at address 0, the results of the API calls
+  ;; at 32, the name of the variable at 128 and the command at 160.
+  (core module $main
+    (import "libc" "memory" (memory 1))
+    (import "nvim" "start" (func $start (param i64 i32 i64) (result i64)))
+    (import "nvim" "stop" (func $stop (param i64)))
+    (import "nvim" "set-var" (func $set_var (param i32 i32 i32 i64 i32 i32)))
+    (import "nvim" "command" (func $command (param i32 i32 i32)))
+    (data (i32.const 128) "wasm_timer_callback")
+    (data (i32.const 160) "let g:wasm_timer_calls += [g:wasm_timer_callback]")
+
+    ;; Returns the integer argument `n` of `args`.
+    (func $arg (param $args i32) (param $len i32) (param $n i32) (result i64)
+      (local $arg i32)
+      (if (i32.ge_u (local.get $n) (local.get $len))
+        (then unreachable))
+      (local.set $arg (i32.add (local.get $args) (i32.mul (local.get $n) (i32.const 16))))
+      (if (i32.ne (i32.load8_u (local.get $arg)) (i32.const 2))
+        (then unreachable))
+      (i64.load offset=8 (local.get $arg)))
+
+    ;; Traps if the API call whose result is at 32 failed.
+    (func $check
+      (if (i32.load8_u (i32.const 32))
+        (then unreachable)))
+
+    (func (export "start") (param $args i32) (param $len i32) (result i32)
+      (i32.store8 (i32.const 0) (i32.const 2))
+      (i64.store (i32.const 8)
+        (call $start
+          (call $arg (local.get $args) (local.get $len) (i32.const 0))
+          (i32.wrap_i64 (call $arg (local.get $args) (local.get $len) (i32.const 1)))
+          (call $arg (local.get $args) (local.get $len) (i32.const 2))))
+      (i32.const 0))
+
+    (func (export "stop") (param $args i32) (param $len i32) (result i32)
+      (call $stop (call $arg (local.get $args) (local.get $len) (i32.const 0)))
+      (i32.store8 (i32.const 0) (i32.const 0))
+      (i32.const 0))
+
+    (func (export "on-timer") (param $timer i64) (param $callback_id i64) (result i32)
+      ;; let g:wasm_timer_callback = callback_id
+      (call $set_var
+        (i32.const 128) (i32.const 19) (i32.const 2) (local.get $callback_id) (i32.const 0)
+        (i32.const 32))
+      (call $check)
+      (call $command (i32.const 160) (i32.const 49) (i32.const 32))
+      (call $check)
+      (i64.eqz (local.get $callback_id)))
+  )
+  (core instance $main (instantiate $main
+    (with "libc" (instance $libc))
+    (with "nvim" (instance
+      (export "start" (func $start))
+      (export "stop" (func $stop))
+      (export "set-var" (func $set_var))
+      (export "command" (func $command))
+    ))
+  ))
+
+  (func (export "start") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "start")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "stop") (param "args" (list $object)) (result $object)
+    (canon lift (core func $main "stop")
+      (memory $libc "memory") (realloc (func $libc "cabi_realloc"))))
+  (func (export "on-timer") (param "timer" s64) (param "callback-id" s64) (result bool)
+    (canon lift (core func $main "on-timer")))
+)
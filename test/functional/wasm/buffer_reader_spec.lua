@@ -0,0 +1,57 @@
+-- Tests for the `buffer-reader` resource of WASM plugins.
+local helpers = require('test.functional.helpers')(after_each)
+
+local clear = helpers.clear
+local command = helpers.command
+local eq = helpers.eq
+local funcs = helpers.funcs
+local meths = helpers.meths
+
+-- `chunks(buffer, max_bytes)` reads the buffer in chunks of at most `max_bytes` and returns the
+-- chunks, or the error message of the reader.
+local plugin = 'test/functional/fixtures/wasm/buffer_reader.wat'
+
+describe('WASM buffer reader', function()
+  local id
+
+  before_each(function()
+    clear()
+    meths.buf_set_lines(0, 0, -1, true, { 'one', 'two', 'three' })
+    id = meths.wasm_load(plugin)
+  end)
+
+  local function chunks(buffer, max_bytes)
+    return meths.wasm_call_func(id, 'chunks', { buffer, max_bytes })
+  end
+
+  it('reads whole lines in chunks of at most max_bytes', function()
+    eq({ 'one\ntwo\n', 'three\n' }, chunks(0, 8))
+    eq({ 'one\ntwo\nthree\n' }, chunks(0, 1000))
+    eq({ 'one\ntwo\nthree\n' }, chunks(0, 14))
+  end)
+
+  it('reads lines longer than max_bytes whole', function()
+    eq({ 'one\n', 'two\n', 'three\n' }, chunks(0, 2))
+    eq({ 'one\n', 'two\n', 'three\n' }, chunks(0, 0))
+  end)
+
+  it('reads the last line of buffers without end of line', function()
+    command('set nofixendofline noendofline')
+    eq({ 'one\ntwo\nthree\n' }, chunks(0, 14))
+  end)
+
+  it('restores NUL bytes', function()
+    meths.buf_set_lines(0, 0, -1, true, { 'a\0b', '' })
+    eq({ 'a\0b\n\n' }, chunks(0, 1000))
+  end)
+
+  it('reads empty lines, and nothing from unloaded buffers', function()
+    command('enew')
+    eq({ '\n' }, chunks(0, 1000))
+    eq({}, chunks(funcs.bufadd('Xunloaded'), 1000))
+  end)
+
+  it('fails for invalid buffers', function()
+    eq('Invalid buffer id: 99', chunks(99, 1000))
+  end)
+end)
@@ -0,0 +1,96 @@
+-- Tests for the buffer update callbacks of WASM plugins.
+local helpers = require('test.functional.helpers')(after_each)
+
+local NIL = helpers.NIL
+local clear = helpers.clear
+local command = helpers.command
+local eq = helpers.eq
+local exec_lua = helpers.exec_lua
+local matches = helpers.matches
+local meths = helpers.meths
+local pcall_err = helpers.pcall_err
+
+-- `run` executes its argument as an Ex command and returns its error message, or nil. The
+-- callbacks log their arguments, returned as a flat list of integers by `events()`, and return
+-- true once `detach()` is called. See the fixture for the format of the log.
+local plugin = 'test/functional/fixtures/wasm/buffer.wat'
+
+describe('WASM buffer updates', function()
+  local id
+
+  before_each(function()
+    clear()
+    meths.buf_set_lines(0, 0, -1, true, { 'a', 'b', 'c' })
+    id = meths.wasm_load(plugin)
+  end)
+
+  local function attach(opts)
+    return meths.wasm_call_func(id, 'run', { ('call nvim_wasm_buf_attach(0, %s)'):format(opts) })
+  end
+
+  local function events()
+    return meths.wasm_call_func(id, 'events', {})
+  end
+
+  it('call on_lines and on_bytes with the events of Lua callbacks', function()
+    eq(NIL, attach('#{on_lines: "on-lines", on_bytes: "on-bytes"}'))
+    -- The same log, from Lua callbacks.
+    exec_lua([[
+      _G.events = {}
+      local function log(...)
+        vim.list_extend(_G.events, { ... })
+      end
+      vim.api.nvim_buf_attach(0, false, {
+        on_lines = function(_, ...)
+          log(1, ...)
+        end,
+        on_bytes = function(_, ...)
+          log(2, ...)
+        end,
+      })
+    ]])
+    meths.buf_set_lines(0, 1, 2, true, { 'X', 'Y' })
+    command('normal! ggx')
+    local expected = exec_lua('return _G.events')
+    eq(2 * (7 + 12), #expected)
+    eq(expected, events())
+  end)
+
+  it('call on_changedtick and on_detach', function()
+    eq(NIL, attach('#{on_changedtick: "on-changedtick", on_detach: "on-detach"}'))
+    meths.buf_set_lines(0, 0, 1, true, { 'x' })
+    command('undo')
+    eq({ 3, 1, meths.buf_get_changedtick(0) }, events())
+    command('enew | bwipeout! 1')
+    eq({ 4, 1 }, events())
+  end)
+
+  it('detach when a callback returns true', function()
+    eq(NIL, attach('#{on_changedtick: "on-changedtick", on_detach: "on-detach"}'))
+    meths.wasm_call_func(id, 'detach', {})
+    meths.buf_set_lines(0, 0, 1, true, { 'x' })
+    command('undo')
+    eq({ 3, 1, meths.buf_get_changedtick(0) }, events())
+    meths.buf_set_lines(0, 0, 1, true, { 'x' })
+    command('undo')
+    eq({}, events())
+  end)
+
+  it('detach after failing 3 times', function()
+    -- `on-changedtick` is not of the type of `on_lines`.
+    eq(NIL, attach('#{on_lines: "on-changedtick", on_bytes: "on-bytes"}'))
+    for i = 1, 3 do
+      matches('The function on%-changedtick is not a function of type '
+        .. 'func%(event: buf%-lines%-event%) %-> bool',
+        pcall_err(meths.buf_set_lines, 0, 0, 1, true, { 'x' .. i }))
+    end
+    meths.buf_set_lines(0, 0, 1, true, { 'y' })
+    -- The `on_bytes` events of the 3 changes before detaching.
+    eq(3 * 12, #events())
+  end)
+
+  it('can only be attached by plugins', function()
+    eq('Can only be called from WASM plugins',
+      pcall_err(meths.wasm_buf_attach, 0, { on_lines = 'on-lines' }))
+  end)
+end)
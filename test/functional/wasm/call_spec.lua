@@ -0,0 +1,56 @@
+-- Tests for nested calls into WASM plugins.
+local helpers = require('test.functional.helpers')(after_each)
+
+local NIL = helpers.NIL
+local clear = helpers.clear
+local command = helpers.command
+local eq = helpers.eq
+local funcs = helpers.funcs
+local matches = helpers.matches
+local meths = helpers.meths
+local assert_alive = helpers.assert_alive
+
+-- The `run` function of this plugin executes its argument as an Ex command, and returns the error
+-- message of the command, or nil.
+local plugin = 'test/functional/fixtures/wasm/command.wat'
+
+describe('WASM plugin', function()
+  local a, b
+
+  before_each(function()
+    clear()
+    a = meths.wasm_load(plugin)
+    b = meths.wasm_load(plugin)
+  end)
+
+  local function run(instance, cmd)
+    return meths.wasm_call_func(instance, 'run', { cmd })
+  end
+
+  it('can call another plugin', function()
+    eq(NIL, run(a, ("call nvim_wasm_call_func(%d, 'run', ['let g:called = 1'])"):format(b)))
+    eq(1, meths.get_var('called'))
+  end)
+
+  it('can trigger an autocommand calling another plugin', function()
+    command(("autocmd User WasmTest call nvim_wasm_call_func(%d, 'run', ['let g:ran = 1'])")
+      :format(b))
+    eq(NIL, run(a, 'doautocmd User WasmTest'))
+    eq(1, meths.get_var('ran'))
+  end)
+
+  it('cannot be called back while running', function()
+    local call_a = ("call nvim_wasm_call_func(%d, 'run', ['let g:called = 1'])"):format(a)
+    matches('Cannot call WASM instance %d+ while it is running %(re%-entrant call%)',
+      run(a, call_a))
+    -- Also through another plugin, which gets the error.
+    eq(NIL, run(a, ('let g:result = nvim_wasm_call_func(%d, "run", ["%s"])'):format(b, call_a)))
+    matches('Cannot call WASM instance %d+ while it is running %(re%-entrant call%)',
+      meths.get_var('result'))
+    eq(0, funcs.exists('g:called'))
+    assert_alive()
+    -- The plugin can still be called.
+    eq(NIL, run(a, 'let g:called = 2'))
+    eq(2, meths.get_var('called'))
+  end)
+end)
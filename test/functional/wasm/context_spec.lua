@@ -0,0 +1,92 @@
+-- Tests for the buffer and window contexts entered by WASM plugins.
+local helpers = require('test.functional.helpers')(after_each)
+
+local NIL = helpers.NIL
+local clear = helpers.clear
+local command = helpers.command
+local eq = helpers.eq
+local funcs = helpers.funcs
+local matches = helpers.matches
+local meths = helpers.meths
+local pcall_err = helpers.pcall_err
+
+-- The `run` function of this plugin executes its argument as an Ex command, and returns the error
+-- message of the command, or nil.
+local plugin = 'test/functional/fixtures/wasm/command.wat'
+
+describe('WASM plugin contexts', function()
+  local a, b, buf
+
+  before_each(function()
+    clear()
+    a = meths.wasm_load(plugin)
+    b = meths.wasm_load(plugin)
+    buf = funcs.bufadd('Xother')
+    funcs.bufload(buf)
+  end)
+
+  local function run(instance, cmd)
+    return meths.wasm_call_func(instance, 'run', { cmd })
+  end
+
+  it('enter a buffer until it is restored', function()
+    eq(NIL, run(a, ('let g:ctx = nvim_wasm_buf_enter(%d) | let g:inside = bufnr()'
+      .. ' | call nvim_wasm_restore_context(g:ctx) | let g:after = bufnr()'):format(buf)))
+    eq(buf, meths.get_var('inside'))
+    eq(1, meths.get_var('after'))
+    eq(1, funcs.bufnr())
+  end)
+
+  it('enter a window until it is restored', function()
+    command('split')
+    local win = funcs.win_getid()
+    local other = funcs.win_getid(2)
+    eq(NIL, run(a, ('let g:ctx = nvim_wasm_win_enter(%d) | let g:inside = win_getid()'
+      .. ' | call nvim_wasm_restore_context(g:ctx) | let g:after = win_getid()'):format(other)))
+    eq(other, meths.get_var('inside'))
+    eq(win, meths.get_var('after'))
+    eq(win, funcs.win_getid())
+  end)
+
+  it('are restored when the plugin call returns', function()
+    command('split')
+    local win = funcs.win_getid()
+    eq(NIL, run(a, ('call nvim_wasm_win_enter(%d) | call nvim_wasm_buf_enter(%d)')
+      :format(funcs.win_getid(2), buf)))
+    eq(win, funcs.win_getid())
+    eq(1, funcs.bufnr())
+  end)
+
+  it('are restored innermost first', function()
+    matches('Invalid context %(not the innermost one of the call%): 1$',
+      run(a, ('let g:outer = nvim_wasm_buf_enter(%d) | let g:inner = nvim_wasm_buf_enter(1)'
+        .. ' | call nvim_wasm_restore_context(g:outer)'):format(buf)))
+    eq(1, funcs.bufnr())
+  end)
+
+  it('cannot be restored by a plugin called by the plugin', function()
+    local restore = ('nvim_wasm_call_func(%d, "run", ["call nvim_wasm_restore_context(g:ctx)"])')
+      :format(b)
+    eq(NIL, run(a, ('let g:ctx = nvim_wasm_buf_enter(%d) | let g:result = %s'
+      .. ' | let g:inside = bufnr()'):format(buf, restore)))
+    matches('Invalid context %(not the innermost one of the call%): 1$',
+      meths.get_var('result'))
+    eq(buf, meths.get_var('inside'))
+    eq(1, funcs.bufnr())
+    -- The called plugin can restore its own contexts.
+    local enter = ('nvim_wasm_call_func(%d, "run", ["let g:ctx = nvim_wasm_buf_enter(1) '
+      .. '| let g:inner = bufnr() | call nvim_wasm_restore_context(g:ctx)"])'):format(b)
+    eq(NIL, run(a, ('call nvim_wasm_buf_enter(%d) | let g:result = %s | let g:after = bufnr()')
+      :format(buf, enter)))
+    eq(NIL, meths.get_var('result'))
+    eq(1, meths.get_var('inner'))
+    eq(buf, meths.get_var('after'))
+  end)
+
+  it('can only be entered by plugins', function()
+    eq('Can only be called from WASM plugins', pcall_err(meths.wasm_buf_enter, buf))
+    eq('Can only be called from WASM plugins', pcall_err(meths.wasm_win_enter, 0))
+    eq('Invalid context (not the innermost one of the call): 1',
+      pcall_err(meths.wasm_restore_context, 1))
+  end)
+end)
@@ -0,0 +1,79 @@
+-- Tests for the decoration providers of WASM plugins.
+local helpers = require('test.functional.helpers')(after_each)
+local Screen = require('test.functional.ui.screen')
+
+local NIL = helpers.NIL
+local clear = helpers.clear
+local command = helpers.command
+local eq = helpers.eq
+local funcs = helpers.funcs
+local matches = helpers.matches
+local meths = helpers.meths
+local pcall_err = helpers.pcall_err
+
+-- `run` executes its argument as an Ex command and returns its error message, or nil.
+-- `setup(ns_id, hl_id)` sets the highlight added by the `on-line` hook to the first character of
+-- each line, and `lines()` returns how many times `on-line` was called.
+local plugin = 'test/functional/fixtures/wasm/decoration.wat'
+
+describe('WASM decoration provider', function()
+  local screen, id, ns, hl
+
+  before_each(function()
+    clear()
+    screen = Screen.new(20, 4)
+    screen:attach()
+    screen:set_default_attr_ids({
+      [1] = { bold = true, foreground = Screen.colors.Blue },
+      [2] = { foreground = Screen.colors.Grey100, background = Screen.colors.Red },
+    })
+    meths.buf_set_lines(0, 0, -1, true, { 'foo', 'bar' })
+    id = meths.wasm_load(plugin)
+    ns = meths.create_namespace('wasm')
+    hl = funcs.hlID('ErrorMsg')
+    meths.wasm_call_func(id, 'setup', { ns, hl })
+  end)
+
+  local function set_provider(opts)
+    return meths.wasm_call_func(id, 'run', {
+      ('call nvim_wasm_set_decoration_provider(%d, %s)'):format(ns, opts),
+    })
+  end
+
+  local function lines()
+    return meths.wasm_call_func(id, 'lines', {})
+  end
+
+  it('highlights the redrawn lines with ephemeral highlights', function()
+    eq(NIL, set_provider('#{on_start: "on-start", on_win: "on-win", on_line: "on-line",'
+      .. ' on_end: "on-end"}'))
+    screen:expect([[
+      {2:^f}oo                 |
+      {2:b}ar                 |
+      {1:~                   }|
+                          |
+    ]])
+    local count = lines()
+    assert(count >= 2)
+    command('redraw!')
+    assert(lines() > count)
+  end)
+
+  it('fails for hooks the plugin does not export', function()
+    matches('The plugin exports no function nope of type func%(s64, s64, s64%) %-> bool$',
+      set_provider('#{on_line: "nope"}'))
+    matches('The plugin exports no function bad%-line of type func%(s64, s64, s64%) %-> bool$',
+      set_provider('#{on_line: "bad-line"}'))
+    matches('The plugin exports no function on%-line of type func%(s64%) %-> bool$',
+      set_provider('#{on_start: "on-line"}'))
+    command('redraw!')
+    eq(0, lines())
+  end)
+
+  it('can only be set by plugins', function()
+    eq('Can only be called from WASM plugins',
+      pcall_err(meths.wasm_set_decoration_provider, ns, { on_line = 'on-line' }))
+    eq('Can only be called from decoration providers',
+      pcall_err(meths.wasm_add_ephemeral_hl, ns, 0, 0, 0, 1, hl, 4096))
+  end)
+end)
@@ -0,0 +1,75 @@
+-- Tests for WASM plugins importing interfaces exported by other plugins.
+local helpers = require('test.functional.helpers')(after_each)
+
+local NIL = helpers.NIL
+local clear = helpers.clear
+local command = helpers.command
+local eq = helpers.eq
+local exec_capture = helpers.exec_capture
+local matches = helpers.matches
+local meths = helpers.meths
+local pcall_err = helpers.pcall_err
+local read_file = helpers.read_file
+local write_file = helpers.write_file
+
+-- Exports the `test:wasm/exec` interface, whose `exec` function executes an Ex command.
+local provider = 'test/functional/fixtures/wasm/exec.wat'
+-- Imports `test:wasm/exec`, and executes the argument of its `run` function with `exec`.
+local client = 'test/functional/fixtures/wasm/exec_client.wat'
+
+describe('WASM plugin', function()
+  before_each(clear)
+
+  local function run(instance, cmd)
+    return meths.wasm_call_func(instance, 'run', { cmd })
+  end
+
+  it('can import an interface exported by another plugin', function()
+    meths.wasm_load(provider)
+    local instance = meths.wasm_load(client)
+    eq(NIL, run(instance, 'let g:called = 1'))
+    eq(1, meths.get_var('called'))
+    matches('E492: Not an editor command: foo', run(instance, 'foo'))
+  end)
+
+  it('calls the exporting plugin with its own channel', function()
+    meths.wasm_load(provider)
+    local instance = meths.wasm_load(client)
+    eq(NIL, run(instance, 'command WasmTest echo'))
+    matches('Last set from WASM plugin .*exec%.wat', exec_capture('verbose command WasmTest'))
+  end)
+
+  it('fails to load when no loaded plugin exports an import', function()
+    matches('The plugin imports `test:wasm/exec`, which is not exported by any loaded plugin',
+      pcall_err(meths.wasm_load, client))
+  end)
+
+  it('is loaded from runtimepath after the plugin exporting its imports', function()
+    local env = { XDG_STATE_HOME = 'Xstate' }
+    helpers.mkdir_p('Xrtp/plugin')
+    finally(function()
+      helpers.rmdir('Xrtp')
+      helpers.rmdir('Xstate')
+    end)
+    -- The client sorts before the provider.
+    write_file('Xrtp/plugin/a_client.wasm', read_file(client))
+    write_file('Xrtp/plugin/b_exec.wasm', read_file(provider))
+    clear{env=env}
+    command('WasmTrust Xrtp/plugin/a_client.wasm')
+    command('WasmTrust Xrtp/plugin/b_exec.wasm')
+
+    clear{args={'--cmd', 'set rtp^=Xrtp loadplugins'}, env=env}
+    local instances = meths.wasm_get_runtime_info().instances
+    eq(2, #instances)
+    matches('b_exec%.wasm$', instances[1].source)
+    matches('a_client%.wasm$', instances[2].source)
+    eq(NIL, run(instances[2].id, 'let g:called = 1'))
+    eq(1, meths.get_var('called'))
+  end)
+
+  it('fails to load when an interface is already exported', function()
+    meths.wasm_load(provider)
+    matches('The plugin exports `test:wasm/exec`, which is already exported by instance %d+',
+      pcall_err(meths.wasm_load, provider))
+  end)
+end)
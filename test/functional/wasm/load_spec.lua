@@ -0,0 +1,74 @@
+-- Tests for loading WASM plugins.
+local helpers = require('test.functional.helpers')(after_each)
+
+local NIL = helpers.NIL
+local clear = helpers.clear
+local command = helpers.command
+local eq = helpers.eq
+local exec_capture = helpers.exec_capture
+local matches = helpers.matches
+local meths = helpers.meths
+local pcall_err = helpers.pcall_err
+local read_file = helpers.read_file
+
+describe('nvim_wasm_load_bytes', function()
+  before_each(clear)
+
+  it('loads a component in the text format', function()
+    local wat = read_file('test/functional/fixtures/wasm/command.wat')
+    local instance = meths.wasm_load_bytes(wat, { name = 'command' })
+    eq(NIL, meths.wasm_call_func(instance, 'run', { 'command WasmTest echo' }))
+    matches('Last set from WASM plugin command', exec_capture('verbose command WasmTest'))
+  end)
+
+  it('loads a core module', function()
+    eq('number', type(meths.wasm_load_bytes('(module)', {})))
+  end)
+
+  it('checks the version of the nvim:api package imported by the plugin', function()
+    local wat = read_file('test/functional/fixtures/wasm/command.wat')
+    local api_version = meths.wasm_get_runtime_info().api_version
+    local function with_version(version)
+      return (wat:gsub('nvim%-api@[%d.]+', 'nvim-api@' .. version))
+    end
+
+    -- Plugins built against an older API level are linked to the current package.
+    local instance = meths.wasm_load_bytes(with_version('1.0.0'), { name = 'command' })
+    eq(NIL, meths.wasm_call_func(instance, 'run', { 'let g:called = 1' }))
+    eq(1, meths.get_var('called'))
+
+    eq(('Failed to load the WASM plugin command: The plugin imports `nvim:api/nvim-api@0.10.0`, '
+      .. 'from a `nvim:api` package this Nvim does not support anymore. Rebuild it against '
+      .. 'nvim:api@%s (API level %d)'):format(api_version, meths.wasm_get_runtime_info().api_level),
+      pcall_err(meths.wasm_load_bytes, with_version('0.10.0'), { name = 'command' }))
+    matches('The plugin imports `nvim:api/nvim%-api@1%.999%.0`, but this Nvim only provides '
+      .. 'nvim:api@' .. api_version:gsub('%.', '%%.'),
+      pcall_err(meths.wasm_load_bytes, with_version('1.999.0'), { name = 'command' }))
+  end)
+
+  it('reports errors with the name of the plugin', function()
+    matches('Failed to load the WASM plugin broken',
+      pcall_err(meths.wasm_load_bytes, '(component', { name = 'broken' }))
+    matches('Failed to load the WASM plugin %[bytes%]',
+      pcall_err(meths.wasm_load_bytes, '(component', {}))
+    eq("Invalid 'name': expected String, got Integer",
+      pcall_err(meths.wasm_load_bytes, '(component)', { name = 1 }))
+  end)
+end)
+
+describe('nvim_wasm_load', function()
+  before_each(clear)
+
+  it('lets plugins call Nvim while they are instantiated', function()
+    local plugin = 'test/functional/fixtures/wasm/start.wat'
+    local instance = meths.wasm_load(plugin)
+    local autocmds = meths.get_autocmds({ event = 'User', pattern = 'WasmStart' })
+    eq(1, #autocmds)
+    eq('let g:wasm_start = 1', autocmds[1].command)
+    matches('Last set from WASM plugin .*start%.wat %(channel id %d+%)',
+      exec_capture('verbose autocmd User WasmStart'))
+    command('doautocmd User WasmStart')
+    eq(1, meths.get_var('wasm_start'))
+    eq(instance + 1, meths.wasm_load(plugin))
+  end)
+end)
@@ -0,0 +1,70 @@
+-- Tests for the `log` interface of WASM plugins.
+local helpers = require('test.functional.helpers')(after_each)
+
+local clear = helpers.clear
+local eq = helpers.eq
+local exec_lua = helpers.exec_lua
+local is_os = helpers.is_os
+local matches = helpers.matches
+local meths = helpers.meths
+local pathsep = helpers.get_pathsep()
+local pcall_err = helpers.pcall_err
+local read_file = helpers.read_file
+
+describe('WASM log', function()
+  local xstate = 'Xstate'
+  local log_file = table.concat(
+    { xstate, is_os('win') and 'nvim-data' or 'nvim', 'wasm', 'log.log' }, pathsep)
+  -- The `run` function of this plugin logs its argument as a warning.
+  local plugin = 'test/functional/fixtures/wasm/log.wat'
+  local id
+
+  before_each(function()
+    clear{env={XDG_STATE_HOME=xstate}}
+    id = meths.wasm_load(plugin)
+  end)
+
+  after_each(function()
+    helpers.rmdir(xstate)
+  end)
+
+  local function run(message)
+    meths.wasm_call_func(id, 'run', { message })
+  end
+
+  it('writes entries to the log file of the plugin', function()
+    run('first')
+    run('second entry')
+    local entries = read_file(log_file)
+    matches('^WRN %d+%-%d+%-%d+T%d+:%d+:%d+%.%d+ first length=5\n'
+      .. 'WRN %d+%-%d+%-%d+T%d+:%d+:%d+%.%d+ second entry length=12\n$', entries)
+  end)
+
+  it('drops entries below the log level', function()
+    meths.wasm_set_log({ level = exec_lua('return vim.log.levels.ERROR') })
+    run('dropped')
+    eq(nil, read_file(log_file))
+    meths.wasm_set_log({ level = exec_lua('return vim.log.levels.DEBUG') })
+    run('kept')
+    matches('^WRN .* kept length=4\n$', read_file(log_file))
+  end)
+
+  it('can show warnings with vim.notify()', function()
+    exec_lua([[
+      _G.notified = {}
+      vim.notify = function(msg, level)
+        table.insert(_G.notified, { msg, level })
+      end
+    ]])
+    run('not shown')
+    meths.wasm_set_log({ notify = true })
+    run('shown')
+    eq({ { 'log: shown', exec_lua('return vim.log.levels.WARN') } }, exec_lua('return _G.notified'))
+  end)
+
+  it('validates the settings', function()
+    eq("Invalid 'level': out of range", pcall_err(meths.wasm_set_log, { level = 6 }))
+    eq("Invalid 'notify': expected Boolean, got Integer",
+      pcall_err(meths.wasm_set_log, { notify = 1 }))
+  end)
+end)
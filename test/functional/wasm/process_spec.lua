@@ -0,0 +1,139 @@
+-- Tests for the `process` interface of WASM plugins.
+local helpers = require('test.functional.helpers')(after_each)
+
+local NIL = helpers.NIL
+local clear = helpers.clear
+local eq = helpers.eq
+local funcs = helpers.funcs
+local is_os = helpers.is_os
+local matches = helpers.matches
+local meths = helpers.meths
+local pathsep = helpers.get_pathsep()
+local pcall_err = helpers.pcall_err
+local retry = helpers.retry
+local skip = helpers.skip
+local sleep = helpers.sleep
+
+-- `spawn(argv, cwd, env, flags)` spawns a process, where `flags` opens stdin (1), stdout (2) and
+-- stderr (4), and returns its ID or an error message. `write(id, data)`, `close-stdin(id)` and
+-- `kill(id)` return nil or an error message. The events of the process are appended to
+-- `g:wasm_process_events`.
+local plugin = 'test/functional/fixtures/wasm/process.wat'
+
+describe('WASM process', function()
+  local xstate = 'Xstate'
+  local id
+
+  before_each(function()
+    clear{env={XDG_STATE_HOME=xstate}}
+    meths.set_var('wasm_process_events', {})
+    meths.wasm_trust(plugin, { capabilities = { 'process' } })
+    id = meths.wasm_load(plugin)
+  end)
+
+  after_each(function()
+    helpers.rmdir(xstate)
+    helpers.rmdir('Xwasm_process')
+  end)
+
+  local function spawn(instance, argv, cwd, env, flags)
+    return meths.wasm_call_func(instance, 'spawn', { argv, cwd, env, flags })
+  end
+
+  local function events()
+    return meths.get_var('wasm_process_events')
+  end
+
+  --- Waits for the exit event of the process, and returns the events.
+  local function wait_exit()
+    retry(nil, nil, function()
+      local last = events()[#events()]
+      eq('exit', last and last[2])
+    end)
+    return events()
+  end
+
+  it('is only imported by plugins granted the process capability', function()
+    meths.wasm_trust(plugin, {})
+    matches('requires the `process` capability. Trust it with `:WasmTrust %+%+allow=process`',
+      pcall_err(meths.wasm_load, plugin))
+  end)
+
+  it('sends the output and exit status to the plugin', function()
+    skip(is_os('win'))
+    local process = spawn(id, { 'sh', '-c', 'echo "$WASM_VAR"; echo error >&2; exit 3' }, NIL,
+      { WASM_VAR = 'value' }, 2 + 4)
+    eq('number', type(process))
+    local stdout = vim.tbl_filter(function(event)
+      return event[2] ~= 'stderr'
+    end, wait_exit())
+    eq({
+      { process, 'stdout', 'value\n' },
+      { process, 'stdout', '' },
+      { process, 'exit', 3 },
+    }, stdout)
+    local stderr = vim.tbl_filter(function(event)
+      return event[2] == 'stderr'
+    end, events())
+    eq({ { process, 'stderr', 'error\n' }, { process, 'stderr', '' } }, stderr)
+  end)
+
+  it('writes to the stdin of the process', function()
+    skip(is_os('win'))
+    local process = spawn(id, { 'cat' }, NIL, NIL, 1 + 2)
+    eq(NIL, meths.wasm_call_func(id, 'write', { process, 'hello' }))
+    eq(NIL, meths.wasm_call_func(id, 'close-stdin', { process }))
+    eq({
+      { process, 'stdout', 'hello' },
+      { process, 'stdout', '' },
+      { process, 'exit', 0 },
+    }, wait_exit())
+    eq("Can't send data to closed stream",
+      meths.wasm_call_func(id, 'write', { process, 'hello' }))
+  end)
+
+  it('runs the process in its working directory', function()
+    skip(is_os('win'))
+    helpers.mkdir('Xwasm_process')
+    local process = spawn(id, { 'pwd' }, 'Xwasm_process', NIL, 2)
+    local output = wait_exit()[1]
+    eq({ process, 'stdout' }, { output[1], output[2] })
+    matches(pathsep .. 'Xwasm_process\n$', output[3])
+  end)
+
+  it('is killed by the plugin', function()
+    skip(is_os('win'))
+    local process = spawn(id, { 'sleep', '10' }, NIL, NIL, 0)
+    eq(NIL, meths.wasm_call_func(id, 'kill', { process }))
+    eq({ { process, 'exit', 143 } }, wait_exit())
+  end)
+
+  it('is not used by another plugin', function()
+    skip(is_os('win'))
+    local other = meths.wasm_load(plugin)
+    local process = spawn(id, { 'cat' }, NIL, NIL, 1)
+    eq(('Invalid process id: %d'):format(process),
+      meths.wasm_call_func(other, 'write', { process, 'hello' }))
+    eq(('Invalid process id: %d'):format(process),
+      meths.wasm_call_func(other, 'kill', { process }))
+    eq({ -1 }, funcs.jobwait({ process }, 0))
+  end)
+
+  it('is killed when the plugin is unloaded', function()
+    skip(is_os('win'))
+    local process = spawn(id, { 'sleep', '10' }, NIL, NIL, 2)
+    meths.wasm_unload(id)
+    eq({ 143 }, funcs.jobwait({ process }, 1000))
+    sleep(50)
+    eq({}, events())
+  end)
+
+  it('reports errors', function()
+    eq("'argv' is empty", spawn(id, {}, NIL, NIL, 0))
+    eq("Invalid 'argv': 'Xnonexistent' is not executable",
+      spawn(id, { 'Xnonexistent' }, NIL, NIL, 0))
+    eq("Invalid 'cwd': 'Xnonexistent' is not a directory",
+      spawn(id, { helpers.nvim_prog }, 'Xnonexistent', NIL, 0))
+    eq('Invalid process id: 0', meths.wasm_call_func(id, 'kill', { 0 }))
+  end)
+end)
@@ -0,0 +1,82 @@
+-- Tests for profiling WASM plugins.
+local helpers = require('test.functional.helpers')(after_each)
+
+local clear = helpers.clear
+local command = helpers.command
+local eq = helpers.eq
+local matches = helpers.matches
+local meths = helpers.meths
+local ok = helpers.ok
+local pcall_err = helpers.pcall_err
+local read_file = helpers.read_file
+
+describe(':WasmProfile', function()
+  local plugin = 'test/functional/fixtures/wasm/command.wat'
+  local report = 'Xwasm_profile'
+  local id
+
+  before_each(function()
+    clear()
+    id = meths.wasm_load(plugin)
+  end)
+
+  after_each(function()
+    os.remove(report)
+  end)
+
+  local function run(cmd)
+    return meths.wasm_call_func(id, 'run', { cmd })
+  end
+
+  it('records calls to and from plugins', function()
+    command('WasmProfile start')
+    run('let g:x = 1')
+    run('let g:x = 2')
+    command('WasmProfile stop')
+    run('let g:x = 3')
+
+    local profile = meths.wasm_profile('dump')[1]
+    eq(id, profile.id)
+    eq('file ' .. plugin, profile.source)
+    eq(2, profile.exports.run.count)
+    eq(2, profile.api_calls.nvim_command.count)
+    ok(profile.exports.run.time >= profile.api_calls.nvim_command.time)
+
+    command('WasmProfile dump ' .. report)
+    local lines = read_file(report)
+    matches('^Instance %d+: file .*command%.wat\n', lines)
+    matches('\n +2 +[%d.]+ +run\n', lines)
+    matches('\n +2 +[%d.]+ +nvim_command\n', lines)
+  end)
+
+  it('clears the profiles when starting', function()
+    command('WasmProfile start')
+    run('let g:x = 1')
+    command('WasmProfile start')
+    local profile = meths.wasm_profile('dump')[1]
+    eq(nil, profile.exports.run)
+    eq({}, profile.api_calls)
+  end)
+
+  it('records the fuel consumed by plugins with NVIM_WASM_PROFILER=fuel', function()
+    command('WasmProfile start')
+    run('let g:x = 1')
+    eq(nil, meths.wasm_profile('dump')[1].fuel)
+
+    clear({ env = { NVIM_WASM_PROFILER = 'fuel' } })
+    id = meths.wasm_load(plugin)
+    command('WasmProfile start')
+    run('let g:x = 1')
+    local profile = meths.wasm_profile('dump')[1]
+    ok(profile.fuel > 0)
+    command('WasmProfile start')
+    eq(0, meths.wasm_profile('dump')[1].fuel)
+
+    command('WasmProfile dump ' .. report)
+    matches('^Instance %d+: file .*command%.wat, 0 fuel consumed\n', read_file(report))
+  end)
+
+  it('reports errors', function()
+    eq("Invalid 'action': 'pause'", pcall_err(meths.wasm_profile, 'pause'))
+  end)
+end)
@@ -0,0 +1,41 @@
+-- Tests for nvim_wasm_get_runtime_info() and :checkhealth vim.wasm.
+local helpers = require('test.functional.helpers')(after_each)
+
+local clear = helpers.clear
+local command = helpers.command
+local eq = helpers.eq
+local matches = helpers.matches
+local meths = helpers.meths
+
+describe('nvim_wasm_get_runtime_info', function()
+  local plugin = 'test/functional/fixtures/wasm/command.wat'
+
+  before_each(clear)
+
+  it('reports the runtime', function()
+    local info = meths.wasm_get_runtime_info()
+    matches('^%d+%.%d+%.%d+$', info.wasmtime_version)
+    eq('number', type(info.api_level))
+    eq(('1.%d.0'):format(info.api_level), info.api_version)
+    eq(true, vim.tbl_contains(info.proposals, 'component-model'))
+    eq({ enabled = false }, info.cache)
+    eq({}, info.instances)
+  end)
+
+  it('reports loaded instances', function()
+    local id = meths.wasm_load(plugin)
+    local instances = meths.wasm_get_runtime_info().instances
+    eq(1, #instances)
+    eq(id, instances[1].id)
+    eq('file ' .. plugin, instances[1].source)
+    eq('number', type(instances[1].memory))
+  end)
+
+  it('is used by :checkhealth', function()
+    meths.wasm_load(plugin)
+    command('checkhealth vim.wasm')
+    local report = table.concat(meths.buf_get_lines(0, 0, -1, false), '\n')
+    matches('WASM runtime', report)
+    matches('Instance %d+: .*command%.wat', report)
+  end)
+end)
@@ -0,0 +1,167 @@
+-- Tests for the `storage` interface of WASM plugins.
+local helpers = require('test.functional.helpers')(after_each)
+
+local NIL = helpers.NIL
+local clear = helpers.clear
+local eq = helpers.eq
+local exec_lua = helpers.exec_lua
+local funcs = helpers.funcs
+local is_os = helpers.is_os
+local matches = helpers.matches
+local meths = helpers.meths
+local pathsep = helpers.get_pathsep()
+local pcall_err = helpers.pcall_err
+local read_file = helpers.read_file
+local write_file = helpers.write_file
+
+-- `set(key, value)` and `set-bytes(key, data)` return nil or an error message. `get(key)` returns
+-- nil, the object, `['bytes', data]` or `['error', message]`. `delete(key)` returns whether the key
+-- was set and `list()` the keys. The plugin declares the name `storage-test`.
+local plugin = 'test/functional/fixtures/wasm/storage.wat'
+
+describe('WASM storage', function()
+  local xdata = 'Xdata'
+  local stores = table.concat({ xdata, is_os('win') and 'nvim-data' or 'nvim', 'wasm' }, pathsep)
+  local id
+
+  before_each(function()
+    clear{env={XDG_DATA_HOME=xdata}}
+    id = meths.wasm_load(plugin)
+  end)
+
+  after_each(function()
+    helpers.rmdir(xdata)
+  end)
+
+  local function call(instance, func, ...)
+    return meths.wasm_call_func(instance, func, { ... })
+  end
+
+  it('keeps values across sessions', function()
+    eq(NIL, call(id, 'set', 'mru', { 'a.txt', 'b.txt' }))
+    eq(NIL, call(id, 'set', 'ranking', { ['a.txt'] = 2, ['b.txt'] = 0.5 }))
+    eq(NIL, call(id, 'set', 'enabled', true))
+    eq(NIL, call(id, 'set-bytes', 'cache', 'raw\0bytes'))
+    eq({ 'a.txt', 'b.txt' }, call(id, 'get', 'mru'))
+
+    clear{env={XDG_DATA_HOME=xdata}}
+    id = meths.wasm_load(plugin)
+    eq({ 'cache', 'enabled', 'mru', 'ranking' }, call(id, 'list'))
+    eq({ 'a.txt', 'b.txt' }, call(id, 'get', 'mru'))
+    eq({ ['a.txt'] = 2, ['b.txt'] = 0.5 }, call(id, 'get', 'ranking'))
+    eq(true, call(id, 'get', 'enabled'))
+    eq({ 'bytes', 'raw\0bytes' }, call(id, 'get', 'cache'))
+    eq(NIL, call(id, 'get', 'missing'))
+  end)
+
+  it('replaces and deletes values', function()
+    eq(NIL, call(id, 'set', 'key', 'old'))
+    eq(NIL, call(id, 'set', 'key', 'new'))
+    eq('new', call(id, 'get', 'key'))
+    eq(true, call(id, 'delete', 'key'))
+    eq(false, call(id, 'delete', 'key'))
+    eq(NIL, call(id, 'get', 'key'))
+    eq({}, call(id, 'list'))
+  end)
+
+  it('stores values under the name declared by the plugin', function()
+    meths.wasm_unload(id)
+    -- The declared name wins over the name given when loading.
+    local renamed = meths.wasm_load_bytes(read_file(plugin), { name = 'renamed' })
+    eq(NIL, call(renamed, 'set-bytes', 'key', 'value'))
+    -- Keys are hex-encoded in file names, bytes are stored after a tag byte. The owner of the
+    -- store is recorded in a file which is not a key.
+    local store = stores .. pathsep .. 'storage-test'
+    eq({ '.owner', '6b6579' }, funcs.readdir(store))
+    eq('bvalue', read_file(store .. pathsep .. '6b6579'))
+    eq('memory', read_file(store .. pathsep .. '.owner'))
+    eq({ 'key' }, call(renamed, 'list'))
+  end)
+
+  it('binds a store to the plugin which first writes to it', function()
+    local copy = 'Xstorage.wat'
+    write_file(copy, read_file(plugin))
+    finally(function()
+      os.remove(copy)
+    end)
+    eq(NIL, call(id, 'set', 'key', 'mine'))
+    local owner = exec_lua('return vim.uv.fs_realpath(...)', plugin)
+    eq(owner, read_file(table.concat({ stores, 'storage-test', '.owner' }, pathsep)))
+
+    -- Plugins from elsewhere cannot take the name of a loaded plugin.
+    local used = ('The plugin name `storage%%-test` is used by WASM instance %d, loaded from file ')
+      :format(id) .. vim.pesc(plugin)
+    matches(used, pcall_err(meths.wasm_load, copy))
+    matches(used, pcall_err(meths.wasm_load_bytes, read_file(plugin), {}))
+
+    -- Nor its store once it is unloaded.
+    meths.wasm_unload(id)
+    local other = meths.wasm_load(copy)
+    local message = 'The store .*storage%-test belongs to the plugin loaded from ' .. vim.pesc(owner)
+    matches(message, call(other, 'set', 'key', 'theirs'))
+    matches(message, call(other, 'get', 'key')[2])
+    meths.wasm_unload(other)
+    eq('mine', call(meths.wasm_load(plugin), 'get', 'key'))
+  end)
+
+  it('does not let WASM plugins name the plugins they load', function()
+    -- `run` executes its argument as an Ex command and returns its error message, or nil.
+    local command = meths.wasm_load('test/functional/fixtures/wasm/command.wat')
+    local message = 'WASM plugins cannot load plugins with a name: it could be the name of another '
+      .. 'plugin'
+    matches(message, meths.wasm_call_func(command, 'run', {
+      'call nvim_wasm_load_bytes("(component)", #{name: "storage-test"})',
+    }))
+    matches(message, meths.wasm_call_func(command, 'run', {
+      ('call nvim_wasm_load_bytes(readblob("%s"))'):format(plugin),
+    }))
+  end)
+
+  it('keeps the stores of plugins apart', function()
+    local wat = read_file(plugin):gsub('%(@custom "nvim%-plugin%-name" "storage%-test"%)', '')
+    local other = meths.wasm_load_bytes(wat, { name = 'other' })
+    eq(NIL, call(id, 'set', 'key', 'mine'))
+    eq(NIL, call(other, 'get', 'key'))
+    eq(NIL, call(other, 'set', 'key', 'theirs'))
+    eq('mine', call(id, 'get', 'key'))
+    eq('theirs', call(other, 'get', 'key'))
+    eq({ 'other', 'storage-test' }, funcs.readdir(stores))
+  end)
+
+  it('gives no store to plugins without a name', function()
+    local wat = read_file(plugin):gsub('%(@custom "nvim%-plugin%-name" "storage%-test"%)', '')
+    local message = 'The plugin has no store: it has no name, or its name is not a valid '
+      .. 'directory name, see `nvim-plugin-name`'
+    local unnamed = meths.wasm_load_bytes(wat, {})
+    eq(message, call(unnamed, 'set', 'key', 1))
+    eq({ 'error', message }, call(unnamed, 'get', 'key'))
+    local dots = meths.wasm_load_bytes(wat, { name = '..' })
+    eq(message, call(dots, 'set', 'key', 1))
+    eq(0, funcs.isdirectory(stores))
+  end)
+
+  it('enforces the quota of the plugin', function()
+    local mib = 1024 * 1024
+    eq(NIL, call(id, 'set-bytes', 'a', ('x'):rep(9 * mib)))
+    eq('Cannot set the key `b`: the store would exceed its quota of 16 MiB',
+      call(id, 'set-bytes', 'b', ('x'):rep(8 * mib)))
+    eq(NIL, call(id, 'get', 'b'))
+    -- The replaced value does not count.
+    eq(NIL, call(id, 'set-bytes', 'a', ('y'):rep(9 * mib)))
+    eq({ 'a' }, call(id, 'list'))
+  end)
+
+  it('reports errors', function()
+    eq({ 'error', 'Key is empty' }, call(id, 'get', ''))
+    eq('Key is longer than 100 bytes', call(id, 'set', ('k'):rep(101), 1))
+    eq(NIL, call(id, 'set', 'key', 1))
+    local file = table.concat({ stores, 'storage-test', '6b6579' }, pathsep)
+    write_file(file, 'corrupted')
+    local result = call(id, 'get', 'key')
+    eq('error', result[1])
+    matches('^The value of the key `key` is corrupted: .*6b6579$', result[2])
+    matches('Failed to load the WASM plugin broken: Invalid plugin name in the nvim%-plugin%-name '
+      .. 'section: "%.%./x"', pcall_err(meths.wasm_load_bytes,
+        read_file(plugin):gsub('"storage%-test"', '"../x"'), { name = 'broken' }))
+  end)
+end)
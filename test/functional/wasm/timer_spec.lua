@@ -0,0 +1,195 @@
+-- Tests for the `timer` interface of WASM plugins and for unloading plugins.
+local helpers = require('test.functional.helpers')(after_each)
+local Screen = require('test.functional.ui.screen')
+
+local NIL = helpers.NIL
+local clear = helpers.clear
+local command = helpers.command
+local eq = helpers.eq
+local exec_lua = helpers.exec_lua
+local funcs = helpers.funcs
+local matches = helpers.matches
+local meths = helpers.meths
+local pcall_err = helpers.pcall_err
+local read_file = helpers.read_file
+local retry = helpers.retry
+local sleep = helpers.sleep
+
+-- `start(ms, repeat, callback_id)` starts a timer whose `on-timer` hook appends the callback ID to
+-- `g:wasm_timer_calls`, and stops the timer when the callback ID is 0. `stop(id)` stops a timer.
+local plugin = 'test/functional/fixtures/wasm/timer.wat'
+
+describe('WASM timer', function()
+  local id
+
+  before_each(function()
+    clear()
+    meths.set_var('wasm_timer_calls', {})
+    id = meths.wasm_load(plugin)
+  end)
+
+  local function start(instance, ms, repeat_count, callback_id)
+    return meths.wasm_call_func(instance, 'start', { ms, repeat_count, callback_id })
+  end
+
+  local function calls()
+    return meths.get_var('wasm_timer_calls')
+  end
+
+  --- Waits for the hook to be called, then checks that the timer is not called anymore.
+  local function wait_stopped()
+    retry(nil, nil, function()
+      assert(#calls() > 0)
+    end)
+    local count = #calls()
+    sleep(50)
+    eq(count, #calls())
+  end
+
+  it('calls the hook of the plugin `repeat` times', function()
+    eq('number', type(start(id, 10, 3, 7)))
+    retry(nil, nil, function()
+      eq({ 7, 7, 7 }, calls())
+    end)
+    sleep(50)
+    eq({ 7, 7, 7 }, calls())
+  end)
+
+  it('stops when the hook returns true', function()
+    start(id, 10, -1, 0)
+    wait_stopped()
+    eq({ 0 }, calls())
+  end)
+
+  it('is stopped by the plugin', function()
+    local timer = start(id, 10, -1, 5)
+    retry(nil, nil, function()
+      assert(#calls() > 0)
+    end)
+    meths.wasm_call_func(id, 'stop', { timer })
+    wait_stopped()
+  end)
+
+  it('is not stopped by another plugin', function()
+    local other = meths.wasm_load(plugin)
+    local timer = start(id, 10, -1, 5)
+    meths.wasm_call_func(other, 'stop', { timer })
+    retry(nil, nil, function()
+      assert(#calls() >= 3)
+    end)
+  end)
+
+  it('is stopped when the plugin is unloaded', function()
+    start(id, 10, -1, 5)
+    retry(nil, nil, function()
+      assert(#calls() > 0)
+    end)
+    meths.wasm_unload(id)
+    wait_stopped()
+  end)
+end)
+
+describe('nvim_wasm_unload', function()
+  before_each(clear)
+
+  it('unloads a plugin without reusing its instance ID', function()
+    local id = meths.wasm_load(plugin)
+    meths.wasm_unload(id)
+    eq(('Cannot find instance with ID = %d'):format(id),
+      pcall_err(meths.wasm_call_func, id, 'start', { 10, 1, 1 }))
+    eq(('Cannot find instance with ID = %d'):format(id), pcall_err(meths.wasm_unload, id))
+    eq(id + 1, meths.wasm_load(plugin))
+  end)
+
+  it('does not unload a running plugin', function()
+    -- The `run` function of this plugin executes its argument as an Ex command, and returns the
+    -- error message of the command, or nil.
+    local id = meths.wasm_load('test/functional/fixtures/wasm/command.wat')
+    matches(('Cannot unload WASM instance %d while it is running'):format(id),
+      meths.wasm_call_func(id, 'run', { ('call nvim_wasm_unload(%d)'):format(id) }))
+    eq(NIL, meths.wasm_call_func(id, 'run', { 'let g:called = 1' }))
+  end)
+
+  it('does not unload a plugin while another one is instantiated', function()
+    local id = meths.wasm_load('test/functional/fixtures/wasm/command.wat')
+    -- The start function of this plugin runs the command, 43 bytes long, while it is instantiated.
+    local cmd = ('try | call nvim_wasm_unload(%d) | catch | let g:err = v:exception | endtry')
+      :format(id)
+    local wat = read_file('test/functional/fixtures/wasm/start.wat')
+      :gsub('autocmd User WasmStart let g:wasm_start = 1', cmd)
+      :gsub('%(i32%.const 43%)', ('(i32.const %d)'):format(#cmd))
+    meths.wasm_load_bytes(wat, { name = 'unload' })
+    matches('Cannot unload a WASM plugin while another one is being instantiated$',
+      meths.get_var('err'))
+    meths.wasm_unload(id)
+  end)
+
+  it('removes the callbacks set by the plugin', function()
+    local screen = Screen.new(20, 4)
+    screen:attach()
+    -- The `run` function of these plugins executes its argument as an Ex command, and returns
+    -- the error message of the command, or nil.
+    local id = meths.wasm_load('test/functional/fixtures/wasm/buffer.wat')
+    local function run(cmd)
+      eq(NIL, meths.wasm_call_func(id, 'run', { cmd }))
+    end
+    run('call nvim_wasm_buf_attach(0, #{on_lines: "on-lines", on_detach: "on-detach"})')
+    run('autocmd User WasmUnload let g:called = 1')
+    run('nnoremap <buffer> x <Cmd>let g:called = 1<CR>')
+    run('inoreabbrev wasmab WASM')
+    run('command WasmCommand let g:called = 1')
+    run('command -buffer WasmBufCommand let g:called = 1')
+    local decoration = meths.wasm_load('test/functional/fixtures/wasm/decoration.wat')
+    local ns = meths.create_namespace('wasm')
+    meths.wasm_call_func(decoration, 'setup', { ns, funcs.hlID('ErrorMsg') })
+    eq(NIL, meths.wasm_call_func(decoration, 'run', {
+      ('call nvim_wasm_set_decoration_provider(%d, #{on_line: "on-line"})'):format(ns),
+    }))
+
+    meths.wasm_unload(id)
+    meths.wasm_unload(decoration)
+    eq({}, meths.get_autocmds({ event = 'User', pattern = 'WasmUnload' }))
+    eq('', funcs.maparg('x', 'n'))
+    eq('', funcs.maparg('wasmab', 'i', true))
+    eq(0, funcs.exists(':WasmCommand'))
+    eq(0, funcs.exists(':WasmBufCommand'))
+    -- Nothing calls the unloaded plugins, which would fail.
+    meths.buf_set_lines(0, 0, -1, true, { 'foo', 'bar' })
+    command('silent doautocmd User WasmUnload')
+    screen:expect([[
+      ^foo                 |
+      bar                 |
+      {1:~                   }|
+                          |
+    ]], { [1] = { bold = true, foreground = Screen.colors.Blue } })
+    eq('', meths.get_vvar('errmsg'))
+    eq(0, funcs.exists('g:called'))
+  end)
+
+  it('does not unload a plugin under textlock', function()
+    local id = meths.wasm_load(plugin)
+    exec_lua([[
+      local id = ...
+      vim.api.nvim_buf_attach(0, false, {
+        on_lines = function()
+          _G.unload_ok, _G.unload_err = pcall(vim.api.nvim_wasm_unload, id)
+          return true
+        end,
+      })
+    ]], id)
+    meths.buf_set_lines(0, 0, -1, true, { 'foo' })
+    eq({ false, 'E565: Not allowed to change text or change window' },
+      exec_lua('return { _G.unload_ok, _G.unload_err }'))
+    meths.wasm_unload(id)
+  end)
+
+  it('does not unload a plugin exporting interfaces', function()
+    local id = meths.wasm_load('test/functional/fixtures/wasm/exec.wat')
+    eq(('Cannot unload WASM instance %d, which exports `test:wasm/exec`'):format(id),
+      pcall_err(meths.wasm_unload, id))
+  end)
+
+  it('validates the instance ID', function()
+    eq("Invalid 'instance_id': -1", pcall_err(meths.wasm_unload, -1))
+  end)
+end)
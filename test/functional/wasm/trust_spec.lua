@@ -0,0 +1,129 @@
+-- Tests for the trust database of WASM plugins.
+local helpers = require('test.functional.helpers')(after_each)
+local Screen = require('test.functional.ui.screen')
+
+local clear = helpers.clear
+local command = helpers.command
+local eq = helpers.eq
+local exec_lua = helpers.exec_lua
+local feed = helpers.feed
+local feed_command = helpers.feed_command
+local funcs = helpers.funcs
+local is_os = helpers.is_os
+local matches = helpers.matches
+local meths = helpers.meths
+local pathsep = helpers.get_pathsep()
+local pcall_err = helpers.pcall_err
+local read_file = helpers.read_file
+local write_file = helpers.write_file
+
+describe(':WasmTrust', function()
+  local xstate = 'Xstate'
+  local db = table.concat({ xstate, is_os('win') and 'nvim-data' or 'nvim', 'wasm_trust' }, pathsep)
+  local plugin = 'test/functional/fixtures/wasm/command.wat'
+  local fullpath, hash
+
+  before_each(function()
+    clear{env={XDG_STATE_HOME=xstate}}
+    fullpath = exec_lua('return vim.uv.fs_realpath(...)', plugin)
+    hash = funcs.sha256(read_file(plugin))
+  end)
+
+  after_each(function()
+    helpers.rmdir(xstate)
+  end)
+
+  it('denies, allows and removes plugins', function()
+    command('WasmTrust ++deny ' .. plugin)
+    eq(hash .. ' deny ' .. fullpath .. '\n', read_file(db))
+    matches('Failed to load the WASM file .*: The plugin is denied in the WASM trust database',
+      pcall_err(meths.wasm_load, plugin))
+
+    command('WasmTrust ' .. plugin)
+    eq(hash .. ' allow ' .. fullpath .. '\n', read_file(db))
+    eq('number', type(meths.wasm_load(plugin)))
+
+    command('WasmTrust ++remove ' .. plugin)
+    eq('', read_file(db))
+    eq('number', type(meths.wasm_load(plugin)))
+  end)
+
+  it('identifies plugins by their hash', function()
+    command('WasmTrust ++deny ' .. plugin)
+    matches('Failed to load the WASM plugin command: The plugin is denied',
+      pcall_err(meths.wasm_load_bytes, read_file(plugin), { name = 'command' }))
+    eq('number', type(meths.wasm_load_bytes('(component)', { name = 'command' })))
+  end)
+
+  it('grants capabilities to allowed plugins', function()
+    local process = 'test/functional/fixtures/wasm/process.wat'
+    local process_path = exec_lua('return vim.uv.fs_realpath(...)', process)
+    local process_hash = funcs.sha256(read_file(process))
+    matches('The plugin imports `nvim:api/process@.*`, which requires the `process` capability',
+      pcall_err(meths.wasm_load, process))
+
+    command('WasmTrust ++allow=process ' .. process)
+    eq(process_hash .. ' allow,process ' .. process_path .. '\n', read_file(db))
+    eq('number', type(meths.wasm_load(process)))
+
+    command('WasmTrust ' .. process)
+    eq(process_hash .. ' allow ' .. process_path .. '\n', read_file(db))
+    matches('requires the `process` capability', pcall_err(meths.wasm_load, process))
+  end)
+
+  it('asks whether to trust the plugins found in runtimepath', function()
+    local screen = Screen.new(80, 8)
+    screen:attach()
+    local rtp_plugin = 'Xrtp/plugin/start.wasm'
+    helpers.mkdir_p('Xrtp/plugin')
+    finally(function()
+      helpers.rmdir('Xrtp')
+    end)
+    local wat = read_file('test/functional/fixtures/wasm/start.wat')
+    write_file(rtp_plugin, wat)
+    command('set rtp^=Xrtp')
+    local rtp_path = exec_lua('return vim.uv.fs_realpath(...)', rtp_plugin)
+
+    feed_command('runtime! plugin/*.wasm')
+    screen:expect({ any = 'start%.wasm is not trusted%..*%[i%]gnore, %(d%)eny, %(a%)llow: ' })
+    feed('a')
+    eq(funcs.sha256(wat) .. ' allow ' .. rtp_path .. '\n', read_file(db))
+    eq(1, #meths.get_autocmds({ event = 'User', pattern = 'WasmStart' }))
+
+    -- The plugin is not trusted anymore once it changes.
+    write_file(rtp_plugin, wat .. '\n')
+    feed_command('runtime! plugin/*.wasm')
+    screen:expect({ any = 'start%.wasm has changed since it was trusted%.' })
+    feed('d')
+    screen:expect({ any = 'The plugin is denied in the WASM trust database' })
+    eq(funcs.sha256(wat .. '\n') .. ' deny ' .. rtp_path .. '\n', read_file(db))
+    eq(1, #meths.get_autocmds({ event = 'User', pattern = 'WasmStart' }))
+
+    -- Plugins loaded by the user run unless denied.
+    command('WasmTrust ++remove ' .. rtp_plugin)
+    eq('number', type(meths.wasm_load(rtp_plugin)))
+    eq(2, #meths.get_autocmds({ event = 'User', pattern = 'WasmStart' }))
+  end)
+
+  it('cannot be changed by WASM plugins', function()
+    -- `run` executes its argument as an Ex command and returns its error message, or nil.
+    local id = meths.wasm_load(plugin)
+    matches('WASM plugins cannot change the trust database$',
+      meths.wasm_call_func(id, 'run', { ('call nvim_wasm_trust("%s", {})'):format(plugin) }))
+    matches('WASM plugins cannot change the trust database$', meths.wasm_call_func(id, 'run', {
+      ('lua vim.api.nvim_wasm_trust("%s", { action = "deny" })'):format(plugin),
+    }))
+    eq(nil, read_file(db))
+  end)
+
+  it('reports errors', function()
+    eq("Invalid 'action': 'trust'", pcall_err(meths.wasm_trust, plugin, { action = 'trust' }))
+    matches('Invalid capability: net',
+      pcall_err(meths.wasm_trust, plugin, { capabilities = { 'net' } }))
+    eq([['capabilities' requires action "allow"]],
+      pcall_err(meths.wasm_trust, plugin, { action = 'deny', capabilities = { 'process' } }))
+    matches('Failed to read the WASM file Xmissing',
+      pcall_err(meths.wasm_trust, 'Xmissing', { action = 'allow' }))
+    eq(nil, read_file(db))
+  end)
+end)
@@ -20,9 +20,7 @@ impl Plugin for MyPlugin {
         let Ok(Object::Float(ans)) = nvim_call_function("sin", &[&Object::Float(val)]) else { return Object::Nil; };
         nvim_exec2(
             &format!("echo ' => sin({val}) = {ans}'"),
-            KeysetExecOpts {
-                output: &Object::Nil,
-            },
+            KeysetExecOpts { output: None },
         )
         .unwrap();
 
@@ -0,0 +1,82 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use nvim::api::{nvim_api, nvim_keysets::KeysetWasmDecorationProvider};
+
+wit_bindgen::generate!("plugin");
+
+/// The namespace of the highlights.
+static NS_ID: AtomicI64 = AtomicI64::new(0);
+/// The highlight group of numbers.
+static HL_ID: AtomicI64 = AtomicI64::new(0);
+
+struct MyPlugin;
+
+impl Plugin for MyPlugin {
+    /// Highlights the numbers in all windows.
+    fn run(_args: Vec<Object>) -> Object {
+        std::panic::set_hook(Box::new(|panic_info| {
+            nvim_api::nvim_err_write(&format!("{panic_info}\n"));
+        }));
+
+        let ns_id = nvim_api::nvim_create_namespace("wasm-highlight");
+        NS_ID.store(ns_id, Ordering::Relaxed);
+        HL_ID.store(
+            nvim_api::nvim_get_hl_id_by_name("Number"),
+            Ordering::Relaxed,
+        );
+        nvim_api::nvim_wasm_set_decoration_provider(
+            ns_id,
+            &KeysetWasmDecorationProvider {
+                on_start: None,
+                on_buf: None,
+                on_win: Some("on-win".to_string()),
+                on_line: Some("on-line".to_string()),
+                on_end: None,
+            },
+        )
+        .unwrap();
+
+        Object::Nil
+    }
+
+    fn on_win(_winid: i64, _bufnr: i64, _toprow: i64, _botrow: i64) -> bool {
+        true
+    }
+
+    fn on_line(_winid: i64, bufnr: i64, row: i64) -> bool {
+        let Ok(lines) = nvim_api::nvim_buf_get_lines(bufnr, row, row + 1, true) else {
+            return false;
+        };
+        let Some(line) = lines.first() else {
+            return true;
+        };
+
+        let ns_id = NS_ID.load(Ordering::Relaxed);
+        let hl_id = HL_ID.load(Ordering::Relaxed);
+        let bytes = line.as_bytes();
+        let mut col = 0;
+        while col < bytes.len() {
+            if !bytes[col].is_ascii_digit() {
+                col += 1;
+                continue;
+            }
+            let start = col;
+            while col < bytes.len() && bytes[col].is_ascii_digit() {
+                col += 1;
+            }
+            let _ = nvim_api::nvim_wasm_add_ephemeral_hl(
+                ns_id,
+                row,
+                start as i64,
+                row,
+                col as i64,
+                hl_id,
+                100,
+            );
+        }
+
+        true
+    }
+}
+
+export_plugin!(MyPlugin);
@@ -11,26 +11,26 @@ impl Plugin for MyPlugin {
         }));
 
         let buf = nvim_api::nvim_create_buf(false, false).unwrap();
-        let _win = nvim_api::nvim_open_win(
+        let win = nvim_api::nvim_open_win(
             buf,
             true,
             &KeysetFloatConfig {
-                row: Object::Integer(0),
-                col: Object::Integer(0),
-                width: Object::Integer(40),
-                height: Object::Integer(40),
-                anchor: Object::Nil,
-                relative: Object::String("win".to_string()),
-                win: Object::Nil,
-                bufpos: Object::Nil,
-                external: Object::Nil,
-                focusable: Object::Nil,
-                zindex: Object::Nil,
-                border: Object::Nil,
-                title: Object::Nil,
-                title_pos: Object::Nil,
-                style: Object::Nil,
-                noautocmd: Object::Nil,
+                row: Some(0.0),
+                col: Some(0.0),
+                width: Some(40),
+                height: Some(40),
+                anchor: None,
+                relative: Some("win".to_string()),
+                win: None,
+                bufpos: None,
+                external: None,
+                focusable: None,
+                zindex: None,
+                border: None,
+                title: None,
+                title_pos: None,
+                style: None,
+                noautocmd: None,
             },
         )
         .unwrap();
@@ -42,6 +42,7 @@ impl Plugin for MyPlugin {
             &["Hello".to_string(), "From".to_string(), "WASM!".to_string()],
         )
         .unwrap();
+        nvim_api::nvim_win_set_cursor(win, (3, 0)).unwrap();
 
         Object::Nil
     }